@@ -1,23 +1,37 @@
-// app/src/ai_integration.rs - Интеграция с OpenRouter API и AI сервисами
+// app/src/ai_integration/mod.rs - Интеграция с OpenRouter API и AI сервисами
+
+pub mod provider;
+pub mod stream;
 
 use std::collections::HashMap;
 use std::time::Duration;
 use serde::{Deserialize, Serialize};
-use reqwest::{Client, header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE}};
+use reqwest::{Client, header::{HeaderValue, CONTENT_TYPE}};
 use tokio::time::timeout;
 
 use crate::error::{JarvisResult, JarvisError};
-use crate::{db, tts};
+use crate::{config, db, tts};
 use log::{info, warn};
+
+pub use self::provider::{LlmProvider, LlmProviderKind, LlmProviders, LlmRouting, LocalLlmSettings};
+pub use self::stream::SpeechStream;
+
 // Конфигурация AI сервисов
 #[derive(Debug, Clone)]
 pub struct AiConfig {
     pub openrouter_api_key: String,
     pub openai_api_key: String,
     pub preferred_model: String,
+    pub openai_model: String,
     pub max_tokens: u32,
     pub temperature: f32,
     pub timeout_seconds: u64,
+
+    // Провайдер для каждого типа запросов
+    pub routing: LlmRouting,
+
+    // Локальный OpenAI-совместимый сервер (None - отключен)
+    pub local: Option<LocalLlmSettings>,
 }
 
 impl Default for AiConfig {
@@ -26,16 +40,49 @@ impl Default for AiConfig {
             openrouter_api_key: String::new(),
             openai_api_key: String::new(),
             preferred_model: "anthropic/claude-3-haiku".to_string(),
+            openai_model: config::OPENAI_DEFAULT_MODEL.to_string(),
             max_tokens: 1000,
             temperature: 0.7,
             timeout_seconds: 30,
+            routing: LlmRouting::default(),
+            local: None,
         }
     }
 }
 
-// Структуры для OpenRouter API
+impl AiConfig {
+    /// Конфигурация из сохраненных настроек
+    pub fn from_settings(settings: &db::structs::Settings) -> Self {
+        let ai = &settings.ai_config;
+
+        // Локальный сервер доступен только в сборке с experimental-local-ai и при включенной функции
+        let local_enabled = cfg!(feature = "experimental-local-ai")
+            && settings.advanced_settings.experimental_features.enable_local_ai;
+
+        Self {
+            openrouter_api_key: settings.api_keys.openrouter.clone(),
+            openai_api_key: settings.api_keys.openai.clone(),
+            preferred_model: ai.preferred_model.clone(),
+            openai_model: ai.openai_model.clone(),
+            max_tokens: ai.max_tokens,
+            temperature: ai.temperature,
+            timeout_seconds: ai.timeout_seconds,
+            routing: ai.providers.clone(),
+            local: local_enabled.then(|| ai.local.clone()),
+        }
+    }
+
+    /// Конфигурация из текущих настроек
+    pub fn load() -> JarvisResult<Self> {
+        db::get_current_settings()
+            .map(|settings| Self::from_settings(&settings))
+            .ok_or_else(|| JarvisError::Generic("Settings not initialized".into()))
+    }
+}
+
+// Структуры chat completions API (формат OpenAI, общий для всех провайдеров)
 #[derive(Debug, Serialize)]
-struct OpenRouterRequest {
+struct ChatRequest {
     model: String,
     messages: Vec<Message>,
     max_tokens: u32,
@@ -51,7 +98,7 @@ struct Message {
 }
 
 #[derive(Debug, Deserialize)]
-struct OpenRouterResponse {
+struct ChatResponse {
     id: String,
     choices: Vec<Choice>,
     usage: Option<Usage>,
@@ -104,48 +151,44 @@ pub enum AiResponseType {
 pub struct AiManager {
     config: AiConfig,
     client: Client,
+    providers: LlmProviders,
     conversation_history: Vec<Message>,
 }
 
 impl AiManager {
     pub fn new() -> JarvisResult<Self> {
-        let config = Self::load_config()?;
+        Self::with_config(AiConfig::load()?)
+    }
 
+    pub fn with_config(config: AiConfig) -> JarvisResult<Self> {
+        // Общий таймаут не задается: потоковый ответ может идти дольше timeout_seconds,
+        // ожидание заголовков и каждого фрагмента ограничивается отдельно
         let client = Client::builder()
-            .timeout(Duration::from_secs(config.timeout_seconds))
+            .connect_timeout(Duration::from_secs(config.timeout_seconds))
             .build()
             .map_err(|e| JarvisError::Generic(format!("Failed to create HTTP client: {}", e)))?;
 
+        let providers = Self::build_providers(&config)?;
+
         Ok(Self {
             config,
             client,
+            providers,
             conversation_history: Vec::new(),
         })
     }
 
-    /// Загрузка конфигурации из настроек
-    fn load_config() -> JarvisResult<AiConfig> {
-        let mut config = AiConfig::default();
-
-        if let Some(db) = db.get() {
-            // Загружаем API ключи
-            config.openai_api_key = db.api_keys.openai.clone();
+    // Нужен хотя бы один провайдер: ключ OpenRouter/OpenAI или локальный сервер
+    fn build_providers(config: &AiConfig) -> JarvisResult<LlmProviders> {
+        let providers = LlmProviders::from_config(config);
 
-            // Загружаем ключ OpenRouter (если добавлен в БД)
-            // config.openrouter_api_key = db.api_keys.openrouter.clone();
-
-            // Для демонстрации используем настройки по умолчанию
-            // В реальном проекте эти настройки должны быть в БД
-        }
-
-        // Проверяем наличие хотя бы одного API ключа
-        if config.openrouter_api_key.is_empty() && config.openai_api_key.is_empty() {
+        if providers.is_empty() {
             return Err(JarvisError::Generic(
-                "No AI API keys configured. Please set OpenRouter or OpenAI API key in settings.".to_string()
+                "No AI providers configured. Please set OpenRouter or OpenAI API key or enable local AI in settings.".to_string()
             ));
         }
 
-        Ok(config)
+        Ok(providers)
     }
 
     /// Определение типа запроса на основе текста
@@ -173,17 +216,31 @@ impl AiManager {
 
     /// Основная функция обработки AI запроса
     pub async fn process_request(&mut self, request: AiRequestType) -> JarvisResult<AiResponse> {
+        self.handle_request(request, None).await
+    }
+
+    /// Обработка запроса с озвучиванием ответа по предложениям по мере получения
+    /// Ответы на команды и запросы документов служебные и в `speech` не передаются
+    pub async fn process_request_speaking(&mut self, request: AiRequestType, speech: &mut SpeechStream) -> JarvisResult<AiResponse> {
+        self.handle_request(request, Some(speech)).await
+    }
+
+    async fn handle_request(&mut self, request: AiRequestType, speech: Option<&mut SpeechStream>) -> JarvisResult<AiResponse> {
+        let provider = self.providers.for_request(&request)
+            .ok_or_else(|| JarvisError::Generic("No AI provider available".to_string()))?;
+        let provider = provider.as_ref();
+
         match request {
-            AiRequestType::Question(text) => self.handle_question(&text).await,
-            AiRequestType::Command(text) => self.handle_command(&text).await,
-            AiRequestType::Conversation(text) => self.handle_conversation(&text).await,
-            AiRequestType::DocumentRequest(text) => self.handle_document_request(&text).await,
-            AiRequestType::Translation(text, lang) => self.handle_translation(&text, &lang).await,
+            AiRequestType::Question(text) => self.handle_question(&text, provider, speech).await,
+            AiRequestType::Command(text) => self.handle_command(&text, provider).await,
+            AiRequestType::Conversation(text) => self.handle_conversation(&text, provider, speech).await,
+            AiRequestType::DocumentRequest(text) => self.handle_document_request(&text, provider).await,
+            AiRequestType::Translation(text, lang) => self.handle_translation(&text, &lang, provider, speech).await,
         }
     }
 
     /// Обработка обычного вопроса
-    async fn handle_question(&mut self, question: &str) -> JarvisResult<AiResponse> {
+    async fn handle_question(&mut self, question: &str, provider: &dyn LlmProvider, speech: Option<&mut SpeechStream>) -> JarvisResult<AiResponse> {
         info!("Processing AI question: {}", question);
 
         let system_prompt = "Ты JARVIS - голосовой AI ассистент. Отвечай кратко и по делу на русском языке. \
@@ -200,7 +257,7 @@ impl AiManager {
             }
         ];
 
-        let response = self.send_openrouter_request(messages).await?;
+        let response = self.send_chat_request(provider, messages, speech).await?;
 
         // Добавляем в историю разговора
         self.conversation_history.push(Message {
@@ -221,7 +278,7 @@ impl AiManager {
     }
 
     /// Обработка команды
-    async fn handle_command(&mut self, command: &str) -> JarvisResult<AiResponse> {
+    async fn handle_command(&mut self, command: &str, provider: &dyn LlmProvider) -> JarvisResult<AiResponse> {
         info!("Processing AI command: {}", command);
 
         let system_prompt = "Ты JARVIS - голосовой AI ассистент. Пользователь просит выполнить команду. \
@@ -241,7 +298,7 @@ impl AiManager {
             }
         ];
 
-        let mut response = self.send_openrouter_request(messages).await?;
+        let mut response = self.send_chat_request(provider, messages, None).await?;
 
         // Определяем тип ответа по префиксу
         response.response_type = if response.text.starts_with("SYSTEM_COMMAND:") {
@@ -261,7 +318,7 @@ impl AiManager {
     }
 
     /// Обработка продолжения разговора
-    async fn handle_conversation(&mut self, text: &str) -> JarvisResult<AiResponse> {
+    async fn handle_conversation(&mut self, text: &str, provider: &dyn LlmProvider, speech: Option<&mut SpeechStream>) -> JarvisResult<AiResponse> {
         info!("Processing AI conversation: {}", text);
 
         let mut messages = vec![
@@ -280,7 +337,7 @@ impl AiManager {
             content: text.to_string(),
         });
 
-        let response = self.send_openrouter_request(messages).await?;
+        let response = self.send_chat_request(provider, messages, speech).await?;
 
         // Обновляем историю
         self.conversation_history.push(Message {
@@ -296,7 +353,7 @@ impl AiManager {
     }
 
     /// Обработка запроса документа
-    async fn handle_document_request(&mut self, request: &str) -> JarvisResult<AiResponse> {
+    async fn handle_document_request(&mut self, request: &str, provider: &dyn LlmProvider) -> JarvisResult<AiResponse> {
         info!("Processing document request: {}", request);
 
         let system_prompt = "Пользователь просит найти или открыть документ. \
@@ -315,7 +372,7 @@ impl AiManager {
             }
         ];
 
-        let response = self.send_openrouter_request(messages).await?;
+        let response = self.send_chat_request(provider, messages, None).await?;
 
         // Извлекаем поисковый запрос
         let search_query = if response.text.starts_with("DOCUMENT_SEARCH:") {
@@ -350,7 +407,7 @@ impl AiManager {
     }
 
     /// Обработка перевода
-    async fn handle_translation(&mut self, text: &str, target_lang: &str) -> JarvisResult<AiResponse> {
+    async fn handle_translation(&mut self, text: &str, target_lang: &str, provider: &dyn LlmProvider, speech: Option<&mut SpeechStream>) -> JarvisResult<AiResponse> {
         info!("Processing translation: {} -> {}", text, target_lang);
 
         let system_prompt = format!(
@@ -369,66 +426,36 @@ impl AiManager {
             }
         ];
 
-        self.send_openrouter_request(messages).await
-    }
-
-    /// Отправка запроса к OpenRouter API
-    async fn send_openrouter_request(&self, messages: Vec<Message>) -> JarvisResult<AiResponse> {
-        // Выбираем API для использования
-        let (api_url, api_key, model) = if !self.config.openrouter_api_key.is_empty() {
-            (
-                "https://openrouter.ai/api/v1/chat/completions",
-                &self.config.openrouter_api_key,
-                &self.config.preferred_model
-            )
-        } else if !self.config.openai_api_key.is_empty() {
-            (
-                "https://api.openai.com/v1/chat/completions",
-                &self.config.openai_api_key,
-                "gpt-3.5-turbo"
-            )
-        } else {
-            return Err(JarvisError::Generic("No API key available".to_string()));
-        };
+        self.send_chat_request(provider, messages, speech).await
+    }
 
-        // Создаем заголовки
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", api_key))
-                .map_err(|e| JarvisError::Generic(format!("Invalid API key format: {}", e)))?
-        );
-        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    /// Отправка запроса chat completions выбранному провайдеру
+    /// Ответ запрашивается потоком (SSE), новый текст сразу передается в `speech`
+    async fn send_chat_request(&self, provider: &dyn LlmProvider, messages: Vec<Message>, speech: Option<&mut SpeechStream>) -> JarvisResult<AiResponse> {
+        let api_url = provider.completions_url();
+        let model = provider.model();
 
-        // Если используем OpenRouter, добавляем дополнительные заголовки
-        if api_url.contains("openrouter") {
-            headers.insert(
-                "HTTP-Referer",
-                HeaderValue::from_static("https://github.com/jarvis-voice-assistant")
-            );
-            headers.insert(
-                "X-Title",
-                HeaderValue::from_static("JARVIS Voice Assistant")
-            );
-        }
+        // Заголовки провайдера (авторизация и т.п.)
+        let mut headers = provider.headers()?;
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
 
         // Создаем тело запроса
-        let request_body = OpenRouterRequest {
+        let request_body = ChatRequest {
             model: model.to_string(),
             messages,
             max_tokens: self.config.max_tokens,
             temperature: self.config.temperature,
-            stream: Some(false),
+            stream: Some(true),
         };
 
-        info!("Sending AI request to: {}", api_url);
+        info!("Sending AI request to {:?} provider: {}", provider.kind(), api_url);
         info!("Using model: {}", model);
 
         // Отправляем запрос с таймаутом
         let response = timeout(
             Duration::from_secs(self.config.timeout_seconds),
             self.client
-                .post(api_url)
+                .post(&api_url)
                 .headers(headers)
                 .json(&request_body)
                 .send()
@@ -437,30 +464,54 @@ impl AiManager {
             .map_err(|e| JarvisError::Generic(format!("AI request failed: {}", e)))?;
 
         // Проверяем статус ответа
-        if !response.status().is_success() {
+        let status = response.status();
+        if !status.is_success() {
             let error_text = response.text().await
                 .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(JarvisError::Generic(format!("AI API error {}: {}", response.status(), error_text)));
+            return Err(JarvisError::Generic(format!("AI API error {}: {}", status, error_text)));
         }
 
-        // Парсим ответ
-        let ai_response: OpenRouterResponse = response
-            .json()
-            .await
-            .map_err(|e| JarvisError::Generic(format!("Failed to parse AI response: {}", e)))?;
+        let is_event_stream = response.headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/event-stream"));
+
+        let request_timeout = Duration::from_secs(self.config.timeout_seconds);
+        let (response_text, tokens_used) = if is_event_stream {
+            stream::read_completion(response, request_timeout, speech).await?
+        } else {
+            // Сервер может проигнорировать stream и вернуть ответ целиком
+            let ai_response: ChatResponse = timeout(request_timeout, response.json())
+                .await
+                .map_err(|_| JarvisError::Generic("AI request timeout".to_string()))?
+                .map_err(|e| JarvisError::Generic(format!("Failed to parse AI response: {}", e)))?;
+
+            let text = ai_response.choices
+                .first()
+                .map(|choice| choice.message.content.clone())
+                .unwrap_or_default();
+
+            if let Some(speech) = speech {
+                speech.push(&text);
+                speech.finish();
+            }
+
+            (text, ai_response.usage.map(|u| u.total_tokens))
+        };
 
         // Извлекаем текст ответа
-        let response_text = ai_response.choices
-            .first()
-            .map(|choice| choice.message.content.clone())
-            .unwrap_or_else(|| "Не удалось получить ответ от AI".to_string());
+        let response_text = if response_text.trim().is_empty() {
+            "Не удалось получить ответ от AI".to_string()
+        } else {
+            response_text
+        };
 
         info!("AI response received: {} characters", response_text.len());
 
         Ok(AiResponse {
             text: response_text,
             response_type: AiResponseType::TextResponse,
-            tokens_used: ai_response.usage.map(|u| u.total_tokens),
+            tokens_used,
             model_used: model.to_string(),
             confidence: 0.8,
         })
@@ -511,6 +562,7 @@ impl AiManager {
 
     /// Обновление конфигурации
     pub fn update_config(&mut self, new_config: AiConfig) -> JarvisResult<()> {
+        self.providers = Self::build_providers(&new_config)?;
         self.config = new_config;
         info!("AI configuration updated");
         Ok(())
@@ -546,13 +598,14 @@ pub async fn process_voice_command(command: &str) -> JarvisResult<AiResponse> {
     // Классифицируем запрос
     let request_type = manager.classify_request(command);
 
-    // Обрабатываем запрос
-    let response = manager.process_request(request_type).await?;
+    // Обрабатываем запрос, ответ озвучивается по предложениям по мере получения
+    let mut speech = SpeechStream::new();
+    let response = manager.process_request_speaking(request_type, &mut speech).await?;
 
     info!("AI processed command: '{}' -> '{}'", command, response.text);
 
-    // Озвучиваем ответ
-    if let Err(e) = tts::speak(&response.text) {
+    // Служебные ответы (команды, документы) озвучиваются целиком
+    if let Err(e) = speak_response(&speech, &response) {
         warn!("Failed to speak AI response: {}", e);
     }
 
@@ -579,6 +632,34 @@ pub async fn process_voice_command(command: &str) -> JarvisResult<AiResponse> {
     Ok(response)
 }
 
+/// Реплика в режиме разговора с AI (с учетом истории)
+pub async fn process_conversation_turn(text: &str) -> JarvisResult<AiResponse> {
+    let manager_mutex = AI_MANAGER.get()
+        .ok_or_else(|| JarvisError::Generic("AI manager not initialized".to_string()))?;
+
+    let mut manager = manager_mutex.lock().await;
+
+    let mut speech = SpeechStream::new();
+    let response = manager.process_request_speaking(AiRequestType::Conversation(text.to_string()), &mut speech).await?;
+
+    info!("AI conversation: '{}' -> '{}'", text, response.text);
+
+    if let Err(e) = speak_response(&speech, &response) {
+        warn!("Failed to speak AI response: {}", e);
+    }
+
+    Ok(response)
+}
+
+/// Ожидание озвучивания ответа; если ответ не озвучивался по мере получения - озвучивается целиком
+fn speak_response(speech: &SpeechStream, response: &AiResponse) -> JarvisResult<()> {
+    if speech.is_empty() {
+        tts::speak(&response.text)
+    } else {
+        speech.wait()
+    }
+}
+
 /// Открытие документа системным приложением
 fn open_document(path: &str) -> JarvisResult<()> {
     info!("Opening document: {}", path);
@@ -659,4 +740,48 @@ pub async fn shutdown_ai() -> JarvisResult<()> {
     }
     info!("AI system shutdown completed");
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_server;
+
+    #[tokio::test]
+    async fn test_streamed_request_to_local_provider() {
+        let events = [
+            r#"{"choices":[{"delta":{"role":"assistant","content":"Сейчас "}}]}"#,
+            r#"{"choices":[{"delta":{"content":"три часа."}}]}"#,
+            r#"{"choices":[{"delta":{},"finish_reason":"stop"}],"usage":{"prompt_tokens":20,"completion_tokens":4,"total_tokens":24}}"#,
+            "[DONE]",
+        ];
+        let body = events.iter().map(|event| format!("data: {}\n\n", event)).collect::<String>();
+        let (server_url, server) = mock_server("200 OK", "text/event-stream", body.into_bytes());
+        let base_url = format!("{}/v1", server_url);
+
+        let config = AiConfig {
+            local: Some(LocalLlmSettings { base_url, model: "qwen2.5".to_string(), api_key: String::new() }),
+            ..AiConfig::default()
+        };
+        let mut manager = AiManager::with_config(config).unwrap();
+
+        let response = manager.process_request(AiRequestType::Question("Который час?".to_string())).await.unwrap();
+        assert_eq!(response.text, "Сейчас три часа.");
+        assert_eq!(response.tokens_used, Some(24));
+        assert_eq!(response.model_used, "qwen2.5");
+
+        let (request_line, headers, body) = server.join().unwrap();
+        assert!(request_line.starts_with("POST /v1/chat/completions "));
+        assert!(!headers.iter().any(|h| h.starts_with("authorization:")));
+
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["model"], "qwen2.5");
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["messages"][1]["content"], "Который час?");
+    }
+
+    #[test]
+    fn test_manager_requires_provider() {
+        assert!(AiManager::with_config(AiConfig::default()).is_err());
+    }
+}
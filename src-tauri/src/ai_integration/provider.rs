@@ -0,0 +1,286 @@
+// app/src/ai_integration/provider.rs - Провайдеры LLM с OpenAI-совместимым chat completions API
+
+use std::sync::Arc;
+
+use log::warn;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+
+use super::{AiConfig, AiRequestType};
+use crate::config;
+use crate::error::{JarvisError, JarvisResult};
+
+pub use crate::db::structs::{LlmProviderKind, LlmRouting, LocalLlmSettings};
+
+/// Провайдер LLM: адрес, модель и заголовки запроса
+/// Формат запроса и потокового ответа у всех провайдеров общий (OpenAI chat completions)
+pub trait LlmProvider: Send + Sync {
+    /// Тип провайдера
+    fn kind(&self) -> LlmProviderKind;
+
+    /// Базовый адрес API (без /chat/completions)
+    fn base_url(&self) -> &str;
+
+    /// Модель для запросов
+    fn model(&self) -> &str;
+
+    /// Заголовки запроса (авторизация и т.п.)
+    fn headers(&self) -> JarvisResult<HeaderMap>;
+
+    /// Адрес chat completions
+    fn completions_url(&self) -> String {
+        format!("{}/chat/completions", self.base_url().trim_end_matches('/'))
+    }
+}
+
+/// OpenRouter
+pub struct OpenRouterProvider {
+    api_key: String,
+    model: String,
+}
+
+impl OpenRouterProvider {
+    pub fn new(api_key: String, model: String) -> Self {
+        Self { api_key, model }
+    }
+}
+
+impl LlmProvider for OpenRouterProvider {
+    fn kind(&self) -> LlmProviderKind {
+        LlmProviderKind::OpenRouter
+    }
+
+    fn base_url(&self) -> &str {
+        config::OPENROUTER_BASE_URL
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn headers(&self) -> JarvisResult<HeaderMap> {
+        let mut headers = bearer_headers(&self.api_key)?;
+
+        // Идентификация приложения для статистики OpenRouter
+        headers.insert("HTTP-Referer", HeaderValue::from_static("https://github.com/jarvis-voice-assistant"));
+        headers.insert("X-Title", HeaderValue::from_static("JARVIS Voice Assistant"));
+
+        Ok(headers)
+    }
+}
+
+/// OpenAI
+pub struct OpenAiProvider {
+    api_key: String,
+    model: String,
+}
+
+impl OpenAiProvider {
+    pub fn new(api_key: String, model: String) -> Self {
+        Self { api_key, model }
+    }
+}
+
+impl LlmProvider for OpenAiProvider {
+    fn kind(&self) -> LlmProviderKind {
+        LlmProviderKind::OpenAI
+    }
+
+    fn base_url(&self) -> &str {
+        config::OPENAI_BASE_URL
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn headers(&self) -> JarvisResult<HeaderMap> {
+        bearer_headers(&self.api_key)
+    }
+}
+
+/// Произвольный OpenAI-совместимый сервер (llama.cpp server, Ollama и т.п.)
+pub struct OpenAiCompatibleProvider {
+    settings: LocalLlmSettings,
+}
+
+impl OpenAiCompatibleProvider {
+    pub fn new(settings: LocalLlmSettings) -> Self {
+        Self { settings }
+    }
+}
+
+impl LlmProvider for OpenAiCompatibleProvider {
+    fn kind(&self) -> LlmProviderKind {
+        LlmProviderKind::Local
+    }
+
+    fn base_url(&self) -> &str {
+        &self.settings.base_url
+    }
+
+    fn model(&self) -> &str {
+        &self.settings.model
+    }
+
+    fn headers(&self) -> JarvisResult<HeaderMap> {
+        // локальные серверы обычно работают без ключа
+        if self.settings.api_key.trim().is_empty() {
+            Ok(HeaderMap::new())
+        } else {
+            bearer_headers(&self.settings.api_key)
+        }
+    }
+}
+
+fn bearer_headers(api_key: &str) -> JarvisResult<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", api_key.trim()))
+            .map_err(|e| JarvisError::Generic(format!("Invalid API key format: {}", e)))?
+    );
+
+    Ok(headers)
+}
+
+/// Настроенные провайдеры и выбор провайдера для запроса
+pub struct LlmProviders {
+    // в порядке приоритета: OpenRouter, OpenAI, локальный сервер
+    providers: Vec<Arc<dyn LlmProvider>>,
+    routing: LlmRouting,
+}
+
+impl LlmProviders {
+    /// Провайдеры, для которых есть ключ (локальный - если включен)
+    pub fn from_config(config: &AiConfig) -> Self {
+        let mut providers: Vec<Arc<dyn LlmProvider>> = Vec::new();
+
+        if !config.openrouter_api_key.trim().is_empty() {
+            providers.push(Arc::new(OpenRouterProvider::new(
+                config.openrouter_api_key.clone(),
+                config.preferred_model.clone(),
+            )));
+        }
+
+        if !config.openai_api_key.trim().is_empty() {
+            providers.push(Arc::new(OpenAiProvider::new(
+                config.openai_api_key.clone(),
+                config.openai_model.clone(),
+            )));
+        }
+
+        if let Some(local) = &config.local {
+            providers.push(Arc::new(OpenAiCompatibleProvider::new(local.clone())));
+        }
+
+        Self {
+            providers,
+            routing: config.routing.clone(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    pub fn get(&self, kind: LlmProviderKind) -> Option<Arc<dyn LlmProvider>> {
+        self.providers.iter().find(|provider| provider.kind() == kind).cloned()
+    }
+
+    /// Провайдер для запроса: выбранный в настройках, иначе первый настроенный
+    pub fn for_request(&self, request: &AiRequestType) -> Option<Arc<dyn LlmProvider>> {
+        let preferred = match request {
+            AiRequestType::Question(_) => self.routing.question,
+            AiRequestType::Command(_) => self.routing.command,
+            AiRequestType::Conversation(_) => self.routing.conversation,
+            AiRequestType::DocumentRequest(_) => self.routing.document,
+            AiRequestType::Translation(_, _) => self.routing.translation,
+        };
+
+        if let Some(kind) = preferred {
+            match self.get(kind) {
+                Some(provider) => return Some(provider),
+                None => warn!("AI provider {:?} is not configured, using the first available one", kind),
+            }
+        }
+
+        self.providers.first().cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with(openrouter: &str, openai: &str, local: bool) -> AiConfig {
+        AiConfig {
+            openrouter_api_key: openrouter.to_string(),
+            openai_api_key: openai.to_string(),
+            local: local.then(LocalLlmSettings::default),
+            ..AiConfig::default()
+        }
+    }
+
+    fn question() -> AiRequestType {
+        AiRequestType::Question("Который час?".to_string())
+    }
+
+    #[test]
+    fn test_providers_from_config() {
+        assert!(LlmProviders::from_config(&config_with("", " ", false)).is_empty());
+
+        let providers = LlmProviders::from_config(&config_with("or-key", "oa-key", true));
+        let openrouter = providers.get(LlmProviderKind::OpenRouter).unwrap();
+        assert_eq!(openrouter.completions_url(), "https://openrouter.ai/api/v1/chat/completions");
+        assert_eq!(openrouter.model(), "anthropic/claude-3-haiku");
+
+        let openai = providers.get(LlmProviderKind::OpenAI).unwrap();
+        assert_eq!(openai.completions_url(), "https://api.openai.com/v1/chat/completions");
+        assert_eq!(openai.model(), config::OPENAI_DEFAULT_MODEL);
+
+        // без явного выбора используется первый настроенный провайдер
+        assert_eq!(providers.for_request(&question()).unwrap().kind(), LlmProviderKind::OpenRouter);
+    }
+
+    #[test]
+    fn test_routing_per_request_type() {
+        let mut config = config_with("", "oa-key", true);
+        config.routing.conversation = Some(LlmProviderKind::Local);
+        config.routing.translation = Some(LlmProviderKind::OpenRouter);
+        let providers = LlmProviders::from_config(&config);
+
+        let conversation = AiRequestType::Conversation("Как дела?".to_string());
+        assert_eq!(providers.for_request(&conversation).unwrap().kind(), LlmProviderKind::Local);
+        assert_eq!(providers.for_request(&question()).unwrap().kind(), LlmProviderKind::OpenAI);
+
+        // выбранный, но не настроенный провайдер заменяется первым доступным
+        let translation = AiRequestType::Translation("Привет".to_string(), "en".to_string());
+        assert_eq!(providers.for_request(&translation).unwrap().kind(), LlmProviderKind::OpenAI);
+
+        // локальный сервер выключен
+        let providers = LlmProviders::from_config(&config_with("", "", false));
+        assert!(providers.for_request(&conversation).is_none());
+    }
+
+    #[test]
+    fn test_provider_headers() {
+        let openrouter = OpenRouterProvider::new("key".to_string(), "model".to_string()).headers().unwrap();
+        assert_eq!(openrouter[AUTHORIZATION], "Bearer key");
+        assert!(openrouter.contains_key("X-Title"));
+
+        let local = OpenAiCompatibleProvider::new(LocalLlmSettings {
+            base_url: "http://127.0.0.1:8080/v1/".to_string(),
+            ..LocalLlmSettings::default()
+        });
+        assert!(local.headers().unwrap().is_empty());
+        assert_eq!(local.completions_url(), "http://127.0.0.1:8080/v1/chat/completions");
+
+        let local = OpenAiCompatibleProvider::new(LocalLlmSettings {
+            api_key: "secret".to_string(),
+            ..LocalLlmSettings::default()
+        });
+        assert_eq!(local.headers().unwrap()[AUTHORIZATION], "Bearer secret");
+
+        assert!(OpenAiProvider::new("bad\nkey".to_string(), "model".to_string()).headers().is_err());
+    }
+}
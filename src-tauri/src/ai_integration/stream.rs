@@ -0,0 +1,461 @@
+// app/src/ai_integration/stream.rs - Потоковые ответы AI (SSE) и озвучивание по предложениям
+
+use std::time::Duration;
+
+use log::warn;
+use reqwest::Response;
+use serde::Deserialize;
+use tokio::time::timeout;
+
+use super::Usage;
+use crate::error::{AudioError, JarvisError, JarvisResult};
+use crate::tts::{self, SpeechPriority, UtteranceHandle};
+
+// Предложение без знака конца режется после этого количества символов,
+// чтобы длинный ответ без точек не задерживал начало озвучивания
+const MAX_SENTENCE_CHARS: usize = 250;
+
+// Сокращения, после точки в которых предложение не заканчивается
+const ABBREVIATIONS: &[&str] = &[
+    "т.е", "т.к", "т.д", "т.п", "т.н", "напр", "др", "см", "ул", "стр", "г", "гг", "вв", "им",
+    "mr", "mrs", "dr", "vs", "e.g", "i.e", "etc",
+];
+
+/// Разбор потока Server-Sent Events
+/// Фрагменты могут резать строки и UTF-8 символы в любом месте
+#[derive(Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    /// Добавление фрагмента ответа, возвращает данные завершенных событий
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+
+            if let Some(event) = self.process_line(line.trim_end_matches(['\n', '\r'])) {
+                events.push(event);
+            }
+        }
+
+        events
+    }
+
+    /// Конец потока: последнее событие может быть без завершающей пустой строки
+    pub fn finish(&mut self) -> Option<String> {
+        let rest = std::mem::take(&mut self.buffer);
+        if !rest.is_empty() {
+            let line = String::from_utf8_lossy(&rest);
+            if let Some(event) = self.process_line(line.trim_end_matches('\r')) {
+                return Some(event);
+            }
+        }
+
+        self.dispatch()
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<String> {
+        // пустая строка завершает событие
+        if line.is_empty() {
+            return self.dispatch();
+        }
+
+        // комментарий (OpenRouter шлет ": OPENROUTER PROCESSING" во время ожидания)
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+
+        // event, id и retry не используются
+        if field == "data" {
+            self.data.push(value.to_string());
+        }
+
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<String> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        Some(std::mem::take(&mut self.data).join("\n"))
+    }
+}
+
+/// Фрагмент потокового ответа chat completions
+#[derive(Debug, Deserialize)]
+pub(super) struct StreamChunk {
+    #[serde(default)]
+    choices: Vec<StreamChoice>,
+    usage: Option<Usage>,
+    error: Option<StreamError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamChoice {
+    #[serde(default)]
+    delta: Delta,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Delta {
+    content: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamError {
+    message: String,
+}
+
+impl StreamChunk {
+    /// Новый текст ответа
+    fn content(&self) -> &str {
+        self.choices.first()
+            .and_then(|choice| choice.delta.content.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+/// Разбор данных события; `[DONE]` означает конец ответа (None)
+pub(super) fn parse_chunk(data: &str) -> Result<Option<StreamChunk>, String> {
+    if data.trim() == "[DONE]" {
+        return Ok(None);
+    }
+
+    serde_json::from_str(data).map(Some).map_err(|e| e.to_string())
+}
+
+/// Чтение потокового ответа: текст передается в `speech` по мере получения
+/// `idle_timeout` ограничивает паузу между фрагментами, а не длительность всего ответа
+/// Возвращает полный текст и количество использованных токенов
+pub(super) async fn read_completion(
+    mut response: Response,
+    idle_timeout: Duration,
+    mut speech: Option<&mut SpeechStream>,
+) -> JarvisResult<(String, Option<u32>)> {
+    let mut parser = SseParser::default();
+    let mut text = String::new();
+    let mut tokens_used = None;
+
+    'stream: loop {
+        let chunk = timeout(idle_timeout, response.chunk()).await
+            .map_err(|_| JarvisError::Generic("AI stream stalled".to_string()))?
+            .map_err(|e| JarvisError::Generic(format!("AI stream failed: {}", e)))?;
+
+        let finished = chunk.is_none();
+        let events = match chunk {
+            Some(bytes) => parser.push(&bytes),
+            None => parser.finish().into_iter().collect(),
+        };
+
+        for data in events {
+            let chunk = match parse_chunk(&data) {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break 'stream,
+                Err(e) => {
+                    warn!("Skipping malformed AI stream event ({}): {}", e, data);
+                    continue;
+                }
+            };
+
+            if let Some(error) = chunk.error {
+                return Err(JarvisError::Generic(format!("AI stream error: {}", error.message)));
+            }
+
+            if let Some(usage) = &chunk.usage {
+                tokens_used = Some(usage.total_tokens);
+            }
+
+            let content = chunk.content();
+            if !content.is_empty() {
+                text.push_str(content);
+                if let Some(speech) = speech.as_deref_mut() {
+                    speech.push(content);
+                }
+            }
+        }
+
+        if finished {
+            break;
+        }
+    }
+
+    if let Some(speech) = speech {
+        speech.finish();
+    }
+
+    Ok((text, tokens_used))
+}
+
+/// Разбиение потока текста на предложения
+/// Предложение отдается, когда после знака конца пришел пробел или перевод строки
+#[derive(Default)]
+pub struct SentenceChunker {
+    buffer: String,
+}
+
+impl SentenceChunker {
+    /// Добавление текста, возвращает законченные предложения
+    pub fn push(&mut self, text: &str) -> Vec<String> {
+        self.buffer.push_str(text);
+
+        let mut sentences = Vec::new();
+        while let Some(end) = sentence_end(&self.buffer) {
+            let rest = self.buffer.split_off(end);
+            let sentence = std::mem::replace(&mut self.buffer, rest);
+
+            let sentence = sentence.trim();
+            if !sentence.is_empty() {
+                sentences.push(sentence.to_string());
+            }
+        }
+
+        sentences
+    }
+
+    /// Остаток текста после конца потока
+    pub fn finish(&mut self) -> Option<String> {
+        let rest = std::mem::take(&mut self.buffer);
+        let rest = rest.trim();
+
+        (!rest.is_empty()).then(|| rest.to_string())
+    }
+}
+
+// Байтовая позиция конца первого законченного предложения
+fn sentence_end(text: &str) -> Option<usize> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+
+    for (i, &(pos, c)) in chars.iter().enumerate() {
+        if c == '\n' {
+            if text[..pos].trim().is_empty() {
+                continue;
+            }
+            return Some(pos + 1);
+        }
+
+        if !matches!(c, '.' | '!' | '?' | '…') {
+            continue;
+        }
+
+        // многоточия, "?!" и закрывающие кавычки и скобки остаются в предложении
+        let mut next = i + 1;
+        while chars.get(next).is_some_and(|&(_, c)| matches!(c, '.' | '!' | '?' | '…' | '"' | '»' | '”' | ')')) {
+            next += 1;
+        }
+
+        // конец предложения подтверждается пробелом, иначе ждем продолжения
+        let &(next_pos, next_char) = chars.get(next)?;
+        if !next_char.is_whitespace() {
+            continue; // 3.14, example.com
+        }
+
+        if c == '.' && !ends_sentence(&text[..pos]) {
+            continue;
+        }
+
+        return Some(next_pos);
+    }
+
+    // слишком длинное предложение режется по последней запятой или пробелу
+    if chars.len() > MAX_SENTENCE_CHARS {
+        let limit = chars[MAX_SENTENCE_CHARS].0;
+        let head = &text[..limit];
+
+        let cut = head.rfind([',', ';', ':']).map(|pos| pos + 1)
+            .or_else(|| head.rfind(char::is_whitespace))
+            .filter(|&pos| pos > 0);
+
+        return Some(cut.unwrap_or(limit));
+    }
+
+    None
+}
+
+// Завершает ли точка после этого текста предложение (сокращения, инициалы, номера пунктов - нет)
+fn ends_sentence(before_dot: &str) -> bool {
+    let word = before_dot.rsplit(|c: char| c.is_whitespace() || c == '(').next().unwrap_or_default();
+
+    if ABBREVIATIONS.contains(&word.to_lowercase().as_str()) {
+        return false;
+    }
+
+    // инициалы: "А. С. Пушкин"
+    let mut letters = word.chars();
+    if let (Some(letter), None) = (letters.next(), letters.next()) {
+        if letter.is_uppercase() {
+            return false;
+        }
+    }
+
+    // номер пункта в начале строки: "1. Первый пункт"
+    let line = before_dot.rsplit('\n').next().unwrap_or_default().trim();
+    if !word.is_empty() && line == word && word.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+
+    true
+}
+
+/// Озвучивание ответа по мере поступления: законченные предложения сразу ставятся в очередь TTS
+#[derive(Default)]
+pub struct SpeechStream {
+    chunker: SentenceChunker,
+    handles: Vec<UtteranceHandle>,
+}
+
+impl SpeechStream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Новый фрагмент текста ответа
+    pub fn push(&mut self, text: &str) {
+        for sentence in self.chunker.push(text) {
+            self.speak(&sentence);
+        }
+    }
+
+    /// Конец ответа: озвучивается остаток без знака конца предложения
+    pub fn finish(&mut self) {
+        if let Some(rest) = self.chunker.finish() {
+            self.speak(&rest);
+        }
+    }
+
+    fn speak(&mut self, sentence: &str) {
+        if tts::is_initialized() {
+            self.handles.push(tts::enqueue(sentence, SpeechPriority::Normal));
+        }
+    }
+
+    /// Ничего не было поставлено в очередь (ответ не потоковый или TTS не инициализирован)
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Ожидание озвучивания всех предложений
+    pub fn wait(&self) -> JarvisResult<()> {
+        for handle in &self.handles {
+            handle.wait().map_err(|e| JarvisError::AudioError(AudioError::PlaybackFailed(e)))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(parser: &mut SseParser, chunks: &[&[u8]]) -> Vec<String> {
+        let mut events: Vec<String> = chunks.iter().flat_map(|chunk| parser.push(chunk)).collect();
+        events.extend(parser.finish());
+        events
+    }
+
+    #[test]
+    fn test_sse_parser_handles_split_chunks() {
+        let stream = ": OPENROUTER PROCESSING\n\n\
+                      data: {\"choices\":[{\"delta\":{\"content\":\"Привет\"}}]}\n\n\
+                      data: [DONE]\n\n";
+
+        // поток режется по одному байту, в том числе внутри кириллических символов
+        let bytes = stream.as_bytes();
+        let chunks: Vec<&[u8]> = bytes.chunks(1).collect();
+        let events = collect(&mut SseParser::default(), &chunks);
+
+        assert_eq!(events, [
+            "{\"choices\":[{\"delta\":{\"content\":\"Привет\"}}]}",
+            "[DONE]",
+        ]);
+    }
+
+    #[test]
+    fn test_sse_parser_multiline_and_crlf() {
+        let mut parser = SseParser::default();
+        let events = collect(&mut parser, &[
+            b"event: message\r\ndata: first\r\ndata:second\r\nid: 1\r\n\r\n",
+            b"retry: 100\n\n",
+            b"data: tail",
+        ]);
+
+        assert_eq!(events, ["first\nsecond", "tail"]);
+    }
+
+    #[test]
+    fn test_parse_chunk() {
+        let chunk = parse_chunk(r#"{"id":"1","choices":[{"index":0,"delta":{"role":"assistant","content":"Да"}}]}"#)
+            .unwrap().unwrap();
+        assert_eq!(chunk.content(), "Да");
+
+        let chunk = parse_chunk(r#"{"choices":[{"delta":{},"finish_reason":"stop"}],"usage":{"prompt_tokens":5,"completion_tokens":7,"total_tokens":12}}"#)
+            .unwrap().unwrap();
+        assert_eq!(chunk.content(), "");
+        assert_eq!(chunk.usage.map(|usage| usage.total_tokens), Some(12));
+
+        let chunk = parse_chunk(r#"{"error":{"message":"Rate limit exceeded","code":429}}"#).unwrap().unwrap();
+        assert_eq!(chunk.error.map(|error| error.message).as_deref(), Some("Rate limit exceeded"));
+
+        assert!(parse_chunk("[DONE]").unwrap().is_none());
+        assert!(parse_chunk("not json").is_err());
+    }
+
+    #[test]
+    fn test_chunker_splits_sentences_as_they_arrive() {
+        let mut chunker = SentenceChunker::default();
+
+        assert!(chunker.push("Привет").is_empty());
+        // знак конца без пробела еще не завершает предложение
+        assert!(chunker.push("!").is_empty());
+        assert_eq!(chunker.push(" Как"), ["Привет!"]);
+        assert_eq!(chunker.push(" дела?! Он сказал: «Да.» Всё"), ["Как дела?!", "Он сказал: «Да.»"]);
+        assert!(chunker.push(" хорошо").is_empty());
+        assert_eq!(chunker.finish().as_deref(), Some("Всё хорошо"));
+        assert_eq!(chunker.finish(), None);
+    }
+
+    #[test]
+    fn test_chunker_keeps_abbreviations_and_numbers() {
+        let mut chunker = SentenceChunker::default();
+
+        let sentences = chunker.push("Число 3.14 больше 3, т.е. почти пи. Это написал А. С. Пушкин в 1830 г. в Болдино. ");
+        assert_eq!(sentences, [
+            "Число 3.14 больше 3, т.е. почти пи.",
+            "Это написал А. С. Пушкин в 1830 г. в Болдино.",
+        ]);
+
+        // пункты списка разделяются переводом строки, номер не считается концом предложения
+        let sentences = chunker.push("1. Первый пункт\n2. Второй");
+        assert_eq!(sentences, ["1. Первый пункт"]);
+        assert_eq!(chunker.finish().as_deref(), Some("2. Второй"));
+    }
+
+    #[test]
+    fn test_chunker_limits_long_sentences() {
+        let mut chunker = SentenceChunker::default();
+        let clause = "очень длинная часть ответа без точки";
+        let text = [clause; 10].join(", ");
+
+        let sentences = chunker.push(&text);
+        assert!(!sentences.is_empty());
+        for sentence in &sentences {
+            assert!(sentence.chars().count() <= MAX_SENTENCE_CHARS);
+            assert!(sentence.ends_with(','));
+        }
+
+        let rest = chunker.finish().unwrap();
+        assert_eq!(format!("{} {}", sentences.join(" "), rest), text);
+    }
+}
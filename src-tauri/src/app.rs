@@ -1,7 +1,8 @@
 // app/src/app.rs - Исправленный основной цикл приложения
 
 use std::time::SystemTime;
-use crate::{config, audio, listener, stt, commands, COMMANDS_LIST, should_shutdown, error, db};
+use crate::{config, audio, listener, stt, commands, ai_integration, COMMANDS_LIST, should_shutdown, error, db};
+use crate::config::structs::{WakeWordAction, WakeWordEngine};
 use crate::error::{JarvisResult, JarvisError};
 use rand::seq::SliceRandom;
 use log::{info, warn, error};
@@ -40,6 +41,12 @@ fn main_loop() -> JarvisResult<()> {
             break 'wake_word_loop;
         }
 
+        // Пока микрофон занят (запись образцов wake-word), не читаем его
+        if listener::is_paused() {
+            std::thread::sleep(std::time::Duration::from_millis(config::LISTENER_PAUSE_POLL_MS));
+            continue;
+        }
+
         // Читаем данные с микрофона
         if let Err(e) = recorder::read_microphone(&mut frame_buffer) {
             error!("Failed to read from microphone: {}", e);
@@ -51,8 +58,33 @@ fn main_loop() -> JarvisResult<()> {
             Some(keyword_index) => {
                 info!("Wake-word detected! Index: {}", keyword_index);
 
-                // Обрабатываем голосовые команды после активации
-                if let Err(e) = handle_voice_commands(&sounds_directory, &mut frame_buffer) {
+                // Vosk уже распознал wake-word в этом аудио: при повторной подаче в тот же
+                // распознаватель фраза сработала бы еще раз, поэтому pre-roll начинается с момента детекции
+                if listener::current_engine() == Some(WakeWordEngine::Vosk) {
+                    audio::preroll::clear();
+                }
+
+                // Без STT голосовую сессию начать нельзя
+                if let Some(reason) = stt::unavailable_reason() {
+                    warn!("Speech recognition is unavailable, voice session skipped: {}", reason);
+                    play_error_sound(&sounds_directory);
+                    continue;
+                }
+
+                // Второй этап: подтверждение срабатывания через STT
+                if listener::guard::is_enabled(listener::current_engine())
+                    && !confirm_wake_word(&mut frame_buffer, keyword_index) {
+                    continue;
+                }
+
+                // Каждый wake-word запускает свой обработчик
+                let result = match listener::keyword_action(keyword_index) {
+                    WakeWordAction::Command => handle_voice_commands(&sounds_directory, &mut frame_buffer, false),
+                    WakeWordAction::Quiet => handle_voice_commands(&sounds_directory, &mut frame_buffer, true),
+                    WakeWordAction::AiConversation => handle_ai_conversation(&sounds_directory, &mut frame_buffer),
+                };
+
+                if let Err(e) = result {
                     error!("Voice command processing failed: {}", e);
                     // Продолжаем работу даже если команда не выполнилась
                 }
@@ -68,14 +100,47 @@ fn main_loop() -> JarvisResult<()> {
     Ok(())
 }
 
-/// Обработка голосовых команд после активации wake-word
-fn handle_voice_commands(
-    sounds_directory: &std::path::PathBuf,
-    frame_buffer: &mut [i16]
-) -> JarvisResult<()> {
-    let start_time = SystemTime::now();
+/// Дозапись аудио после срабатывания и проверка его через STT на фразу обнаруженного wake-word
+/// Дозаписанные кадры остаются в pre-roll и попадут в распознавание команды
+fn confirm_wake_word(frame_buffer: &mut [i16], keyword_index: i32) -> bool {
+    for _ in 0..config::GUARD_POST_ROLL_FRAMES {
+        if let Err(e) = recorder::read_microphone(frame_buffer) {
+            error!("Failed to read from microphone during wake-word confirmation: {}", e);
+            break;
+        }
+    }
 
-    // Воспроизводим приветственную фразу
+    listener::guard::confirm_activation(&listener::keyword_phrase(keyword_index))
+}
+
+/// Аудио, предшествующее началу сессии (wake-word и начало команды)
+fn session_preroll() -> Vec<i16> {
+    let session_settings = db::get_current_settings()
+        .map(|settings| settings.advanced_settings.voice_session)
+        .unwrap_or_default();
+
+    audio::preroll::take_latest(session_settings.preroll_ms as usize)
+}
+
+/// Воспроизведение звука ошибки (если он есть)
+fn play_error_sound(sounds_directory: &std::path::PathBuf) {
+    let error_sound = sounds_directory.join("error.wav");
+    if error_sound.exists() {
+        if let Err(sound_err) = audio::play_sound(&error_sound) {
+            warn!("Failed to play error sound: {}", sound_err);
+        }
+    }
+}
+
+/// Нужно ли воспроизводить приветствие после wake-word
+fn greeting_enabled() -> bool {
+    db::get_current_settings()
+        .map(|settings| settings.advanced_settings.voice_session.play_greeting)
+        .unwrap_or(true)
+}
+
+/// Воспроизведение случайной приветственной фразы
+fn play_greeting(sounds_directory: &std::path::PathBuf) {
     let greet_phrase = config::ASSISTANT_GREET_PHRASES
         .choose(&mut rand::thread_rng())
         .unwrap_or(&"greet1");
@@ -83,6 +148,25 @@ fn handle_voice_commands(
     if let Err(e) = audio::play_sound(&sounds_directory.join(format!("{}.wav", greet_phrase))) {
         warn!("Failed to play greeting sound: {}", e);
     }
+}
+
+/// Обработка голосовых команд после активации wake-word
+/// В тихом режиме (`silent`) приветствие и звуки ответов не воспроизводятся
+fn handle_voice_commands(
+    sounds_directory: &std::path::PathBuf,
+    frame_buffer: &mut [i16],
+    silent: bool,
+) -> JarvisResult<()> {
+    let start_time = SystemTime::now();
+
+    // Аудио до активации: команда могла прозвучать сразу после wake-word
+    let preroll = session_preroll();
+    let mut preroll_frames = preroll.chunks(frame_buffer.len());
+
+    // Воспроизводим приветственную фразу
+    if !silent && greeting_enabled() {
+        play_greeting(sounds_directory);
+    }
 
     info!("Listening for voice commands...");
 
@@ -93,38 +177,49 @@ fn handle_voice_commands(
             break 'voice_recognition;
         }
 
-        // Читаем данные с микрофона
-        if let Err(e) = recorder::read_microphone(frame_buffer) {
-            error!("Failed to read from microphone during voice recognition: {}", e);
-            continue;
-        }
+        // Сначала распознаем pre-roll, затем читаем данные с микрофона
+        let frame: &[i16] = match preroll_frames.next() {
+            Some(preroll_frame) => preroll_frame,
+            None => {
+                if let Err(e) = recorder::read_microphone(frame_buffer) {
+                    error!("Failed to read from microphone during voice recognition: {}", e);
+                    continue;
+                }
+                frame_buffer
+            }
+        };
 
         // STT обработка (без частичных результатов)
-        match stt::recognize(frame_buffer, false) {
-            Some(mut recognized_voice) => {
+        match stt::recognize_alternatives(frame) {
+            Ok(Some(alternatives)) => {
+                let recognized_voice = alternatives.first()
+                    .map(|alternative| alternative.text.clone())
+                    .unwrap_or_default();
+
                 if recognized_voice.trim().is_empty() {
                     continue; // Пустое распознавание, продолжаем слушать
                 }
 
-                info!("Recognized voice: '{}'", recognized_voice);
+                info!("Recognized voice: '{}' ({} alternatives)", recognized_voice, alternatives.len());
 
-                // Фильтруем распознанный текст
-                recognized_voice = filter_recognized_voice(recognized_voice);
+                // Фильтруем все варианты распознавания
+                let alternatives = filter_recognized_alternatives(alternatives);
 
-                if recognized_voice.trim().is_empty() {
+                if alternatives.is_empty() {
                     info!("Voice filtered to empty string, ignoring");
                     continue;
                 }
 
+                let recognized_voice = alternatives[0].text.clone();
                 info!("Filtered voice: '{}'", recognized_voice);
 
-                // Ищем подходящую команду
-                if let Some((cmd_path, cmd_config)) = find_matching_command(&recognized_voice)? {
+                // Ищем подходящую команду среди всех вариантов
+                if let Some((cmd_path, cmd_config)) = find_matching_command(&alternatives)? {
                     info!("Command found: {:?}", cmd_path);
                     info!("Executing command...");
 
                     // Выполняем команду
-                    match execute_found_command(cmd_path, cmd_config, sounds_directory) {
+                    match execute_found_command(cmd_path, cmd_config, sounds_directory, silent) {
                         Ok(should_continue_chain) => {
                             info!("Command executed successfully");
 
@@ -150,9 +245,16 @@ fn handle_voice_commands(
                 // После обработки команды возвращаемся к wake-word
                 break 'voice_recognition;
             }
-            None => {
+            Ok(None) => {
                 // STT ничего не распознал, продолжаем слушать
             }
+            Err(e) => {
+                error!("Speech recognition failed: {}", e);
+
+                if !stt::is_available() {
+                    break 'voice_recognition;
+                }
+            }
         }
 
         // Проверяем таймаут голосовых команд
@@ -167,6 +269,85 @@ fn handle_voice_commands(
     Ok(())
 }
 
+/// Разговор с AI после активации соответствующего wake-word
+fn handle_ai_conversation(
+    sounds_directory: &std::path::PathBuf,
+    frame_buffer: &mut [i16]
+) -> JarvisResult<()> {
+    let mut start_time = SystemTime::now();
+
+    let preroll = session_preroll();
+    let mut preroll_frames = preroll.chunks(frame_buffer.len());
+
+    // Разговор с AI требует свободной диктовки, а не грамматики команд
+    stt::set_mode(stt::RecognitionMode::Dictation);
+
+    if greeting_enabled() {
+        play_greeting(sounds_directory);
+    }
+
+    info!("Listening for AI conversation...");
+
+    'conversation: loop {
+        if should_shutdown() {
+            break 'conversation;
+        }
+
+        let frame: &[i16] = match preroll_frames.next() {
+            Some(preroll_frame) => preroll_frame,
+            None => {
+                if let Err(e) = recorder::read_microphone(frame_buffer) {
+                    error!("Failed to read from microphone during AI conversation: {}", e);
+                    continue;
+                }
+                frame_buffer
+            }
+        };
+
+        let recognized_voice = match stt::recognize(frame, false) {
+            Ok(recognized_voice) => recognized_voice,
+            Err(e) => {
+                error!("Speech recognition failed: {}", e);
+
+                if !stt::is_available() {
+                    break 'conversation;
+                }
+                None
+            }
+        };
+
+        if let Some(recognized_voice) = recognized_voice {
+            let recognized_voice = filter_recognized_voice(recognized_voice);
+
+            if !recognized_voice.is_empty() {
+                info!("AI conversation input: '{}'", recognized_voice);
+
+                // Ответ озвучивается внутри ai_integration
+                if let Err(e) = tauri::async_runtime::block_on(
+                    ai_integration::process_conversation_turn(&recognized_voice)
+                ) {
+                    error!("AI conversation failed: {}", e);
+                    break 'conversation;
+                }
+
+                // Даем пользователю время на следующую реплику
+                start_time = SystemTime::now();
+            }
+        }
+
+        if let Ok(elapsed) = start_time.elapsed() {
+            if elapsed > config::CMS_WAIT_DELAY {
+                info!("AI conversation timeout reached, returning to wake-word detection");
+                break 'conversation;
+            }
+        }
+    }
+
+    stt::set_mode(stt::RecognitionMode::Command);
+
+    Ok(())
+}
+
 /// Фильтрация распознанного голоса от служебных фраз
 fn filter_recognized_voice(mut voice: String) -> String {
     voice = voice.to_lowercase();
@@ -180,14 +361,25 @@ fn filter_recognized_voice(mut voice: String) -> String {
     voice.trim().to_string()
 }
 
-/// Поиск подходящей команды
-fn find_matching_command(voice: &str) -> JarvisResult<Option<(&std::path::PathBuf, &commands::structs::Config)>> {
+/// Фильтрация вариантов распознавания, пустые варианты отбрасываются
+fn filter_recognized_alternatives(alternatives: Vec<stt::RecognitionAlternative>) -> Vec<stt::RecognitionAlternative> {
+    alternatives.into_iter()
+        .map(|alternative| stt::RecognitionAlternative {
+            text: filter_recognized_voice(alternative.text),
+            confidence: alternative.confidence,
+        })
+        .filter(|alternative| !alternative.text.is_empty())
+        .collect()
+}
+
+/// Поиск подходящей команды по вариантам распознавания
+fn find_matching_command(alternatives: &[stt::RecognitionAlternative]) -> JarvisResult<Option<(&std::path::PathBuf, &commands::structs::Config)>> {
     let commands_list = COMMANDS_LIST.get()
         .ok_or_else(|| JarvisError::CommandError(error::CommandError::CommandNotFound(
             "Commands list not initialized".to_string()
         )))?;
 
-    Ok(commands::fetch_command(voice, commands_list))
+    Ok(commands::fetch_command_alternatives(alternatives, &stt::current_language(), commands_list))
 }
 use db::structs;
 /// Выполнение найденной команды
@@ -195,10 +387,15 @@ fn execute_found_command(
     cmd_path: &std::path::PathBuf,
     cmd_config: &commands::structs::Config,
     sounds_directory: &std::path::PathBuf,
+    silent: bool,
 ) -> JarvisResult<bool> {
     match commands::execute_command(cmd_path, cmd_config) {
         Ok(should_chain) => {
             // Воспроизводим звук успешного выполнения
+            if silent {
+                return Ok(should_chain);
+            }
+
             if let Some(random_sound) = cmd_config.voice.sounds.choose(&mut rand::thread_rng()) {
                 let sound_file = sounds_directory.join(format!("{}.wav", random_sound));
                 if let Err(e) = audio::play_sound(&sound_file) {
@@ -212,12 +409,7 @@ fn execute_found_command(
             error!("Command execution error: {}", e);
 
             // Воспроизводим звук ошибки (если есть)
-            let error_sound = sounds_directory.join("error.wav");
-            if error_sound.exists() {
-                if let Err(sound_err) = audio::play_sound(&error_sound) {
-                    warn!("Failed to play error sound: {}", sound_err);
-                }
-            }
+            play_error_sound(sounds_directory);
 
             Err(JarvisError::CommandError(error::CommandError::ExecutionFailed(
                 e.to_string()
@@ -253,7 +445,7 @@ pub fn get_main_loop_stats() -> serde_json::Value {
         "is_running": is_running(),
         "is_recording": recorder::is_recording(),
         "shutdown_requested": should_shutdown(),
-        "commands_available": COMMANDS_LIST.get().map(|c| c.len()).unwrap_or(0),
+        "commands_available": COMMANDS_LIST.get().map(|c| commands::list(c).len()).unwrap_or(0),
     })
 }
 
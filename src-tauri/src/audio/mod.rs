@@ -2,6 +2,8 @@
 
 pub mod audio_monitor;
 pub mod recorder;
+pub mod preroll;
+pub mod preprocess;
 pub mod pvrecorder;
 pub mod portaudio;
 pub mod kira;
@@ -15,6 +17,14 @@ pub use recorder::*;
 use crate::error::JarvisResult;
 use log::info;
 
+/// Частота дискретизации микрофона (Гц)
+pub const SAMPLE_RATE: usize = 16000;
+
+/// Количество сэмплов микрофона в `ms` миллисекундах
+pub const fn samples_for_ms(ms: usize) -> usize {
+    ms * SAMPLE_RATE / 1000
+}
+
 pub fn init() -> JarvisResult<()> {
     audio_monitor::init()?;
     recorder::init()?;
@@ -0,0 +1,440 @@
+// Предобработка сигнала микрофона: high-pass, шумоподавление, шумовой гейт и AGC
+// Применяется один раз в слое записи, так что wake-word и STT получают одинаковый сигнал
+
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::sync::Mutex;
+
+use log::info;
+
+use crate::db::structs::AudioPreprocessingSettings;
+
+const SAMPLE_RATE: f32 = 16000.0;
+
+// noise floor tracking: fast fall, slow rise (minimum statistics)
+const NOISE_FLOOR_FALL: f32 = 0.5;
+const NOISE_FLOOR_RISE: f32 = 1.01;
+const NOISE_FLOOR_MIN: f32 = 1.0;
+
+// gate attenuation and gain smoothing per frame
+const GATE_ATTENUATION: f32 = 0.1;
+const GATE_SMOOTHING: f32 = 0.3;
+const AGC_ATTACK: f32 = 0.5;
+const AGC_RELEASE: f32 = 0.05;
+const AGC_MIN_GAIN: f32 = 0.25;
+
+// spectral subtraction on 50% overlapped windows
+const SUPPRESSION_FFT_SIZE: usize = 512;
+const SUPPRESSION_HOP: usize = SUPPRESSION_FFT_SIZE / 2;
+const SUPPRESSION_OVERSUBTRACTION: f32 = 1.5;
+const SUPPRESSION_SPECTRAL_FLOOR: f32 = 0.1;
+const NOISE_SPECTRUM_SMOOTHING: f32 = 0.9;
+
+static PREPROCESSOR: Mutex<Option<Preprocessor>> = Mutex::new(None);
+
+/// Применение настроек предобработки
+pub fn configure(settings: &AudioPreprocessingSettings) {
+    let preprocessor = if settings.enabled {
+        Some(Preprocessor::new(settings.clone()))
+    } else {
+        None
+    };
+
+    info!("Audio preprocessing: {}", if settings.enabled { "enabled" } else { "disabled" });
+    *PREPROCESSOR.lock().unwrap() = preprocessor;
+}
+
+/// Обработка прочитанного кадра на месте
+pub fn process(frame: &mut [i16]) {
+    if let Ok(mut preprocessor) = PREPROCESSOR.lock() {
+        if let Some(preprocessor) = preprocessor.as_mut() {
+            preprocessor.process(frame);
+        }
+    }
+}
+
+/// Цепочка обработки со своим состоянием (фильтры, оценка шума, усиление)
+pub struct Preprocessor {
+    settings: AudioPreprocessingSettings,
+    high_pass: HighPass,
+    noise_floor: Option<f32>,
+    suppressor: NoiseSuppressor,
+    gate_gain: f32,
+    agc_gain: f32,
+}
+
+impl Preprocessor {
+    pub fn new(settings: AudioPreprocessingSettings) -> Self {
+        Self {
+            high_pass: HighPass::new(settings.high_pass_hz),
+            settings,
+            noise_floor: None,
+            suppressor: NoiseSuppressor::new(),
+            gate_gain: 1.0,
+            agc_gain: 1.0,
+        }
+    }
+
+    pub fn process(&mut self, frame: &mut [i16]) {
+        let mut samples = frame.iter().map(|&s| s as f32).collect::<Vec<_>>();
+
+        if self.settings.high_pass_hz > 0.0 {
+            self.high_pass.process(&mut samples);
+        }
+
+        // speaker-adaptive noise floor estimate
+        let level = rms(&samples);
+        let noise_floor = self.update_noise_floor(level);
+        let is_noise = level < noise_floor * self.settings.noise_gate_ratio;
+
+        if self.settings.noise_suppression {
+            self.suppressor.process(&mut samples, is_noise);
+        }
+
+        if self.settings.noise_gate {
+            let target = if is_noise { GATE_ATTENUATION } else { 1.0 };
+            self.gate_gain += (target - self.gate_gain) * GATE_SMOOTHING;
+            samples.iter_mut().for_each(|s| *s *= self.gate_gain);
+        }
+
+        // gain is adapted on speech only, so silence is not pumped up
+        if self.settings.agc {
+            if !is_noise && level > 0.0 {
+                let desired = (self.settings.agc_target_rms / level).clamp(AGC_MIN_GAIN, self.settings.agc_max_gain);
+                let speed = if desired < self.agc_gain { AGC_ATTACK } else { AGC_RELEASE };
+                self.agc_gain += (desired - self.agc_gain) * speed;
+            }
+            samples.iter_mut().for_each(|s| *s *= self.agc_gain);
+        }
+
+        for (out, sample) in frame.iter_mut().zip(samples) {
+            *out = sample.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+        }
+    }
+
+    fn update_noise_floor(&mut self, level: f32) -> f32 {
+        let floor = match self.noise_floor {
+            None => level,
+            Some(floor) if level < floor => floor + (level - floor) * NOISE_FLOOR_FALL,
+            Some(floor) => (floor * NOISE_FLOOR_RISE).min(level),
+        }.max(NOISE_FLOOR_MIN);
+
+        self.noise_floor = Some(floor);
+        floor
+    }
+}
+
+/// Спектральное вычитание на перекрывающихся окнах (sqrt-Hann, 50%, overlap-add)
+/// Спектр шума обучается на кадрах без речи; выход задержан на SUPPRESSION_FFT_SIZE - 1 сэмплов,
+/// так что кадр любой длины заполняется готовыми сэмплами целиком
+struct NoiseSuppressor {
+    window: Vec<f32>,
+    // входные сэмплы, еще не вошедшие в полное окно
+    input: Vec<f32>,
+    // сумма обработанных окон, еще не готовая к выдаче
+    overlap: Vec<f32>,
+    output: VecDeque<f32>,
+    noise_spectrum: Vec<f32>,
+}
+
+impl NoiseSuppressor {
+    fn new() -> Self {
+        // sqrt-Hann при анализе и синтезе: квадраты окон со сдвигом в половину дают в сумме 1
+        let window = (0..SUPPRESSION_FFT_SIZE)
+            .map(|n| (PI * n as f32 / SUPPRESSION_FFT_SIZE as f32).sin())
+            .collect();
+
+        Self {
+            window,
+            input: vec![0.0; SUPPRESSION_FFT_SIZE - 1],
+            overlap: vec![0.0; SUPPRESSION_FFT_SIZE],
+            output: VecDeque::new(),
+            noise_spectrum: Vec::new(),
+        }
+    }
+
+    fn process(&mut self, samples: &mut [f32], is_noise: bool) {
+        self.input.extend_from_slice(samples);
+
+        while self.input.len() >= SUPPRESSION_FFT_SIZE {
+            let mut spectrum = self.input[..SUPPRESSION_FFT_SIZE].iter()
+                .zip(&self.window)
+                .map(|(&s, &w)| Complex::new(s * w, 0.0))
+                .collect::<Vec<_>>();
+
+            fft(&mut spectrum, false);
+            self.subtract(&mut spectrum, is_noise);
+            fft(&mut spectrum, true);
+
+            for ((out, bin), &w) in self.overlap.iter_mut().zip(&spectrum).zip(&self.window) {
+                *out += bin.re * w;
+            }
+
+            self.output.extend(self.overlap.drain(..SUPPRESSION_HOP));
+            self.overlap.resize(SUPPRESSION_FFT_SIZE, 0.0);
+            self.input.drain(..SUPPRESSION_HOP);
+        }
+
+        for sample in samples.iter_mut() {
+            *sample = self.output.pop_front().unwrap_or(0.0);
+        }
+    }
+
+    fn subtract(&mut self, spectrum: &mut [Complex], is_noise: bool) {
+        let magnitudes = spectrum.iter().map(|c| c.norm()).collect::<Vec<_>>();
+
+        if self.noise_spectrum.len() != magnitudes.len() {
+            self.noise_spectrum = magnitudes.clone();
+        } else if is_noise {
+            for (noise, &magnitude) in self.noise_spectrum.iter_mut().zip(&magnitudes) {
+                *noise = *noise * NOISE_SPECTRUM_SMOOTHING + magnitude * (1.0 - NOISE_SPECTRUM_SMOOTHING);
+            }
+        }
+
+        for ((bin, &magnitude), &noise) in spectrum.iter_mut().zip(&magnitudes).zip(&self.noise_spectrum) {
+            let gain = if magnitude > 0.0 {
+                (1.0 - SUPPRESSION_OVERSUBTRACTION * noise / magnitude).max(SUPPRESSION_SPECTRAL_FLOOR)
+            } else {
+                SUPPRESSION_SPECTRAL_FLOOR
+            };
+            *bin = bin.scale(gain);
+        }
+    }
+}
+
+/// Однополюсный фильтр верхних частот
+struct HighPass {
+    alpha: f32,
+    prev_input: f32,
+    prev_output: f32,
+}
+
+impl HighPass {
+    fn new(cutoff_hz: f32) -> Self {
+        let rc = 1.0 / (2.0 * PI * cutoff_hz.max(1.0));
+        let dt = 1.0 / SAMPLE_RATE;
+
+        Self {
+            alpha: rc / (rc + dt),
+            prev_input: 0.0,
+            prev_output: 0.0,
+        }
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            let output = self.alpha * (self.prev_output + *sample - self.prev_input);
+            self.prev_input = *sample;
+            self.prev_output = output;
+            *sample = output;
+        }
+    }
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Complex {
+    re: f32,
+    im: f32,
+}
+
+impl Complex {
+    fn new(re: f32, im: f32) -> Self {
+        Self { re, im }
+    }
+
+    fn norm(self) -> f32 {
+        (self.re * self.re + self.im * self.im).sqrt()
+    }
+
+    fn scale(self, k: f32) -> Self {
+        Self::new(self.re * k, self.im * k)
+    }
+
+    fn plus(self, other: Self) -> Self {
+        Self::new(self.re + other.re, self.im + other.im)
+    }
+
+    fn minus(self, other: Self) -> Self {
+        Self::new(self.re - other.re, self.im - other.im)
+    }
+
+    fn times(self, other: Self) -> Self {
+        Self::new(self.re * other.re - self.im * other.im, self.re * other.im + self.im * other.re)
+    }
+}
+
+// in-place iterative radix-2 FFT (length must be a power of two)
+fn fft(data: &mut [Complex], inverse: bool) {
+    let n = data.len();
+
+    // bit reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * 2.0 * PI / len as f32;
+        let step = Complex::new(angle.cos(), angle.sin());
+
+        for start in (0..n).step_by(len) {
+            let mut w = Complex::new(1.0, 0.0);
+            for k in 0..len / 2 {
+                let even = data[start + k];
+                let odd = data[start + k + len / 2].times(w);
+                data[start + k] = even.plus(odd);
+                data[start + k + len / 2] = even.minus(odd);
+                w = w.times(step);
+            }
+        }
+        len <<= 1;
+    }
+
+    if inverse {
+        let scale = 1.0 / n as f32;
+        data.iter_mut().for_each(|c| *c = c.scale(scale));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> AudioPreprocessingSettings {
+        AudioPreprocessingSettings {
+            enabled: true,
+            high_pass_hz: 0.0,
+            agc: false,
+            agc_target_rms: 3000.0,
+            agc_max_gain: 8.0,
+            noise_gate: false,
+            noise_gate_ratio: 2.0,
+            noise_suppression: false,
+        }
+    }
+
+    fn tone(amplitude: f32, freq: f32, offset: usize) -> Vec<i16> {
+        (0..512)
+            .map(|i| (amplitude * (2.0 * PI * freq * (i + offset) as f32 / SAMPLE_RATE).sin()) as i16)
+            .collect()
+    }
+
+    fn frame_rms(frame: &[i16]) -> f32 {
+        rms(&frame.iter().map(|&s| s as f32).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_high_pass_removes_dc() {
+        let mut preprocessor = Preprocessor::new(AudioPreprocessingSettings { high_pass_hz: 80.0, ..settings() });
+
+        let mut frame = vec![4000i16; 512];
+        for _ in 0..10 {
+            frame = vec![4000i16; 512];
+            preprocessor.process(&mut frame);
+        }
+
+        assert!(frame_rms(&frame) < 100.0);
+    }
+
+    #[test]
+    fn test_agc_raises_quiet_speech() {
+        let mut preprocessor = Preprocessor::new(AudioPreprocessingSettings { agc: true, ..settings() });
+
+        // сначала тишина, чтобы оценка шума была низкой
+        preprocessor.process(&mut vec![5i16; 512]);
+
+        let mut frame = tone(500.0, 440.0, 0);
+        for i in 0..100 {
+            frame = tone(500.0, 440.0, i * 512);
+            preprocessor.process(&mut frame);
+        }
+
+        let level = frame_rms(&frame);
+        assert!(level > 1500.0 && level < 3500.0, "level = {}", level);
+    }
+
+    #[test]
+    fn test_noise_gate_attenuates_background() {
+        let mut preprocessor = Preprocessor::new(AudioPreprocessingSettings { noise_gate: true, ..settings() });
+
+        let mut frame = tone(200.0, 300.0, 0);
+        for i in 0..20 {
+            frame = tone(200.0, 300.0, i * 512);
+            preprocessor.process(&mut frame);
+        }
+        assert!(frame_rms(&frame) < 200.0 * 0.2);
+
+        // речь заметно громче фона проходит почти без ослабления
+        let mut speech = tone(3000.0, 300.0, 0);
+        for i in 0..5 {
+            speech = tone(3000.0, 300.0, i * 512);
+            preprocessor.process(&mut speech);
+        }
+        assert!(frame_rms(&speech) > 3000.0 * 0.5);
+    }
+
+    #[test]
+    fn test_fft_roundtrip() {
+        let original = tone(1000.0, 440.0, 0);
+        let mut data = original.iter().map(|&s| Complex::new(s as f32, 0.0)).collect::<Vec<_>>();
+
+        fft(&mut data, false);
+        fft(&mut data, true);
+
+        for (restored, &sample) in data.iter().zip(&original) {
+            assert!((restored.re - sample as f32).abs() < 0.5);
+        }
+    }
+
+    #[test]
+    fn test_noise_suppression_reduces_stationary_noise() {
+        let mut preprocessor = Preprocessor::new(AudioPreprocessingSettings { noise_suppression: true, ..settings() });
+
+        let mut frame = tone(300.0, 1000.0, 0);
+        for i in 0..30 {
+            frame = tone(300.0, 1000.0, i * 512);
+            preprocessor.process(&mut frame);
+        }
+
+        assert!(frame_rms(&frame) < 300.0 * 0.5);
+    }
+
+    #[test]
+    fn test_suppressor_reconstructs_signal_without_noise() {
+        let mut suppressor = NoiseSuppressor::new();
+        suppressor.noise_spectrum = vec![0.0; SUPPRESSION_FFT_SIZE];
+
+        // длина кадра не кратна шагу окна
+        let input = (0..4000).map(|i| ((i * 7919) % 2001) as f32 - 1000.0).collect::<Vec<_>>();
+        let mut output = Vec::new();
+        for chunk in input.chunks(320) {
+            let mut frame = chunk.to_vec();
+            suppressor.process(&mut frame, false);
+            output.extend(frame);
+        }
+
+        // без шума окна складываются в исходный сигнал с задержкой
+        let delay = SUPPRESSION_FFT_SIZE - 1;
+        for (restored, &sample) in output[delay..].iter().zip(&input) {
+            assert!((restored - sample).abs() < 1.0, "{} != {}", restored, sample);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+// Кольцевой буфер последних кадров микрофона (pre-roll)
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use log::info;
+
+use crate::config;
+use super::samples_for_ms;
+
+static PREROLL: Mutex<PrerollBuffer> = Mutex::new(PrerollBuffer::new(samples_for_ms(config::DEFAULT_PREROLL_MS as usize)));
+
+/// Буфер фиксированной емкости, хранящий последние сэмплы
+pub struct PrerollBuffer {
+    samples: VecDeque<i16>,
+    capacity: usize,
+}
+
+impl PrerollBuffer {
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, frame: &[i16]) {
+        self.samples.extend(frame.iter().copied());
+        self.trim();
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.trim();
+    }
+
+    /// Последние `count` сэмплов (или все, если их меньше)
+    pub fn latest(&self, count: usize) -> Vec<i16> {
+        let skip = self.samples.len().saturating_sub(count);
+        self.samples.iter().skip(skip).copied().collect()
+    }
+
+    /// Забрать содержимое буфера, очистив его
+    pub fn take(&mut self) -> Vec<i16> {
+        self.samples.drain(..).collect()
+    }
+
+    /// Последние `count` сэмплов, буфер очищается целиком
+    pub fn take_latest(&mut self, count: usize) -> Vec<i16> {
+        let latest = self.latest(count);
+        self.samples.clear();
+        latest
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    fn trim(&mut self) {
+        let overflow = self.samples.len().saturating_sub(self.capacity);
+        self.samples.drain(..overflow);
+    }
+}
+
+/// Установка длины pre-roll (в миллисекундах)
+/// Буфер не бывает короче окна, нужного для проверки wake-word
+pub fn configure(preroll_ms: u32) {
+    let buffer_ms = (preroll_ms as usize).max(config::GUARD_HISTORY_MS);
+
+    if let Ok(mut preroll) = PREROLL.lock() {
+        preroll.set_capacity(samples_for_ms(buffer_ms));
+    }
+
+    info!("Pre-roll buffer set to {} ms", preroll_ms);
+}
+
+/// Сохранение прочитанного кадра
+pub fn push(frame: &[i16]) {
+    if let Ok(mut preroll) = PREROLL.lock() {
+        preroll.push(frame);
+    }
+}
+
+/// Последние `ms` миллисекунд аудио
+pub fn latest(ms: usize) -> Vec<i16> {
+    PREROLL.lock().map(|preroll| preroll.latest(samples_for_ms(ms))).unwrap_or_default()
+}
+
+/// Забрать накопленное аудио, очистив буфер
+pub fn take() -> Vec<i16> {
+    PREROLL.lock().map(|mut preroll| preroll.take()).unwrap_or_default()
+}
+
+/// Последние `ms` миллисекунд аудио с очисткой буфера (под одной блокировкой,
+/// чтобы кадры, пришедшие между чтением и очисткой, не терялись)
+pub fn take_latest(ms: usize) -> Vec<i16> {
+    PREROLL.lock().map(|mut preroll| preroll.take_latest(samples_for_ms(ms))).unwrap_or_default()
+}
+
+/// Отбросить накопленное аудио (например, уже распознанный wake-word)
+pub fn clear() {
+    if let Ok(mut preroll) = PREROLL.lock() {
+        preroll.take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preroll_keeps_latest_samples() {
+        let mut buffer = PrerollBuffer::new(4);
+        buffer.push(&[1, 2, 3]);
+        buffer.push(&[4, 5, 6]);
+
+        assert_eq!(buffer.len(), 4);
+        assert_eq!(buffer.latest(2), vec![5, 6]);
+        assert_eq!(buffer.latest(10), vec![3, 4, 5, 6]);
+        assert_eq!(buffer.take(), vec![3, 4, 5, 6]);
+        assert_eq!(buffer.len(), 0);
+    }
+
+    #[test]
+    fn test_preroll_take_latest_clears_buffer() {
+        let mut buffer = PrerollBuffer::new(8);
+        buffer.push(&[1, 2, 3, 4, 5]);
+
+        assert_eq!(buffer.take_latest(3), vec![3, 4, 5]);
+        assert_eq!(buffer.len(), 0);
+    }
+
+    #[test]
+    fn test_preroll_shrink_capacity() {
+        let mut buffer = PrerollBuffer::new(6);
+        buffer.push(&[1, 2, 3, 4, 5, 6]);
+        buffer.set_capacity(2);
+
+        assert_eq!(buffer.take(), vec![5, 6]);
+    }
+}
@@ -1,6 +1,6 @@
 // app/src/recorder.rs - Рефакторинг с улучшенной обработкой ошибок
 
-use super::pvrecorder;
+use super::{pvrecorder, preroll, preprocess};
 // mod cpal;     // TODO: Implement later
 // mod portaudio; // TODO: Implement later
 
@@ -50,6 +50,13 @@ pub fn init() -> JarvisResult<()> {
         }
     }
 
+    // Длина pre-roll и предобработка из настроек
+    let advanced_settings = db::get_current_settings()
+        .map(|settings| settings.advanced_settings)
+        .unwrap_or_default();
+    preroll::configure(advanced_settings.voice_session.preroll_ms);
+    preprocess::configure(&advanced_settings.audio_preprocessing);
+
     IS_INITIALIZED.store(true, Ordering::SeqCst);
     info!("Recorder initialization completed successfully");
     Ok(())
@@ -121,7 +128,12 @@ pub fn read_microphone(frame_buffer: &mut [i16]) -> JarvisResult<()> {
             pvrecorder::read_microphone(frame_buffer)
                 .map_err(|e| JarvisError::RecorderError(RecorderError::RecordingFailed(
                     format!("PvRecorder read error: {}", e)
-                )))
+                )))?;
+
+            // Кадр очищается один раз для всех потребителей и попадает в pre-roll
+            preprocess::process(frame_buffer);
+            preroll::push(frame_buffer);
+            Ok(())
         },
         RecorderType::PortAudio => {
             Err(JarvisError::RecorderError(RecorderError::InitializationFailed(
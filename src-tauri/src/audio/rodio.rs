@@ -2,17 +2,21 @@
 
 use std::fs::File;
 use std::path::PathBuf;
-use std::io::BufReader;
+use std::io::{BufReader, Cursor};
 use std::sync::{Arc, Mutex};
 use once_cell::sync::OnceCell;
 
-use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink};
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+use rodio::buffer::SamplesBuffer;
 
 // Глобальные статические переменные для Rodio
 static STREAM: OnceCell<OutputStream> = OnceCell::new();
 static STREAM_HANDLE: OnceCell<OutputStreamHandle> = OnceCell::new();
 static SINK: OnceCell<Arc<Mutex<Sink>>> = OnceCell::new();
 
+// Отдельный sink для синтезированной речи (останавливается независимо от звуков)
+static SPEECH_SINK: Mutex<Option<Arc<Sink>>> = Mutex::new(None);
+
 /// Инициализация Rodio аудио системы
 pub fn init() -> Result<(), ()> {
     if STREAM_HANDLE.get().is_some() {
@@ -99,6 +103,61 @@ pub fn play_sound(filename: &PathBuf, sleep: bool) {
     }
 }
 
+/// Воспроизведение синтезированной речи из PCM сэмплов (блокирующее)
+pub fn play_speech_samples(samples: Vec<i16>, sample_rate: u32, channels: u16, volume: f32) -> Result<(), String> {
+    play_speech(SamplesBuffer::new(channels, sample_rate, samples), volume)
+}
+
+/// Воспроизведение синтезированной речи из закодированного буфера WAV/MP3 (блокирующее)
+pub fn play_speech_encoded(data: Vec<u8>, volume: f32) -> Result<(), String> {
+    let source = Decoder::new(Cursor::new(data))
+        .map_err(|e| format!("Cannot decode speech audio: {}", e))?;
+
+    play_speech(source, volume)
+}
+
+/// Воспроизведение синтезированной речи из произвольного источника (блокирующее)
+pub fn play_speech<S>(source: S, volume: f32) -> Result<(), String>
+where
+    S: Source + Send + 'static,
+    S::Item: rodio::Sample + Send,
+    f32: rodio::cpal::FromSample<S::Item>,
+{
+    let stream_handle = STREAM_HANDLE.get()
+        .ok_or_else(|| "Rodio is not initialized".to_string())?;
+
+    let sink = Sink::try_new(stream_handle)
+        .map_err(|e| format!("Cannot create speech sink: {}", e))?;
+
+    // громкость речи относительно общей громкости плеера
+    sink.set_volume(get_volume() * volume);
+    sink.append(source);
+
+    let sink = Arc::new(sink);
+    *SPEECH_SINK.lock().unwrap() = Some(sink.clone());
+
+    sink.sleep_until_end();
+
+    // sink мог быть уже заменен следующей фразой
+    let mut speech_sink = SPEECH_SINK.lock().unwrap();
+    if speech_sink.as_ref().is_some_and(|current| Arc::ptr_eq(current, &sink)) {
+        speech_sink.take();
+    }
+    Ok(())
+}
+
+/// Остановка воспроизведения речи (звуки ассистента не затрагиваются)
+pub fn stop_speech() -> bool {
+    match SPEECH_SINK.lock().unwrap().take() {
+        Some(sink) => {
+            sink.stop();
+            info!("Rodio speech playback stopped");
+            true
+        }
+        None => false,
+    }
+}
+
 /// Установка громкости
 pub fn set_volume(volume: f32) {
     if let Some(sink_arc) = SINK.get() {
@@ -13,6 +13,7 @@ use super::structs;
 pub use structs::*;
 use log::{info, warn, error};
 use crate::{config, audio};
+use crate::stt::RecognitionAlternative;
 
 // @TODO. Allow commands both in yaml and json format.
 pub fn parse_commands() -> Result<Vec<AssistantCommand>, String> {
@@ -25,26 +26,31 @@ pub fn parse_commands() -> Result<Vec<AssistantCommand>, String> {
         for cpath in cpaths {
             // validate this command, check if required files exists
             let _cpath = cpath.unwrap().path();
-            let cc_file = Path::new(&_cpath).join("command.yaml");
 
-            if cc_file.exists() {
+            // every language pack of the command: command.yaml + command.<lang>.yaml
+            for (language, cc_file) in command_pack_files(&_cpath) {
                 // try parse config files
-                let cc_reader = std::fs::File::open(&cc_file).unwrap();
-                let cc_yaml: CommandsList;
+                let cc_reader = match std::fs::File::open(&cc_file) {
+                    Ok(reader) => reader,
+                    Err(msg) => {
+                        warn!("Can't open {}, skipping ...\nError is: {}", &cc_file.display(), msg);
+                        continue;
+                    }
+                };
 
                 // try parse command.yaml
-                match serde_yaml::from_reader::<File, CommandsList>(cc_reader) {
-                    Ok(parse_result) => {
-                        cc_yaml = parse_result;
-                    },
+                let cc_yaml = match serde_yaml::from_reader::<File, CommandsList>(cc_reader) {
+                    Ok(parse_result) => parse_result,
                     Err(msg) => {
                         warn!("Can't parse {}, skipping ...\nCommand parse error is: {:?}", &cc_file.display(), msg);
                         continue;
                     }
-                }
+                };
+
                 // everything seems to be Ok
                 commands.push(AssistantCommand {
-                    path: _cpath,
+                    path: _cpath.clone(),
+                    language,
                     commands: cc_yaml,
                 });
             }
@@ -62,11 +68,87 @@ pub fn parse_commands() -> Result<Vec<AssistantCommand>, String> {
     }
 }
 
+/// Файлы команд в директории с их языком
+fn command_pack_files(command_dir: &Path) -> Vec<(String, PathBuf)> {
+    let mut packs = vec![];
+
+    let entries = match fs::read_dir(command_dir) {
+        Ok(entries) => entries,
+        Err(_) => return packs,
+    };
+
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+
+        if let Some(language) = command_pack_language(&file_name) {
+            packs.push((language, entry.path()));
+        }
+    }
+
+    packs.sort();
+    packs
+}
+
+/// Язык набора команд по имени файла
+pub fn command_pack_language(file_name: &str) -> Option<String> {
+    if file_name == "command.yaml" {
+        return Some(config::DEFAULT_STT_LANGUAGE.to_string());
+    }
+
+    let language = file_name.strip_prefix("command.")?.strip_suffix(".yaml")?;
+    if language.len() == 2 && language.chars().all(|c| c.is_ascii_alphabetic()) {
+        Some(language.to_lowercase())
+    } else {
+        None
+    }
+}
+
 // @TODO. NLU or smthng else is required, in order to infer commands with highest accuracy possible.
 pub fn fetch_command<'a>(
     phrase: &str,
     commands: &'a Vec<AssistantCommand>,
 ) -> Option<(&'a PathBuf, &'a Config)> {
+    if let Some((cmd_path, scmd, ratio)) = best_phrase_match(phrase, None, commands) {
+        info!("CMD is: {cmd_path:?}, SCMD is: {scmd:?}, Ratio is: {}", ratio);
+        Some((cmd_path, scmd))
+    } else {
+        None
+    }
+}
+
+/// Поиск команды по N лучшим вариантам распознавания
+/// Итоговая оценка объединяет похожесть фразы и уверенность распознавателя
+pub fn fetch_command_alternatives<'a>(
+    alternatives: &[RecognitionAlternative],
+    language: &str,
+    commands: &'a Vec<AssistantCommand>,
+) -> Option<(&'a PathBuf, &'a Config)> {
+    let confidences = normalize_confidences(alternatives);
+    let mut result: Option<(&PathBuf, &Config, f64)> = None;
+
+    for (alternative, confidence) in alternatives.iter().zip(confidences) {
+        if let Some((cmd_path, scmd, ratio)) = best_phrase_match(&alternative.text, Some(language), commands) {
+            let score = combined_score(ratio, confidence);
+
+            if result.map_or(true, |(_, _, best_score)| score > best_score) {
+                result = Some((cmd_path, scmd, score));
+            }
+        }
+    }
+
+    result.map(|(cmd_path, scmd, score)| {
+        info!("CMD is: {cmd_path:?}, SCMD is: {scmd:?}, Combined score is: {:.1}", score);
+        (cmd_path, scmd)
+    })
+}
+
+// phrase similarity must pass the threshold on its own, confidence only ranks candidates
+// (only command packs of the given language are considered, if any)
+fn best_phrase_match<'a>(
+    phrase: &str,
+    language: Option<&str>,
+    commands: &'a Vec<AssistantCommand>,
+) -> Option<(&'a PathBuf, &'a Config, f64)> {
     // result scmd
     let mut result_scmd: Option<(&PathBuf, &Config)> = None;
     let mut current_max_ratio = config::CMD_RATIO_THRESHOLD;
@@ -75,7 +157,7 @@ pub fn fetch_command<'a>(
     let fetch_phrase_chars = phrase.chars().collect::<Vec<_>>();
 
     // list all the commands
-    for cmd in commands {
+    for cmd in commands.iter().filter(|cmd| language.map_or(true, |l| cmd.language == l)) {
         // list all subcommands
         for scmd in &cmd.commands.list {
             // list all phrases in command
@@ -90,20 +172,51 @@ pub fn fetch_command<'a>(
                 if ratio >= current_max_ratio {
                     result_scmd = Some((&cmd.path, &scmd));
                     current_max_ratio = ratio;
-                    // println!("Ratio is: {}", ratio);
-                    // return Some((&cmd.path, &scmd))
                 }
             }
         }
     }
 
-    if let Some((cmd_path, scmd)) = result_scmd {
-        println!("Ratio is: {}", current_max_ratio);
-        info!("CMD is: {cmd_path:?}, SCMD is: {scmd:?}, Ratio is: {}", current_max_ratio);
-        Some((&cmd_path, &scmd))
-    } else {
-        None
+    result_scmd.map(|(cmd_path, scmd)| (cmd_path, scmd, current_max_ratio))
+}
+
+/// Приведение уверенности вариантов к диапазону 0..1 с сохранением реального разрыва между ними:
+/// значения в пределах 0..1 используются как есть, большие (Vosk) делятся на максимальное
+pub fn normalize_confidences(alternatives: &[RecognitionAlternative]) -> Vec<f64> {
+    let max = alternatives.iter().map(|a| a.confidence as f64).fold(0.0, f64::max);
+    let scale = max.max(1.0);
+
+    alternatives.iter()
+        .map(|alternative| (alternative.confidence as f64 / scale).clamp(0.0, 1.0))
+        .collect()
+}
+
+/// Итоговая оценка варианта (0..100)
+pub fn combined_score(phrase_ratio: f64, confidence: f64) -> f64 {
+    phrase_ratio * (1.0 - config::CMD_CONFIDENCE_WEIGHT) + confidence * 100.0 * config::CMD_CONFIDENCE_WEIGHT
+}
+
+/// Грамматика для распознавания в режиме команд:
+/// все фразы команд, словарь слотов, фраза активации и фразы привязанных wake-word
+pub fn grammar_phrases(commands: &[AssistantCommand], language: &str, wake_phrases: &[String]) -> Vec<String> {
+    let mut grammar = vec![config::VOSK_FETCH_PHRASE.to_string()];
+
+    let entries = wake_phrases.iter()
+        .chain(commands.iter()
+            .filter(|cmd| cmd.language == language)
+            .flat_map(|cmd| cmd.commands.list.iter())
+            .flat_map(|scmd| scmd.phrases.iter().chain(scmd.vocabulary.iter())));
+
+    for entry in entries {
+        let entry = entry.trim().to_lowercase();
+        if !entry.is_empty() && !grammar.contains(&entry) {
+            grammar.push(entry);
+        }
     }
+
+    // неизвестные слова не должны насильно приводиться к командам
+    grammar.push("[unk]".to_string());
+    grammar
 }
 
 // @TODO. Rewrite executors by executor type struct. (with match arms)
@@ -214,13 +327,155 @@ pub fn execute_command(
     }
 }
 
+/// Пути команд; языковые наборы одной команды (command.yaml, command.en.yaml) - одна запись
 pub fn list(from: &[AssistantCommand]) -> Vec<String> {
     let mut out: Vec<String> = vec![];
 
     for x in from.iter() {
-        out.push(String::from(x.path.to_str().unwrap()));
-        // out.append()
+        let path = x.path.to_string_lossy().to_string();
+        if !out.contains(&path) {
+            out.push(path);
+        }
     }
 
     out
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(phrases: &[&str], vocabulary: &[&str]) -> AssistantCommand {
+        language_command(config::DEFAULT_STT_LANGUAGE, phrases, vocabulary)
+    }
+
+    fn language_command(language: &str, phrases: &[&str], vocabulary: &[&str]) -> AssistantCommand {
+        AssistantCommand {
+            path: PathBuf::from("test"),
+            language: language.to_string(),
+            commands: CommandsList {
+                list: vec![Config {
+                    command: ConfigCommandSection {
+                        action: "voice".to_string(),
+                        exe_path: String::new(),
+                        exe_args: vec![],
+                        cli_cmd: String::new(),
+                        cli_args: vec![],
+                    },
+                    voice: ConfigVoiceSection { sounds: vec![] },
+                    phrases: phrases.iter().map(|p| p.to_string()).collect(),
+                    vocabulary: vocabulary.iter().map(|v| v.to_string()).collect(),
+                }],
+            },
+        }
+    }
+
+    fn alternative(text: &str, confidence: f32) -> RecognitionAlternative {
+        RecognitionAlternative { text: text.to_string(), confidence }
+    }
+
+    #[test]
+    fn test_normalize_confidences() {
+        let alternatives = vec![alternative("a", 200.0), alternative("b", 150.0), alternative("c", 100.0)];
+        assert_eq!(normalize_confidences(&alternatives), vec![1.0, 0.75, 0.5]);
+
+        // близкие варианты остаются близкими
+        let close = vec![alternative("a", 210.0), alternative("b", 205.0)];
+        let confidences = normalize_confidences(&close);
+        assert!(confidences[0] - confidences[1] < 0.05);
+
+        let probabilities = vec![alternative("a", 0.9), alternative("b", 0.3), alternative("c", -0.2)];
+        let confidences = normalize_confidences(&probabilities);
+        assert!((confidences[0] - 0.9).abs() < 1e-6 && (confidences[1] - 0.3).abs() < 1e-6);
+        assert_eq!(confidences[2], 0.0);
+
+        let single = vec![alternative("a", 42.0)];
+        assert_eq!(normalize_confidences(&single), vec![1.0]);
+    }
+
+    #[test]
+    fn test_fetch_command_alternatives_uses_lower_ranked_match() {
+        let commands = vec![command(&["включи музыку"], &[])];
+
+        // лучший вариант не похож ни на одну команду, второй совпадает
+        let alternatives = vec![
+            alternative("и всё", 210.0),
+            alternative("включи музыку", 205.0),
+        ];
+
+        let (_, scmd) = fetch_command_alternatives(&alternatives, config::DEFAULT_STT_LANGUAGE, &commands).unwrap();
+        assert_eq!(scmd.phrases[0], "включи музыку");
+
+        let unrelated = vec![alternative("какая погода", 300.0)];
+        assert!(fetch_command_alternatives(&unrelated, config::DEFAULT_STT_LANGUAGE, &commands).is_none());
+    }
+
+    #[test]
+    fn test_fetch_command_alternatives_prefers_confident_variant() {
+        let commands = vec![
+            command(&["включи музыку"], &[]),
+            command(&["включи мультик"], &[]),
+        ];
+
+        let alternatives = vec![
+            alternative("включи мультик", 200.0),
+            alternative("включи музыку", 100.0),
+        ];
+
+        let (_, scmd) = fetch_command_alternatives(&alternatives, config::DEFAULT_STT_LANGUAGE, &commands).unwrap();
+        assert_eq!(scmd.phrases[0], "включи мультик");
+    }
+
+    #[test]
+    fn test_grammar_phrases() {
+        let commands = vec![
+            command(&["Включи музыку", "открой браузер"], &["громкость"]),
+            command(&["включи музыку", " "], &[]),
+            language_command("en", &["play music"], &[]),
+        ];
+
+        let wake_phrases = vec!["hey friday".to_string(), config::VOSK_FETCH_PHRASE.to_string()];
+
+        let grammar = grammar_phrases(&commands, config::DEFAULT_STT_LANGUAGE, &wake_phrases);
+        assert_eq!(grammar, vec![
+            config::VOSK_FETCH_PHRASE.to_string(),
+            "hey friday".to_string(),
+            "включи музыку".to_string(),
+            "открой браузер".to_string(),
+            "громкость".to_string(),
+            "[unk]".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_list_merges_language_packs() {
+        let commands = vec![
+            language_command("ru", &["включи музыку"], &[]),
+            language_command("en", &["play music"], &[]),
+        ];
+
+        assert_eq!(list(&commands), vec!["test".to_string()]);
+    }
+
+    #[test]
+    fn test_command_pack_language() {
+        assert_eq!(command_pack_language("command.yaml"), Some(config::DEFAULT_STT_LANGUAGE.to_string()));
+        assert_eq!(command_pack_language("command.en.yaml"), Some("en".to_string()));
+        assert_eq!(command_pack_language("command.backup.yaml"), None);
+        assert_eq!(command_pack_language("script.ahk"), None);
+    }
+
+    #[test]
+    fn test_fetch_command_alternatives_respects_language() {
+        let commands = vec![
+            command(&["включи музыку"], &[]),
+            language_command("en", &["play music"], &[]),
+        ];
+
+        let alternatives = vec![alternative("play music", 100.0)];
+        assert!(fetch_command_alternatives(&alternatives, config::DEFAULT_STT_LANGUAGE, &commands).is_none());
+
+        let (_, scmd) = fetch_command_alternatives(&alternatives, "en", &commands).unwrap();
+        assert_eq!(scmd.phrases[0], "play music");
+    }
+}
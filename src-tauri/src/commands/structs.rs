@@ -4,6 +4,8 @@ use std::path::PathBuf;
 #[derive(Debug)]
 pub struct AssistantCommand {
     pub path: PathBuf,
+    // язык набора команд (command.yaml - язык по умолчанию, command.<lang>.yaml - остальные)
+    pub language: String,
     pub commands: CommandsList,
 }
 
@@ -19,6 +21,10 @@ pub struct Config {
     pub voice: ConfigVoiceSection,
 
     pub phrases: Vec<String>,
+
+    // Дополнительные слова для грамматики распознавания (значения слотов и т.п.)
+    #[serde(default)]
+    pub vocabulary: Vec<String>,
 }
 
 #[derive(Deserialize, Debug)]
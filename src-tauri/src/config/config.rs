@@ -1,6 +1,6 @@
 // app/src/config.rs - Рефакторинг с улучшенной обработкой ошибок
 
-use super::structs::{WakeWordEngine, SpeechToTextEngine, RecorderType, AudioType};
+use super::structs::{WakeWordEngine, SpeechToTextEngine, RecorderType, AudioType, RustpotterScoreMode};
 use log::{info, warn, error};
 
 use std::path::PathBuf;
@@ -12,7 +12,8 @@ use platform_dirs::AppDirs;
 use rustpotter::{RustpotterConfig, WavFmt, DetectorConfig, FiltersConfig, ScoreMode, GainNormalizationConfig, BandPassConfig};
 
 use crate::error::{JarvisResult, JarvisError, ConfigError};
-use crate::{APP_DIRS, APP_CONFIG_DIR, APP_LOG_DIR};
+use crate::db::structs::{RustpotterSettings, RustpotterWakeWordFile};
+use crate::{db, APP_DIRS, APP_CONFIG_DIR, APP_LOG_DIR};
 
 #[allow(dead_code)]
 
@@ -148,32 +149,34 @@ pub fn validate_configuration() -> JarvisResult<()> {
 
 /// Проверка требований для движков распознавания
 fn validate_engine_requirements() -> JarvisResult<()> {
-    // Проверяем наличие файлов для Vosk
-    let vosk_model_path = PathBuf::from(VOSK_MODEL_PATH);
-    if !vosk_model_path.exists() {
-        warn!("Vosk model not found at: {}", vosk_model_path.display());
-        warn!("STT functionality may not work properly");
-    }
-
-    // Проверяем наличие файлов для Rustpotter
-    let rustpotter_files = [
-        "rustpotter/jarvis-default.rpw",
-        "rustpotter/jarvis-community-1.rpw",
-        "rustpotter/jarvis-community-2.rpw",
-        "rustpotter/jarvis-community-3.rpw",
-        "rustpotter/jarvis-community-4.rpw",
-    ];
-
-    let mut missing_rustpotter = 0;
-    for file in &rustpotter_files {
-        if !PathBuf::from(file).exists() {
-            missing_rustpotter += 1;
+    // Проверяем наличие моделей Vosk
+    match get_vosk_models_dir().map(|dir| discover_vosk_models(&dir)) {
+        Ok(models) if !models.is_empty() => {
+            info!("Vosk models found for languages: {:?}", models.iter().map(|m| &m.language).collect::<Vec<_>>());
+        }
+        _ => {
+            warn!("No Vosk models found");
+            warn!("STT functionality may not work properly");
         }
     }
 
-    if missing_rustpotter == rustpotter_files.len() {
-        warn!("No Rustpotter wake-word files found");
-        warn!("Rustpotter wake-word detection may not work");
+    // Проверяем наличие файлов для Rustpotter (тот же набор, что загружает listener)
+    let rustpotter_settings = db::get_current_settings()
+        .map(|settings| settings.wake_word.rustpotter)
+        .unwrap_or_default();
+
+    match resolve_rustpotter_files(&rustpotter_settings.wake_word_files) {
+        Ok(resolution) => {
+            for missing in &resolution.missing {
+                warn!("Rustpotter wake-word file not found: {}", missing);
+            }
+
+            if resolution.found.is_empty() {
+                warn!("No Rustpotter wake-word files found");
+                warn!("Rustpotter wake-word detection may not work");
+            }
+        }
+        Err(e) => warn!("Cannot check Rustpotter wake-word files: {}", e),
     }
 
     // Проверяем наличие файлов для Picovoice
@@ -235,34 +238,124 @@ pub const FEEDBACK_LINK: Option<&str> = Some("https://t.me/jarvis_feedback_bot")
 pub const TRAY_ICON: &str = "32x32.png";
 pub const TRAY_TOOLTIP: &str = "Jarvis Voice Assistant";
 
-// RUSTPOTTER - улучшенная конфигурация
+// RUSTPOTTER - значения по умолчанию, актуальные параметры хранятся в Settings
 pub const RUSPOTTER_MIN_SCORE: f32 = 0.62;
+pub const CALIBRATION_THRESHOLD_MARGIN: f32 = 0.05;
+pub const RUSTPOTTER_WAKE_WORD_EXTENSION: &str = "rpw";
+pub const RUSTPOTTER_DEFAULT_WAKE_WORD_FILES: [&str; 5] = [
+    "jarvis-default.rpw",
+    "jarvis-community-1.rpw",
+    "jarvis-community-2.rpw",
+    "jarvis-community-3.rpw",
+    "jarvis-community-4.rpw",
+];
+
+// Обучение собственного wake-word (experimental-voice-training)
+pub const TRAINING_SAMPLE_RATE: u32 = 16000;
+pub const TRAINING_SAMPLE_DURATION: std::time::Duration = std::time::Duration::from_secs(2);
+pub const TRAINING_SILENCE_LEVEL: i16 = 500;
+pub const TRAINING_MIN_SAMPLES: usize = 3;
+pub const TRAINING_MFCC_SIZE: u16 = 16;
+
+// Интервал проверки снятия паузы главного цикла
+pub const LISTENER_PAUSE_POLL_MS: u64 = 20;
+
 pub const RUSTPOTTER_DEFAULT_CONFIG: Lazy<RustpotterConfig> = Lazy::new(|| {
+    build_rustpotter_config(&RustpotterSettings::default())
+});
+
+/// Построение конфигурации Rustpotter из сохраненных настроек
+pub fn build_rustpotter_config(settings: &RustpotterSettings) -> RustpotterConfig {
+    let detector = &settings.detector;
+    let filters = &settings.filters;
+
     RustpotterConfig {
         fmt: WavFmt::default(),
         detector: DetectorConfig {
-            avg_threshold: 0.,
-            threshold: 0.5,
-            min_scores: 15,
-            score_mode: ScoreMode::Average,
-            comparator_band_size: 5,
-            comparator_ref: 0.22
+            avg_threshold: detector.avg_threshold,
+            threshold: detector.threshold,
+            min_scores: detector.min_scores,
+            score_mode: match detector.score_mode {
+                RustpotterScoreMode::Max => ScoreMode::Max,
+                RustpotterScoreMode::Average => ScoreMode::Average,
+                RustpotterScoreMode::Median => ScoreMode::Median,
+            },
+            comparator_band_size: detector.comparator_band_size,
+            comparator_ref: detector.comparator_ref,
         },
         filters: FiltersConfig {
             gain_normalizer: GainNormalizationConfig {
-                enabled: true,
-                gain_ref: None,
-                min_gain: 0.7,
-                max_gain: 1.0,
+                enabled: filters.gain_normalizer_enabled,
+                gain_ref: filters.gain_ref,
+                min_gain: filters.min_gain,
+                max_gain: filters.max_gain,
             },
             band_pass: BandPassConfig {
-                enabled: true,
-                low_cutoff: 80.,
-                high_cutoff: 400.,
+                enabled: filters.band_pass_enabled,
+                low_cutoff: filters.low_cutoff,
+                high_cutoff: filters.high_cutoff,
             }
         }
     }
-});
+}
+
+/// Найденный wake-word файл Rustpotter
+#[derive(Debug, Clone)]
+pub struct ResolvedWakeWordFile {
+    pub key: String,
+    pub path: PathBuf,
+    pub threshold: Option<f32>,
+}
+
+/// Результат поиска wake-word файлов
+#[derive(Debug, Default)]
+pub struct WakeWordFilesResolution {
+    pub found: Vec<ResolvedWakeWordFile>,
+    pub missing: Vec<String>,
+}
+
+/// Поиск wake-word файлов в пользовательской и встроенной директориях
+pub fn resolve_rustpotter_files(files: &[RustpotterWakeWordFile]) -> JarvisResult<WakeWordFilesResolution> {
+    let search_dirs = vec![get_user_rustpotter_path()?, get_rustpotter_path()?];
+    Ok(resolve_wake_word_files(files, &search_dirs))
+}
+
+/// Поиск wake-word файлов в заданных директориях (пользовательские имеют приоритет)
+pub fn resolve_wake_word_files(files: &[RustpotterWakeWordFile], search_dirs: &[PathBuf]) -> WakeWordFilesResolution {
+    let mut resolution = WakeWordFilesResolution::default();
+
+    for file in files.iter().filter(|f| f.enabled) {
+        let file_path = PathBuf::from(&file.path);
+
+        let found_path = if file_path.is_absolute() {
+            Some(file_path.clone()).filter(|p| p.is_file())
+        } else {
+            search_dirs.iter()
+                .map(|dir| dir.join(&file_path))
+                .find(|p| p.is_file())
+        };
+
+        match found_path {
+            Some(path) => {
+                let key = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+
+                // Один и тот же wake-word не загружаем дважды
+                if resolution.found.iter().any(|f| f.key == key) {
+                    continue;
+                }
+
+                resolution.found.push(ResolvedWakeWordFile {
+                    key,
+                    path,
+                    threshold: file.threshold,
+                });
+            }
+            None => resolution.missing.push(file.path.clone()),
+        }
+    }
+
+    resolution
+}
 
 // PICOVOICE
 
@@ -294,11 +387,80 @@ pub fn get_keywords_path() -> JarvisResult<PathBuf> {
 
 /// Получение пути к модели Vosk
 pub fn get_vosk_model_path() -> JarvisResult<PathBuf> {
+    Ok(get_vosk_models_dir()?.join(VOSK_LEGACY_MODEL_DIR))
+}
+
+/// Получение пути к директории моделей Vosk
+pub fn get_vosk_models_dir() -> JarvisResult<PathBuf> {
     let resource_dir = resource_dir(&tauri::generate_context!().config())
         .ok_or_else(|| JarvisError::ConfigError(ConfigError::FileNotFound(
             "Resource directory not found".to_string()
         )))?;
-    Ok(resource_dir.join("vosk").join("model_small"))
+    Ok(resource_dir.join("vosk"))
+}
+
+/// Модель Vosk для конкретного языка
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoskModelInfo {
+    pub language: String,
+    pub path: PathBuf,
+}
+
+/// Поиск моделей в директории: `<lang>` или `model_<lang>`,
+/// старая `model_small` считается моделью языка по умолчанию
+pub fn discover_vosk_models(models_dir: &std::path::Path) -> Vec<VoskModelInfo> {
+    let mut models = Vec::new();
+
+    let entries = match fs::read_dir(models_dir) {
+        Ok(entries) => entries,
+        Err(_) => return models,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+
+        let dir_name = entry.file_name().to_string_lossy().to_lowercase();
+        let language = if dir_name == VOSK_LEGACY_MODEL_DIR {
+            DEFAULT_STT_LANGUAGE.to_string()
+        } else {
+            let code = dir_name.strip_prefix("model_").unwrap_or(&dir_name);
+            if code.len() != 2 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+                continue;
+            }
+            code.to_string()
+        };
+
+        // language specific directory wins over the legacy one
+        match models.iter().position(|m: &VoskModelInfo| m.language == language) {
+            Some(idx) if dir_name != VOSK_LEGACY_MODEL_DIR => models[idx] = VoskModelInfo { language, path },
+            Some(_) => {}
+            None => models.push(VoskModelInfo { language, path }),
+        }
+    }
+
+    models.sort_by(|a, b| a.language.cmp(&b.language));
+    models
+}
+
+/// Получение пути к модели Whisper
+pub fn get_whisper_model_path() -> JarvisResult<PathBuf> {
+    let resource_dir = resource_dir(&tauri::generate_context!().config())
+        .ok_or_else(|| JarvisError::ConfigError(ConfigError::FileNotFound(
+            "Resource directory not found".to_string()
+        )))?;
+    Ok(resource_dir.join("whisper").join(WHISPER_MODEL_FILE))
+}
+
+/// Директория голосов Piper (локальный TTS) в данных пользователя
+pub fn get_piper_models_dir() -> JarvisResult<PathBuf> {
+    let app_dirs = APP_DIRS.get()
+        .ok_or_else(|| JarvisError::ConfigError(ConfigError::InvalidConfiguration(
+            "Application directories not initialized".to_string()
+        )))?;
+    Ok(app_dirs.data_dir.join("piper"))
 }
 
 /// Получение пути к директории звуков
@@ -319,13 +481,70 @@ pub fn get_rustpotter_path() -> JarvisResult<PathBuf> {
     Ok(resource_dir.join("rustpotter"))
 }
 
+/// Получение пути к пользовательской директории Rustpotter (создается при необходимости)
+pub fn get_user_rustpotter_path() -> JarvisResult<PathBuf> {
+    let config_dir = APP_CONFIG_DIR.get()
+        .ok_or_else(|| JarvisError::ConfigError(ConfigError::MissingRequiredSetting(
+            "CONFIG_DIR not initialized".to_string()
+        )))?;
+
+    let rustpotter_dir = config_dir.join("rustpotter");
+    fs::create_dir_all(&rustpotter_dir).map_err(|e| {
+        JarvisError::ConfigError(ConfigError::DirectoryCreationFailed(
+            format!("{}: {}", rustpotter_dir.display(), e)
+        ))
+    })?;
+
+    Ok(rustpotter_dir)
+}
+
 // VOSK
+pub const VOSK_LEGACY_MODEL_DIR: &str = "model_small";
+pub const DEFAULT_STT_LANGUAGE: &str = "ru";
+
 pub const VOSK_FETCH_PHRASE: &str = "джарвис";
 
 pub const VOSK_MIN_RATIO: f64 = 70.0;
 
+// WHISPER
+pub const WHISPER_MODEL_FILE: &str = "ggml-base.bin";
+pub const WHISPER_THREADS: i32 = 4;
+pub const WHISPER_SPEECH_RMS: f32 = 500.0;
+pub const WHISPER_MIN_SPEECH_MS: usize = 200;
+pub const WHISPER_END_SILENCE_MS: usize = 700;
+pub const WHISPER_MAX_UTTERANCE_MS: usize = 15000;
+
+// WAKE-WORD GUARD
+pub const GUARD_HISTORY_MS: usize = 1500;
+pub const GUARD_POST_ROLL_FRAMES: usize = 10;
+
+// PRE-ROLL
+pub const DEFAULT_PREROLL_MS: u32 = 2000;
+
+// TTS
+pub const OPENAI_TTS_URL: &str = "https://api.openai.com/v1/audio/speech";
+pub const TTS_REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+pub const TTS_LOW_PRIORITY_TTL: std::time::Duration = std::time::Duration::from_secs(10);
+pub const DEFAULT_TTS_CACHE_SIZE_MB: u64 = 50;
+pub const PIPER_DEFAULT_SAMPLE_RATE: u32 = 22050;
+pub const ELEVENLABS_BASE_URL: &str = "https://api.elevenlabs.io";
+pub const ELEVENLABS_DEFAULT_VOICE: &str = "21m00Tcm4TlvDq8ikWAM";
+pub const ELEVENLABS_DEFAULT_MODEL: &str = "eleven_multilingual_v2";
+pub const ELEVENLABS_SAMPLE_RATE: u32 = 16000;
+pub const TTS_PREWARM_PHRASES: [&str; 4] = ["Да, сэр", "Слушаю, сэр", "Выполняю", "Запрос выполнен, сэр"];
+pub const TTS_DEFAULT_PAUSE: std::time::Duration = std::time::Duration::from_millis(500);
+pub const TTS_MAX_PAUSE: std::time::Duration = std::time::Duration::from_secs(5);
+
+// AI
+pub const OPENROUTER_BASE_URL: &str = "https://openrouter.ai/api/v1";
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+pub const OPENAI_DEFAULT_MODEL: &str = "gpt-3.5-turbo";
+pub const LOCAL_LLM_BASE_URL: &str = "http://127.0.0.1:11434/v1"; // Ollama; llama.cpp server - http://127.0.0.1:8080/v1
+pub const LOCAL_LLM_DEFAULT_MODEL: &str = "llama3.1";
+
 // ETC
 pub const CMD_RATIO_THRESHOLD: f64 = 65f64;
+pub const CMD_CONFIDENCE_WEIGHT: f64 = 0.3;
 pub const CMS_WAIT_DELAY: std::time::Duration = std::time::Duration::from_secs(15);
 
 pub const ASSISTANT_GREET_PHRASES: [&str; 3] = ["greet1", "greet2", "greet3"];
@@ -334,4 +553,102 @@ pub const ASSISTANT_PHRASES_TBR: [&str; 17] = [
     "ответь", "покажи", "скажи", "давай", "да сэр", "к вашим услугам сэр",
     "всегда к вашим услугам сэр", "запрос выполнен сэр", "выполнен сэр",
     "есть", "загружаю сэр", "очень тонкое замечание сэр",
-];
\ No newline at end of file
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn wake_word_file(path: &str) -> RustpotterWakeWordFile {
+        RustpotterWakeWordFile {
+            path: path.to_string(),
+            threshold: None,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_resolve_wake_word_files_prefers_user_dir() {
+        let user_dir = tempdir().unwrap();
+        let bundled_dir = tempdir().unwrap();
+        fs::write(user_dir.path().join("custom.rpw"), b"rpw").unwrap();
+        fs::write(bundled_dir.path().join("custom.rpw"), b"rpw").unwrap();
+        fs::write(bundled_dir.path().join("jarvis-default.rpw"), b"rpw").unwrap();
+
+        let files = vec![wake_word_file("custom.rpw"), wake_word_file("jarvis-default.rpw")];
+        let dirs = vec![user_dir.path().to_path_buf(), bundled_dir.path().to_path_buf()];
+        let resolution = resolve_wake_word_files(&files, &dirs);
+
+        assert!(resolution.missing.is_empty());
+        assert_eq!(resolution.found.len(), 2);
+        assert_eq!(resolution.found[0].key, "custom");
+        assert!(resolution.found[0].path.starts_with(user_dir.path()));
+        assert!(resolution.found[1].path.starts_with(bundled_dir.path()));
+    }
+
+    #[test]
+    fn test_resolve_wake_word_files_reports_missing() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("present.rpw"), b"rpw").unwrap();
+
+        let mut disabled = wake_word_file("disabled.rpw");
+        disabled.enabled = false;
+
+        let files = vec![wake_word_file("present.rpw"), wake_word_file("absent.rpw"), disabled];
+        let resolution = resolve_wake_word_files(&files, &[dir.path().to_path_buf()]);
+
+        assert_eq!(resolution.found.len(), 1);
+        assert_eq!(resolution.missing, vec!["absent.rpw".to_string()]);
+    }
+
+    #[test]
+    fn test_resolve_wake_word_files_skips_duplicates() {
+        let dir = tempdir().unwrap();
+        let absolute = dir.path().join("twice.rpw");
+        fs::write(&absolute, b"rpw").unwrap();
+
+        let files = vec![wake_word_file("twice.rpw"), wake_word_file(absolute.to_str().unwrap())];
+        let resolution = resolve_wake_word_files(&files, &[dir.path().to_path_buf()]);
+
+        assert_eq!(resolution.found.len(), 1);
+    }
+
+    #[test]
+    fn test_build_rustpotter_config_from_settings() {
+        let mut settings = RustpotterSettings::default();
+        settings.detector.threshold = 0.42;
+        settings.filters.band_pass_enabled = false;
+
+        let rustpotter_config = build_rustpotter_config(&settings);
+        assert_eq!(rustpotter_config.detector.threshold, 0.42);
+        assert!(!rustpotter_config.filters.band_pass.enabled);
+    }
+
+    #[test]
+    fn test_discover_vosk_models() {
+        let models_dir = tempdir().unwrap();
+        fs::create_dir(models_dir.path().join("model_small")).unwrap();
+        fs::create_dir(models_dir.path().join("en")).unwrap();
+        fs::create_dir(models_dir.path().join("model_de")).unwrap();
+        fs::create_dir(models_dir.path().join("readme")).unwrap();
+        fs::write(models_dir.path().join("uk"), b"not a dir").unwrap();
+
+        let models = discover_vosk_models(models_dir.path());
+        let languages = models.iter().map(|m| m.language.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(languages, vec!["de", "en", DEFAULT_STT_LANGUAGE]);
+        assert!(models[2].path.ends_with("model_small"));
+    }
+
+    #[test]
+    fn test_discover_vosk_models_prefers_language_dir() {
+        let models_dir = tempdir().unwrap();
+        fs::create_dir(models_dir.path().join("model_small")).unwrap();
+        fs::create_dir(models_dir.path().join(DEFAULT_STT_LANGUAGE)).unwrap();
+
+        let models = discover_vosk_models(models_dir.path());
+        assert_eq!(models.len(), 1);
+        assert!(models[0].path.ends_with(DEFAULT_STT_LANGUAGE));
+    }
+}
@@ -7,9 +7,26 @@ pub enum WakeWordEngine {
     Porcupine
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Поведение, запускаемое конкретным wake-word
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum WakeWordAction {
+    Command,         // Обычный режим голосовых команд
+    AiConversation,  // Разговор с AI
+    Quiet,           // Команды без звуковых ответов
+}
+
+/// Режим агрегации оценок Rustpotter
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum RustpotterScoreMode {
+    Max,
+    Average,
+    Median,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum SpeechToTextEngine {
-    Vosk
+    Vosk,
+    Whisper,  // whisper.cpp (feature experimental-whisper-stt)
 }
 
 #[derive(PartialEq, Debug)]
@@ -2,7 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use crate::config;
-use crate::config::structs::{WakeWordEngine, SpeechToTextEngine};
+use crate::config::structs::{WakeWordEngine, WakeWordAction, RustpotterScoreMode, SpeechToTextEngine};
 
 /// Основная структура настроек приложения
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -16,6 +16,14 @@ pub struct Settings {
     pub wake_word_engine: WakeWordEngine,
     pub speech_to_text_engine: SpeechToTextEngine,
 
+    // Настройки wake-word
+    #[serde(default)]
+    pub wake_word: WakeWordSettings,
+
+    // Настройки распознавания речи
+    #[serde(default)]
+    pub stt: SttSettings,
+
     // API ключи
     pub api_keys: ApiKeys,
 
@@ -43,6 +51,9 @@ impl Default for Settings {
             wake_word_engine: config::DEFAULT_WAKE_WORD_ENGINE,
             speech_to_text_engine: config::DEFAULT_SPEECH_TO_TEXT_ENGINE,
 
+            wake_word: WakeWordSettings::default(),
+            stt: SttSettings::default(),
+
             api_keys: ApiKeys::default(),
             ai_config: AiConfig::default(),
             tts_config: TtsConfig::default(),
@@ -54,13 +65,164 @@ impl Default for Settings {
     }
 }
 
+/// Настройки wake-word
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WakeWordSettings {
+    // Привязки wake-word к поведению (несвязанные слова запускают режим команд)
+    pub bindings: Vec<WakeWordBinding>,
+
+    // Настройки движка Rustpotter
+    #[serde(default)]
+    pub rustpotter: RustpotterSettings,
+
+    // Подтверждение срабатывания через STT (защита от ложных активаций)
+    #[serde(default)]
+    pub stt_confirmation: bool,
+}
+
+impl Default for WakeWordSettings {
+    fn default() -> Self {
+        Self {
+            bindings: Vec::new(),
+            rustpotter: RustpotterSettings::default(),
+            stt_confirmation: false,
+        }
+    }
+}
+
+/// Настройки распознавания речи
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SttSettings {
+    // Ограничить распознавание в режиме команд грамматикой из фраз команд
+    pub use_command_grammar: bool,
+
+    // Язык распознавания (код модели Vosk, например "ru" или "en")
+    pub language: String,
+
+    // Автоматический выбор языка по уверенности распознавания
+    // (только при включенном enable_multi_language)
+    pub auto_language: bool,
+}
+
+impl Default for SttSettings {
+    fn default() -> Self {
+        Self {
+            use_command_grammar: false,
+            language: config::DEFAULT_STT_LANGUAGE.to_string(),
+            auto_language: false,
+        }
+    }
+}
+
+/// Настройки Rustpotter: набор wake-word файлов и параметры детектора
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RustpotterSettings {
+    pub wake_word_files: Vec<RustpotterWakeWordFile>,
+    pub min_score: f32,
+    pub detector: RustpotterDetectorSettings,
+    pub filters: RustpotterFiltersSettings,
+}
+
+impl Default for RustpotterSettings {
+    fn default() -> Self {
+        Self {
+            wake_word_files: config::RUSTPOTTER_DEFAULT_WAKE_WORD_FILES.iter()
+                .map(|path| RustpotterWakeWordFile {
+                    path: path.to_string(),
+                    threshold: None,
+                    enabled: true,
+                })
+                .collect(),
+            min_score: config::RUSPOTTER_MIN_SCORE,
+            detector: RustpotterDetectorSettings::default(),
+            filters: RustpotterFiltersSettings::default(),
+        }
+    }
+}
+
+/// Wake-word файл Rustpotter
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RustpotterWakeWordFile {
+    // Абсолютный путь или имя файла в пользовательской/встроенной директории rustpotter
+    pub path: String,
+    // Собственный порог срабатывания (иначе используется min_score)
+    pub threshold: Option<f32>,
+    pub enabled: bool,
+}
+
+/// Параметры детектора Rustpotter
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RustpotterDetectorSettings {
+    pub avg_threshold: f32,
+    pub threshold: f32,
+    pub min_scores: usize,
+    pub score_mode: RustpotterScoreMode,
+    pub comparator_band_size: usize,
+    pub comparator_ref: f32,
+}
+
+impl Default for RustpotterDetectorSettings {
+    fn default() -> Self {
+        Self {
+            avg_threshold: 0.,
+            threshold: 0.5,
+            min_scores: 15,
+            score_mode: RustpotterScoreMode::Average,
+            comparator_band_size: 5,
+            comparator_ref: 0.22,
+        }
+    }
+}
+
+/// Параметры фильтров Rustpotter
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RustpotterFiltersSettings {
+    pub gain_normalizer_enabled: bool,
+    pub gain_ref: Option<f32>,
+    pub min_gain: f32,
+    pub max_gain: f32,
+    pub band_pass_enabled: bool,
+    pub low_cutoff: f32,
+    pub high_cutoff: f32,
+}
+
+impl Default for RustpotterFiltersSettings {
+    fn default() -> Self {
+        Self {
+            gain_normalizer_enabled: true,
+            gain_ref: None,
+            min_gain: 0.7,
+            max_gain: 1.0,
+            band_pass_enabled: true,
+            low_cutoff: 80.,
+            high_cutoff: 400.,
+        }
+    }
+}
+
+/// Привязка wake-word к поведению
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WakeWordBinding {
+    // Имя ключевого слова: имя .rpw/.ppn файла без расширения или фраза для Vosk
+    pub keyword: String,
+    pub action: WakeWordAction,
+}
+
+impl WakeWordBinding {
+    /// Совпадает ли привязка с ключевым словом (без учета регистра, в том числе кириллицы)
+    pub fn matches(&self, keyword: &str) -> bool {
+        self.keyword.trim().to_lowercase() == keyword.trim().to_lowercase()
+    }
+}
+
 /// API ключи для различных сервисов
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ApiKeys {
     pub picovoice: String,
     pub openai: String,
     pub openrouter: String,
-    pub elevenlabs: String,  // Для будущего использования
+    pub elevenlabs: String,
 }
 
 impl Default for ApiKeys {
@@ -76,30 +238,82 @@ impl Default for ApiKeys {
 
 /// Настройки AI системы
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 pub struct AiConfig {
-    pub preferred_model: String,
+    pub preferred_model: String,  // модель OpenRouter
+    pub openai_model: String,
     pub temperature: f32,
     pub max_tokens: u32,
     pub timeout_seconds: u64,
     pub enable_conversation_mode: bool,
     pub conversation_history_limit: usize,
+
+    // Провайдер LLM для каждого типа запросов
+    pub providers: LlmRouting,
+
+    // Локальный OpenAI-совместимый сервер (experimental-local-ai)
+    pub local: LocalLlmSettings,
 }
 
 impl Default for AiConfig {
     fn default() -> Self {
         Self {
             preferred_model: "anthropic/claude-3-haiku".to_string(),
+            openai_model: config::OPENAI_DEFAULT_MODEL.to_string(),
             temperature: 0.7,
             max_tokens: 1000,
             timeout_seconds: 30,
             enable_conversation_mode: false,
             conversation_history_limit: 10,
+            providers: LlmRouting::default(),
+            local: LocalLlmSettings::default(),
+        }
+    }
+}
+
+/// Провайдеры LLM
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmProviderKind {
+    OpenRouter,
+    OpenAI,
+    Local,  // OpenAI-совместимый сервер (llama.cpp server, Ollama)
+}
+
+/// Выбор провайдера LLM по типам запросов (None - первый настроенный провайдер)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct LlmRouting {
+    pub question: Option<LlmProviderKind>,
+    pub command: Option<LlmProviderKind>,
+    pub conversation: Option<LlmProviderKind>,
+    pub document: Option<LlmProviderKind>,
+    pub translation: Option<LlmProviderKind>,
+}
+
+/// Настройки локального OpenAI-совместимого сервера
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct LocalLlmSettings {
+    // Базовый адрес API, к нему добавляется /chat/completions
+    pub base_url: String,
+    pub model: String,
+    // Ключ, если сервер его требует (обычно пустой)
+    pub api_key: String,
+}
+
+impl Default for LocalLlmSettings {
+    fn default() -> Self {
+        Self {
+            base_url: config::LOCAL_LLM_BASE_URL.to_string(),
+            model: config::LOCAL_LLM_DEFAULT_MODEL.to_string(),
+            api_key: String::new(),
         }
     }
 }
 
 /// Настройки синтеза речи
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 pub struct TtsConfig {
     pub engine: TtsEngine,
     pub voice_id: String,
@@ -107,6 +321,12 @@ pub struct TtsConfig {
     pub pitch: f32,
     pub volume: f32,
     pub language: String,
+
+    // Размер дискового кэша синтезированной речи (0 - кэш отключен)
+    pub cache_size_mb: u64,
+
+    // Параметры ElevenLabs (и совместимых сервисов)
+    pub elevenlabs: ElevenLabsSettings,
 }
 
 impl Default for TtsConfig {
@@ -118,17 +338,43 @@ impl Default for TtsConfig {
             pitch: 1.0,
             volume: 0.8,
             language: "ru-RU".to_string(),
+            cache_size_mb: config::DEFAULT_TTS_CACHE_SIZE_MB,
+            elevenlabs: ElevenLabsSettings::default(),
         }
     }
 }
 
-/// Типы TTS движков
+/// Настройки ElevenLabs TTS
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ElevenLabsSettings {
+    // Адрес API (можно указать совместимый сервер)
+    pub base_url: String,
+    pub voice_id: String,
+    pub model_id: String,
+    pub stability: f32,         // 0.0 - 1.0
+    pub similarity_boost: f32,  // 0.0 - 1.0
+}
+
+impl Default for ElevenLabsSettings {
+    fn default() -> Self {
+        Self {
+            base_url: config::ELEVENLABS_BASE_URL.to_string(),
+            voice_id: config::ELEVENLABS_DEFAULT_VOICE.to_string(),
+            model_id: config::ELEVENLABS_DEFAULT_MODEL.to_string(),
+            stability: 0.5,
+            similarity_boost: 0.75,
+        }
+    }
+}
+
+/// Типы TTS движков
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum TtsEngine {
     System,
     OpenAI,
     ElevenLabs,
-    Local,  // Для будущих локальных движков
+    Local,  // Локальный нейросетевой TTS (Piper)
 }
 
 /// Продвинутые настройки
@@ -157,6 +403,69 @@ pub struct AdvancedSettings {
 
     // Экспериментальные функции
     pub experimental_features: ExperimentalFeatures,
+
+    // Начало голосовой сессии
+    #[serde(default)]
+    pub voice_session: VoiceSessionSettings,
+
+    // Предобработка сигнала микрофона
+    #[serde(default)]
+    pub audio_preprocessing: AudioPreprocessingSettings,
+}
+
+/// Настройки общей цепочки предобработки аудио (для wake-word и STT)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AudioPreprocessingSettings {
+    pub enabled: bool,
+
+    // Частота среза фильтра верхних частот (0 - выключен)
+    pub high_pass_hz: f32,
+
+    // Автоматическая регулировка усиления
+    pub agc: bool,
+    pub agc_target_rms: f32,
+    pub agc_max_gain: f32,
+
+    // Шумовой гейт: кадр считается шумом, если он тише оценки шума в noise_gate_ratio раз
+    pub noise_gate: bool,
+    pub noise_gate_ratio: f32,
+
+    // Спектральное шумоподавление (дороже по CPU)
+    pub noise_suppression: bool,
+}
+
+impl Default for AudioPreprocessingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            high_pass_hz: 80.0,
+            agc: true,
+            agc_target_rms: 3000.0,
+            agc_max_gain: 8.0,
+            noise_gate: true,
+            noise_gate_ratio: 2.0,
+            noise_suppression: false,
+        }
+    }
+}
+
+/// Настройки начала голосовой сессии после wake-word
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VoiceSessionSettings {
+    // Сколько аудио до начала сессии передается распознавателю (мс)
+    pub preroll_ms: u32,
+    // Воспроизводить ли приветствие после wake-word
+    pub play_greeting: bool,
+}
+
+impl Default for VoiceSessionSettings {
+    fn default() -> Self {
+        Self {
+            preroll_ms: config::DEFAULT_PREROLL_MS,
+            play_greeting: true,
+        }
+    }
 }
 
 impl Default for AdvancedSettings {
@@ -180,6 +489,8 @@ impl Default for AdvancedSettings {
             startup_behavior: StartupBehavior::StartListening,
 
             experimental_features: ExperimentalFeatures::default(),
+            voice_session: VoiceSessionSettings::default(),
+            audio_preprocessing: AudioPreprocessingSettings::default(),
         }
     }
 }
@@ -300,6 +611,14 @@ impl Settings {
         }
     }
 
+    /// Поведение для обнаруженного wake-word
+    pub fn wake_word_action(&self, keyword: &str) -> WakeWordAction {
+        self.wake_word.bindings.iter()
+            .find(|binding| binding.matches(keyword))
+            .map(|binding| binding.action)
+            .unwrap_or(WakeWordAction::Command)
+    }
+
     /// Проверка наличия необходимых API ключей
     pub fn has_required_keys(&self) -> bool {
         !self.api_keys.openrouter.is_empty() || !self.api_keys.openai.is_empty()
@@ -373,6 +692,58 @@ mod tests {
         assert_eq!(settings.get_active_ai_key(), Some(("openrouter", "openrouter-key")));
     }
 
+    #[test]
+    fn test_wake_word_action_lookup() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.wake_word_action("jarvis-default"), WakeWordAction::Command);
+
+        settings.wake_word.bindings.push(WakeWordBinding {
+            keyword: "friday".to_string(),
+            action: WakeWordAction::AiConversation,
+        });
+        settings.wake_word.bindings.push(WakeWordBinding {
+            keyword: "Тихо".to_string(),
+            action: WakeWordAction::Quiet,
+        });
+
+        assert_eq!(settings.wake_word_action("Friday"), WakeWordAction::AiConversation);
+        assert_eq!(settings.wake_word_action("тихо"), WakeWordAction::Quiet);
+        assert_eq!(settings.wake_word_action("ТИХО "), WakeWordAction::Quiet);
+        assert_eq!(settings.wake_word_action("unknown"), WakeWordAction::Command);
+    }
+
+    #[test]
+    fn test_rustpotter_defaults() {
+        let settings = Settings::default();
+        let rustpotter = &settings.wake_word.rustpotter;
+
+        assert_eq!(rustpotter.wake_word_files.len(), config::RUSTPOTTER_DEFAULT_WAKE_WORD_FILES.len());
+        assert!(rustpotter.wake_word_files.iter().all(|f| f.enabled && f.threshold.is_none()));
+        assert_eq!(rustpotter.min_score, config::RUSPOTTER_MIN_SCORE);
+    }
+
+    #[test]
+    fn test_ai_config_without_providers() {
+        // настройки, сохраненные до появления выбора провайдеров
+        let ai_config: AiConfig = serde_json::from_str(r#"{
+            "preferred_model": "openai/gpt-4o-mini",
+            "temperature": 0.5,
+            "max_tokens": 500,
+            "timeout_seconds": 20,
+            "enable_conversation_mode": true,
+            "conversation_history_limit": 10
+        }"#).unwrap();
+
+        assert_eq!(ai_config.preferred_model, "openai/gpt-4o-mini");
+        assert_eq!(ai_config.openai_model, config::OPENAI_DEFAULT_MODEL);
+        assert_eq!(ai_config.providers, LlmRouting::default());
+        assert_eq!(ai_config.local, LocalLlmSettings::default());
+
+        let routing: LlmRouting = serde_json::from_str(r#"{"conversation": "Local"}"#).unwrap();
+        assert_eq!(routing.conversation, Some(LlmProviderKind::Local));
+        assert_eq!(routing.question, None);
+    }
+
     #[test]
     fn test_exportable_settings() {
         let mut settings = Settings::default();
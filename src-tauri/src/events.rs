@@ -1,5 +1,10 @@
+use log::warn;
+use once_cell::sync::OnceCell;
 use tauri::Manager;
 
+// Handle приложения для событий из фоновых модулей (TTS и т.п.)
+static APP_HANDLE: OnceCell<tauri::AppHandle> = OnceCell::new();
+
 // the payload type must implement `Serialize` and `Clone`.
 #[derive(Clone, serde::Serialize)]
 pub struct Payload {
@@ -14,6 +19,7 @@ pub enum EventTypes {
     CommandStart,
     CommandInProcess,
     CommandEnd,
+    SpeechInterrupted,
 }
 
 impl EventTypes {
@@ -25,6 +31,7 @@ impl EventTypes {
             Self::CommandStart => "command-start",
             Self::CommandInProcess => "command-in-process",
             Self::CommandEnd => "command-end",
+            Self::SpeechInterrupted => "speech-interrupted",
         }
     }
 }
@@ -39,3 +46,17 @@ pub fn play(phrase: &str, app_handle: &tauri::AppHandle) {
         )
         .unwrap();
 }
+
+/// Сохранение handle приложения для событий из фоновых модулей
+pub fn set_app_handle(app_handle: tauri::AppHandle) {
+    let _ = APP_HANDLE.set(app_handle);
+}
+
+/// Отправка события всем окнам (если приложение уже запущено)
+pub fn emit(event: EventTypes, data: &str) {
+    if let Some(app_handle) = APP_HANDLE.get() {
+        if let Err(e) = app_handle.emit_all(event.get(), Payload { data: data.into() }) {
+            warn!("Failed to emit {} event: {}", event.get(), e);
+        }
+    }
+}
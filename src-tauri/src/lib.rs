@@ -29,6 +29,9 @@ pub mod ai_integration;
 pub mod events;
 pub mod tts;
 
+#[cfg(test)]
+mod test_utils;
+
 // Экспорт основных типов и функций
 pub use error::{JarvisResult, JarvisError};
 pub use commands::structs::AssistantCommand;
@@ -0,0 +1,364 @@
+// Режим калибровки порога срабатывания wake-word
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use log::{info, warn};
+
+use crate::config;
+use crate::db;
+use crate::error::{JarvisResult, JarvisError, ListenerError};
+
+// Активная сессия калибровки
+static CALIBRATION: Mutex<Option<CalibrationSession>> = Mutex::new(None);
+
+struct CalibrationSession {
+    started_at: chrono::DateTime<chrono::Utc>,
+    samples: Vec<ScoreSample>,
+}
+
+/// Оценка одного срабатывания детектора
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoreSample {
+    pub keyword: String,
+    pub score: f32,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    // оценка превысила порог и ассистент активировался (пользователь это услышал)
+    pub activated: bool,
+    // true - настоящая активация, false - ложное срабатывание, None - не подтверждено
+    pub is_true_activation: Option<bool>,
+}
+
+/// Итоги сессии калибровки
+#[derive(Debug, Clone, Serialize)]
+pub struct CalibrationReport {
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub samples: Vec<ScoreSample>,
+    pub true_activations: usize,
+    pub false_positives: usize,
+    // предложенный порог для каждого wake-word с подтвержденными активациями
+    pub suggested_thresholds: BTreeMap<String, f32>,
+}
+
+/// Начало сессии калибровки
+pub fn start() {
+    if let Ok(mut session) = CALIBRATION.lock() {
+        *session = Some(CalibrationSession {
+            started_at: chrono::Utc::now(),
+            samples: Vec::new(),
+        });
+    }
+
+    info!("Wake-word calibration started");
+}
+
+/// Завершение сессии калибровки
+pub fn stop() -> JarvisResult<CalibrationReport> {
+    let session = lock_session()?.take().ok_or_else(no_session_error)?;
+    let report = build_report(&session);
+
+    info!("Wake-word calibration stopped: {} samples, suggested thresholds {:?}",
+          report.samples.len(), report.suggested_thresholds);
+    Ok(report)
+}
+
+/// Проверка активности калибровки
+pub fn is_active() -> bool {
+    CALIBRATION.lock().map(|session| session.is_some()).unwrap_or(false)
+}
+
+/// Текущее состояние калибровки
+pub fn status() -> Option<CalibrationReport> {
+    CALIBRATION.lock().ok()?.as_ref().map(build_report)
+}
+
+/// Запись оценки срабатывания (вызывается движком для каждого срабатывания,
+/// в том числе ниже порога; `activated` - срабатывание активировало ассистента)
+pub fn record_score(keyword: &str, score: f32, activated: bool) {
+    if let Ok(mut session) = CALIBRATION.lock() {
+        if let Some(session) = session.as_mut() {
+            session.samples.push(ScoreSample {
+                keyword: keyword.to_string(),
+                score,
+                timestamp: chrono::Utc::now(),
+                activated,
+                is_true_activation: None,
+            });
+        }
+    }
+}
+
+/// Отмена последней активации (отклонена проверкой через STT, пользователь ее не услышал)
+pub fn revoke_last_activation() {
+    if let Ok(mut session) = CALIBRATION.lock() {
+        if let Some(sample) = session.as_mut().and_then(|session| last_unlabeled_activation(&mut session.samples)) {
+            sample.activated = false;
+        }
+    }
+}
+
+/// Подтверждение пользователем последней неразмеченной активации
+/// Срабатывания ниже порога пользователь не слышал, поэтому они не размечаются
+pub fn label_last(is_true_activation: bool) -> JarvisResult<ScoreSample> {
+    let mut session = lock_session()?;
+    let session = session.as_mut().ok_or_else(no_session_error)?;
+
+    let sample = last_unlabeled_activation(&mut session.samples)
+        .ok_or_else(|| JarvisError::ListenerError(ListenerError::ProcessingError(
+            "No unlabeled activations to confirm".to_string()
+        )))?;
+
+    sample.is_true_activation = Some(is_true_activation);
+    Ok(sample.clone())
+}
+
+fn last_unlabeled_activation(samples: &mut [ScoreSample]) -> Option<&mut ScoreSample> {
+    samples.iter_mut()
+        .rev()
+        .find(|sample| sample.activated && sample.is_true_activation.is_none())
+}
+
+/// Запись предложенных порогов в настройки wake-word файлов и перезагрузка движка
+/// Порог файла имеет приоритет над общим min_score, поэтому записывается именно он
+pub fn apply_suggested_thresholds() -> JarvisResult<BTreeMap<String, f32>> {
+    let thresholds = status()
+        .map(|report| report.suggested_thresholds)
+        .filter(|thresholds| !thresholds.is_empty())
+        .ok_or_else(|| JarvisError::ListenerError(ListenerError::ProcessingError(
+            "Not enough confirmed detections to suggest a threshold".to_string()
+        )))?;
+
+    let mut applied = BTreeMap::new();
+    db::update_settings(|settings| {
+        for file in settings.wake_word.rustpotter.wake_word_files.iter_mut() {
+            if let Some(&threshold) = thresholds.get(&file_keyword(&file.path)) {
+                file.threshold = Some(threshold);
+                applied.insert(file_keyword(&file.path), threshold);
+            }
+        }
+        settings.touch();
+    })?;
+
+    for keyword in thresholds.keys().filter(|keyword| !applied.contains_key(*keyword)) {
+        warn!("Wake-word '{}' has no configured file, threshold not applied", keyword);
+    }
+
+    super::reload()?;
+
+    info!("Wake-word thresholds set: {:?}", applied);
+    Ok(applied)
+}
+
+// имя wake-word, под которым Rustpotter сообщает о срабатывании (имя файла без расширения)
+fn file_keyword(path: &str) -> String {
+    Path::new(path).file_stem().unwrap_or_default().to_string_lossy().to_string()
+}
+
+/// Подбор порога отдельно для каждого wake-word
+/// Порог не опускается ниже `detector_threshold`: более слабые оценки Rustpotter не сообщает
+pub fn suggest_thresholds(samples: &[ScoreSample], detector_threshold: f32) -> BTreeMap<String, f32> {
+    let mut by_keyword: BTreeMap<&str, Vec<ScoreSample>> = BTreeMap::new();
+    for sample in samples {
+        by_keyword.entry(sample.keyword.as_str()).or_default().push(sample.clone());
+    }
+
+    by_keyword.into_iter()
+        .filter_map(|(keyword, samples)| {
+            Some((keyword.to_string(), suggest_threshold(&samples)?.max(detector_threshold)))
+        })
+        .collect()
+}
+
+/// Подбор порога по размеченным оценкам одного wake-word
+/// Порог выбирается так, чтобы минимизировать сумму пропусков и ложных срабатываний
+/// (срабатывание засчитывается при score > threshold, некорректные оценки пропускаются)
+pub fn suggest_threshold(samples: &[ScoreSample]) -> Option<f32> {
+    let mut true_scores = samples.iter()
+        .filter(|s| s.is_true_activation == Some(true) && s.score.is_finite())
+        .map(|s| s.score)
+        .collect::<Vec<_>>();
+    let false_scores = samples.iter()
+        .filter(|s| s.is_true_activation == Some(false) && s.score.is_finite())
+        .map(|s| s.score)
+        .collect::<Vec<_>>();
+
+    if true_scores.is_empty() {
+        return None;
+    }
+
+    true_scores.sort_by(f32::total_cmp);
+    let min_true = true_scores[0];
+
+    // Без ложных срабатываний оставляем запас ниже самой слабой активации
+    let max_false = match false_scores.iter().cloned().reduce(f32::max) {
+        Some(max_false) => max_false,
+        None => return Some((min_true - config::CALIBRATION_THRESHOLD_MARGIN).max(0.0)),
+    };
+
+    // Классы разделимы - порог посередине между ними
+    if max_false < min_true {
+        return Some((max_false + min_true) / 2.0);
+    }
+
+    // Иначе перебираем кандидатов между соседними оценками
+    let mut all_scores = true_scores.iter().chain(false_scores.iter()).cloned().collect::<Vec<_>>();
+    all_scores.sort_by(f32::total_cmp);
+    all_scores.dedup();
+
+    let errors = |threshold: f32| {
+        true_scores.iter().filter(|&&s| s <= threshold).count()
+            + false_scores.iter().filter(|&&s| s > threshold).count()
+    };
+
+    all_scores.windows(2)
+        .map(|pair| (pair[0] + pair[1]) / 2.0)
+        .min_by_key(|&threshold| errors(threshold))
+}
+
+fn build_report(session: &CalibrationSession) -> CalibrationReport {
+    CalibrationReport {
+        started_at: session.started_at,
+        samples: session.samples.clone(),
+        true_activations: session.samples.iter().filter(|s| s.is_true_activation == Some(true)).count(),
+        false_positives: session.samples.iter().filter(|s| s.is_true_activation == Some(false)).count(),
+        suggested_thresholds: suggest_thresholds(&session.samples, detector_threshold()),
+    }
+}
+
+// порог детектора Rustpotter: оценки ниже него не сообщаются
+fn detector_threshold() -> f32 {
+    db::get_current_settings()
+        .unwrap_or_default()
+        .wake_word.rustpotter.detector.threshold
+}
+
+fn lock_session() -> JarvisResult<std::sync::MutexGuard<'static, Option<CalibrationSession>>> {
+    CALIBRATION.lock().map_err(|e| JarvisError::ListenerError(ListenerError::ProcessingError(
+        format!("Failed to lock calibration session: {}", e)
+    )))
+}
+
+fn no_session_error() -> JarvisError {
+    JarvisError::ListenerError(ListenerError::ProcessingError(
+        "Wake-word calibration is not active".to_string()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(score: f32, is_true_activation: Option<bool>) -> ScoreSample {
+        keyword_sample("jarvis-default", score, is_true_activation)
+    }
+
+    fn keyword_sample(keyword: &str, score: f32, is_true_activation: Option<bool>) -> ScoreSample {
+        ScoreSample {
+            keyword: keyword.to_string(),
+            score,
+            timestamp: chrono::Utc::now(),
+            activated: true,
+            is_true_activation,
+        }
+    }
+
+    #[test]
+    fn test_suggest_threshold_requires_true_activations() {
+        assert_eq!(suggest_threshold(&[]), None);
+        assert_eq!(suggest_threshold(&[sample(0.4, Some(false)), sample(0.7, None)]), None);
+    }
+
+    #[test]
+    fn test_suggest_threshold_separable() {
+        let samples = vec![
+            sample(0.45, Some(false)),
+            sample(0.55, Some(false)),
+            sample(0.65, Some(true)),
+            sample(0.8, Some(true)),
+            sample(0.3, None),
+        ];
+
+        let threshold = suggest_threshold(&samples).unwrap();
+        assert!((threshold - 0.6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_suggest_threshold_without_false_positives() {
+        let samples = vec![sample(0.7, Some(true)), sample(0.9, Some(true))];
+
+        let threshold = suggest_threshold(&samples).unwrap();
+        assert!((threshold - (0.7 - config::CALIBRATION_THRESHOLD_MARGIN)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_suggest_threshold_overlapping() {
+        let samples = vec![
+            sample(0.5, Some(false)),
+            sample(0.52, Some(false)),
+            sample(0.66, Some(false)),
+            sample(0.6, Some(true)),
+            sample(0.7, Some(true)),
+            sample(0.75, Some(true)),
+        ];
+
+        // Лучший порог пропускает одно ложное срабатывание и не теряет активаций
+        let threshold = suggest_threshold(&samples).unwrap();
+        assert!(threshold > 0.52 && threshold < 0.6);
+    }
+
+    #[test]
+    fn test_suggest_thresholds_per_keyword() {
+        let samples = vec![
+            keyword_sample("jarvis", 0.45, Some(false)),
+            keyword_sample("jarvis", 0.65, Some(true)),
+            keyword_sample("friday", 0.25, Some(false)),
+            keyword_sample("friday", 0.35, Some(true)),
+            keyword_sample("alice", 0.9, Some(false)),
+        ];
+
+        let thresholds = suggest_thresholds(&samples, 0.0);
+        assert_eq!(thresholds.len(), 2);
+        assert!((thresholds["jarvis"] - 0.55).abs() < 1e-6);
+        assert!((thresholds["friday"] - 0.3).abs() < 1e-6);
+
+        // ниже порога детектора предлагать бессмысленно
+        let thresholds = suggest_thresholds(&samples, 0.4);
+        assert!((thresholds["jarvis"] - 0.55).abs() < 1e-6);
+        assert!((thresholds["friday"] - 0.4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_only_activations_are_labeled() {
+        let mut samples = vec![
+            sample(0.7, None),
+            ScoreSample { activated: false, ..sample(0.3, None) },
+        ];
+
+        // последняя оценка ниже порога, пользователь слышал только первую
+        let labeled = last_unlabeled_activation(&mut samples).unwrap();
+        assert_eq!(labeled.score, 0.7);
+        labeled.is_true_activation = Some(true);
+
+        assert!(last_unlabeled_activation(&mut samples).is_none());
+    }
+
+    #[test]
+    fn test_suggest_threshold_with_nan_score() {
+        let samples = vec![
+            sample(f32::NAN, Some(true)),
+            sample(0.7, Some(true)),
+            sample(0.5, Some(false)),
+        ];
+
+        let threshold = suggest_threshold(&samples).unwrap();
+        assert!((threshold - 0.6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_file_keyword() {
+        assert_eq!(file_keyword("jarvis-default.rpw"), "jarvis-default");
+        assert_eq!(file_keyword("/home/user/wake/friday.rpw"), "friday");
+    }
+}
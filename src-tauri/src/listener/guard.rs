@@ -0,0 +1,97 @@
+// Защита от ложных срабатываний: подтверждение wake-word через STT
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use log::{info, warn};
+
+use crate::config;
+use crate::config::structs::WakeWordEngine;
+use crate::audio::preroll;
+use super::calibration;
+use crate::{db, stt};
+
+// Количество отклоненных срабатываний за сессию
+static REJECTED_ACTIVATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Включена ли проверка срабатываний через STT
+/// Для Vosk проверка не нужна - он сам распознает фразу
+pub fn is_enabled(engine: Option<WakeWordEngine>) -> bool {
+    let enabled = db::get_current_settings()
+        .map(|settings| settings.wake_word.stt_confirmation)
+        .unwrap_or(false);
+
+    enabled && engine != Some(WakeWordEngine::Vosk)
+}
+
+/// Проверка срабатывания: последние `GUARD_HISTORY_MS` аудио из pre-roll буфера
+/// (включая дозаписанный после детекции хвост) распознаются Vosk и сравниваются с фразой
+/// обнаруженного wake-word (см. `listener::keyword_phrase`)
+pub fn confirm_activation(phrase: &str) -> bool {
+    let audio = preroll::latest(config::GUARD_HISTORY_MS);
+
+    let transcript = match stt::transcribe(&audio) {
+        Ok(Some(transcript)) => transcript,
+        Ok(None) => String::new(),
+        Err(e) => {
+            // без STT проверить нечего - не блокируем активацию
+            warn!("Wake-word guard: {}, activation accepted without confirmation", e);
+            return true;
+        }
+    };
+
+    if transcript_matches(&transcript, phrase, config::VOSK_MIN_RATIO) {
+        info!("Wake-word guard: activation confirmed ('{}')", transcript);
+        true
+    } else {
+        let rejected = REJECTED_ACTIVATIONS.fetch_add(1, Ordering::SeqCst) + 1;
+        warn!("Wake-word guard: activation rejected, transcript '{}' does not match '{}' (rejected total: {})", transcript, phrase, rejected);
+        calibration::revoke_last_activation();
+        false
+    }
+}
+
+/// Количество отклоненных срабатываний
+pub fn rejected_activations() -> usize {
+    REJECTED_ACTIVATIONS.load(Ordering::SeqCst)
+}
+
+/// Нечеткое сравнение фразы активации с транскриптом: каждое слово фразы должно
+/// найтись среди слов транскрипта
+pub fn transcript_matches(transcript: &str, phrase: &str, min_ratio: f64) -> bool {
+    let words = transcript.split_whitespace()
+        .map(|word| word.trim().to_lowercase().chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let mut phrase_words = phrase.split_whitespace().peekable();
+    if phrase_words.peek().is_none() {
+        return false;
+    }
+
+    phrase_words.all(|phrase_word| {
+        let phrase_chars = phrase_word.to_lowercase().chars().collect::<Vec<_>>();
+        words.iter().any(|word_chars| seqdiff::ratio(&phrase_chars, word_chars) >= min_ratio)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcript_matches() {
+        assert!(transcript_matches("джарвис включи музыку", "джарвис", config::VOSK_MIN_RATIO));
+        assert!(transcript_matches("ну Джарвиз", "джарвис", config::VOSK_MIN_RATIO));
+        assert!(!transcript_matches("сегодня в новостях", "джарвис", config::VOSK_MIN_RATIO));
+        assert!(!transcript_matches("", "джарвис", config::VOSK_MIN_RATIO));
+    }
+
+    #[test]
+    fn test_transcript_matches_bound_phrase() {
+        assert!(transcript_matches("эй пятница включи свет", "эй пятница", config::VOSK_MIN_RATIO));
+        assert!(transcript_matches("Friday", "friday", config::VOSK_MIN_RATIO));
+        // все слова фразы должны прозвучать
+        assert!(!transcript_matches("пятница", "эй пятница", config::VOSK_MIN_RATIO));
+        assert!(!transcript_matches("джарвис", "пятница", config::VOSK_MIN_RATIO));
+        assert!(!transcript_matches("джарвис", "  ", config::VOSK_MIN_RATIO));
+    }
+}
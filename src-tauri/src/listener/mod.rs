@@ -2,18 +2,178 @@
 pub mod porcupine;
 pub mod rustpotter;
 pub mod vosk;
+pub mod calibration;
+pub mod guard;
 
-// Re-export based on configuration
-pub use rustpotter::*; // default
+#[cfg(feature = "experimental-voice-training")]
+pub mod training;
 
-use crate::error::JarvisResult;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 
+use crate::config;
+use crate::config::structs::{WakeWordAction, WakeWordEngine};
+use crate::{db, stt};
+use crate::error::{JarvisResult, JarvisError, ListenerError};
+use log::{info, warn};
+
+use self::porcupine::PorcupineDetector;
+use self::rustpotter::RustpotterDetector;
+use self::vosk::VoskDetector;
+
+/// Общий интерфейс движков распознавания wake-word
+pub trait WakeWordDetector: Send {
+    /// Тип движка
+    fn engine(&self) -> WakeWordEngine;
+
+    /// Обработка кадра, возвращает индекс обнаруженного ключевого слова
+    fn process(&mut self, frame_buffer: &[i16]) -> Option<i32>;
+
+    /// Имя ключевого слова по его индексу
+    fn keyword_name(&self, keyword_index: i32) -> Option<String>;
+}
+
+// Активный движок wake-word
+static DETECTOR: Mutex<Option<Box<dyn WakeWordDetector>>> = Mutex::new(None);
+
+// Число активных пауз: пока больше нуля, главный цикл не читает микрофон
+static PAUSE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Пауза прослушивания, снимается при удалении
+pub struct ListenerPause(());
+
+impl Drop for ListenerPause {
+    fn drop(&mut self) {
+        if PAUSE_COUNT.fetch_sub(1, Ordering::SeqCst) == 1 {
+            info!("Wake-word listening resumed");
+        }
+    }
+}
+
+/// Инициализация движка, выбранного в настройках
 pub fn init() -> JarvisResult<()> {
-    // Initialize default wake word engine
-    rustpotter::init();
+    let engine = db::get_current_settings()
+        .map(|settings| settings.wake_word_engine)
+        .unwrap_or(config::DEFAULT_WAKE_WORD_ENGINE);
+
+    let detector = create_detector(engine)?;
+    info!("Wake-word engine initialized: {:?}", detector.engine());
+
+    *lock_detector()? = Some(detector);
+    Ok(())
+}
+
+/// Переинициализация движка после изменения настроек
+pub fn reload() -> JarvisResult<()> {
+    info!("Reloading wake-word engine...");
+    init()
+}
+
+/// Создание движка с fallback на Vosk, если для Porcupine не задан API ключ
+fn create_detector(engine: WakeWordEngine) -> JarvisResult<Box<dyn WakeWordDetector>> {
+    match engine {
+        WakeWordEngine::Rustpotter => Ok(Box::new(RustpotterDetector::new()?)),
+        WakeWordEngine::Vosk => create_vosk_detector(),
+        WakeWordEngine::Porcupine => match PorcupineDetector::new() {
+            Ok(detector) => Ok(Box::new(detector)),
+            Err(JarvisError::ListenerError(ListenerError::ApiKeyMissing)) => {
+                warn!("Picovoice API key is missing, falling back to Vosk wake-word engine");
+                create_vosk_detector()
+            }
+            Err(e) => Err(e),
+        },
+    }
+}
+
+// Vosk использует распознаватель STT; без STT используется Rustpotter, чтобы wake-word продолжал работать
+fn create_vosk_detector() -> JarvisResult<Box<dyn WakeWordDetector>> {
+    if let Some(reason) = stt::unavailable_reason() {
+        warn!("Vosk wake-word engine is unavailable ({}), falling back to Rustpotter", reason);
+        return Ok(Box::new(RustpotterDetector::new()?));
+    }
+
+    Ok(Box::new(VoskDetector::new()?))
+}
+
+fn lock_detector() -> JarvisResult<std::sync::MutexGuard<'static, Option<Box<dyn WakeWordDetector>>>> {
+    DETECTOR.lock().map_err(|e| JarvisError::ListenerError(ListenerError::ProcessingError(
+        format!("Failed to lock wake-word detector: {}", e)
+    )))
+}
+
+/// Обработка кадра активным движком
+pub fn data_callback(frame_buffer: &[i16]) -> Option<i32> {
+    lock_detector().ok()?.as_mut()?.process(frame_buffer)
+}
+
+/// Тип активного движка
+pub fn current_engine() -> Option<WakeWordEngine> {
+    lock_detector().ok()?.as_ref().map(|detector| detector.engine())
+}
+
+/// Определение поведения по индексу обнаруженного wake-word
+pub fn keyword_action(keyword_index: i32) -> WakeWordAction {
+    let keyword = match lock_detector().ok().and_then(|d| d.as_ref()?.keyword_name(keyword_index)) {
+        Some(keyword) => keyword,
+        None => return WakeWordAction::Command,
+    };
+
+    let action = db::get_current_settings()
+        .map(|settings| settings.wake_word_action(&keyword))
+        .unwrap_or(WakeWordAction::Command);
+
+    info!("Wake-word '{}' mapped to {:?}", keyword, action);
+    action
+}
+
+/// Фраза, которую произносит пользователь для обнаруженного wake-word
+/// Привязанные слова произносятся как их имя ("hey_friday" -> "hey friday"),
+/// встроенные модели - как `VOSK_FETCH_PHRASE`
+pub fn keyword_phrase(keyword_index: i32) -> String {
+    let keyword = lock_detector().ok().and_then(|d| d.as_ref()?.keyword_name(keyword_index));
+
+    let bound = keyword.filter(|keyword| {
+        db::get_current_settings()
+            .map(|settings| settings.wake_word.bindings.iter()
+                .any(|binding| binding.matches(keyword)))
+            .unwrap_or(false)
+    });
+
+    match bound {
+        Some(keyword) => spoken_phrase(&keyword),
+        None => config::VOSK_FETCH_PHRASE.to_string(),
+    }
+}
+
+/// Произносимые фразы всех привязанных wake-word (для грамматики распознавания)
+pub fn binding_phrases() -> Vec<String> {
+    db::get_current_settings()
+        .map(|settings| settings.wake_word.bindings.iter()
+            .map(|binding| spoken_phrase(&binding.keyword))
+            .filter(|phrase| !phrase.is_empty())
+            .collect())
+        .unwrap_or_default()
+}
+
+// имя ключевого слова (файла модели) в виде произносимой фразы
+fn spoken_phrase(keyword: &str) -> String {
+    keyword.replace(['_', '-'], " ").trim().to_lowercase()
+}
+
+/// Приостановка главного цикла (микрофон нужен другому потребителю)
+pub fn pause() -> ListenerPause {
+    if PAUSE_COUNT.fetch_add(1, Ordering::SeqCst) == 0 {
+        info!("Wake-word listening paused");
+    }
+    ListenerPause(())
+}
+
+pub fn is_paused() -> bool {
+    PAUSE_COUNT.load(Ordering::SeqCst) > 0
 }
 
 pub fn shutdown() -> JarvisResult<()> {
-    log::info!("Listener shutdown completed");
+    *lock_detector()? = None;
+    info!("Listener shutdown completed");
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,56 +1,87 @@
-use std::path::Path;
-
-use once_cell::sync::OnceCell;
 use porcupine::{Porcupine, PorcupineBuilder};
 
+use super::WakeWordDetector;
 use crate::db;
 use crate::config;
+use crate::config::structs::WakeWordEngine;
+use crate::error::{JarvisResult, JarvisError, ListenerError};
 use log::{info, warn, error};
 
-// store porcupine instance
-static PORCUPINE: OnceCell<Porcupine> = OnceCell::new();
+pub struct PorcupineDetector {
+    porcupine: Porcupine,
+    // loaded keyword names, in the same order as passed to porcupine
+    keyword_names: Vec<String>,
+}
 
-pub fn init() -> Result<(), ()> {
-    let picovoice_api_key: String;
+impl PorcupineDetector {
+    pub fn new() -> JarvisResult<Self> {
+        let settings = db::get_current_settings().unwrap_or_default();
 
-    // retrieve picovoice api key
-    picovoice_api_key = db.get().unwrap().api_keys.picovoice.clone();
-    if picovoice_api_key.trim().is_empty() {
-        warn!("Picovoice API key is not set.");
-        return Err(())
-    }
+        // retrieve picovoice api key
+        let picovoice_api_key = settings.api_keys.picovoice.trim().to_string();
+        if picovoice_api_key.is_empty() {
+            warn!("Picovoice API key is not set.");
+            return Err(JarvisError::ListenerError(ListenerError::ApiKeyMissing));
+        }
+
+        // collect keyword files: default one + every bound keyword that has a .ppn file
+        let keywords_dir = crate::config::get_keywords_path()?;
+        let mut keyword_paths = vec![keywords_dir.join(config::DEFAULT_KEYWORD)];
 
-    // create porcupine instance with the given API key
-    let keywords_dir = crate::config::get_keywords_path()?;
-    let keyword_path = keywords_dir.join(config::DEFAULT_KEYWORD);
-
-    match PorcupineBuilder::new_with_keyword_paths(picovoice_api_key, &[keyword_path])
-        .sensitivities(&[config::DEFAULT_SENSITIVITY]) // set sensitivity
-        .init() {
-            Ok(pinstance) => {
-                // success
-                info!("Porcupine successfully initialized with the given API key.");
-
-                // store
-                PORCUPINE.set(pinstance);
-            },
-            Err(msg) => {
-                error!("Porcupine failed to initialize, either API key is not valid or there is no internet connection.");
-                error!("Error details: {}", msg);
-
-                return Err(());
+        for binding in &settings.wake_word.bindings {
+            let keyword_path = keywords_dir.join(format!("{}.ppn", binding.keyword));
+            if keyword_path.exists() && !keyword_paths.contains(&keyword_path) {
+                keyword_paths.push(keyword_path);
             }
-    }
+        }
+
+        let keyword_names = keyword_paths.iter()
+            .map(|p| p.file_stem().unwrap_or_default().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        let sensitivities = vec![config::DEFAULT_SENSITIVITY; keyword_paths.len()];
+
+        // create porcupine instance with the given API key
+        match PorcupineBuilder::new_with_keyword_paths(picovoice_api_key, &keyword_paths)
+            .sensitivities(&sensitivities) // set sensitivity
+            .init() {
+                Ok(pinstance) => {
+                    // success
+                    info!("Porcupine successfully initialized with the given API key.");
+                    info!("Porcupine keywords: {:?}", keyword_names);
 
-    Ok(())
+                    Ok(Self {
+                        porcupine: pinstance,
+                        keyword_names,
+                    })
+                },
+                Err(msg) => {
+                    error!("Porcupine failed to initialize, either API key is not valid or there is no internet connection.");
+                    error!("Error details: {}", msg);
+
+                    Err(JarvisError::ListenerError(ListenerError::EngineInitializationFailed(
+                        format!("Porcupine: {}", msg)
+                    )))
+                }
+        }
+    }
 }
 
-pub fn data_callback(frame_buffer: &[i16]) -> Option<i32> {
-    if let Ok(keyword_index) = PORCUPINE.get().unwrap().process(&frame_buffer) {
-        if keyword_index >= 0 {
-            return Some(keyword_index)
+impl WakeWordDetector for PorcupineDetector {
+    fn engine(&self) -> WakeWordEngine {
+        WakeWordEngine::Porcupine
+    }
+
+    fn process(&mut self, frame_buffer: &[i16]) -> Option<i32> {
+        if let Ok(keyword_index) = self.porcupine.process(&frame_buffer) {
+            if keyword_index >= 0 {
+                return Some(keyword_index)
+            }
         }
+
+        None
     }
 
-    None
-}
\ No newline at end of file
+    fn keyword_name(&self, keyword_index: i32) -> Option<String> {
+        self.keyword_names.get(keyword_index as usize).cloned()
+    }
+}
@@ -1,71 +1,138 @@
-use std::path::Path;
+use std::collections::HashMap;
 use std::sync::Mutex;
 
-use once_cell::sync::OnceCell;
-use rustpotter::{Rustpotter, RustpotterConfig, WavFmt, DetectorConfig, FiltersConfig, ScoreMode, GainNormalizationConfig, BandPassConfig};
+use rustpotter::Rustpotter;
 
+use super::{calibration, WakeWordDetector};
 use crate::db;
 use crate::config;
-use log::{warn, info, error};
-// store rustpotter instance
-static RUSTPOTTER: OnceCell<Mutex<Rustpotter>> = OnceCell::new();
-
-pub fn init() -> Result<(), ()> {
-    let rustpotter_config = config::RUSTPOTTER_DEFAULT_CONFIG;
-
-    // create rustpotter instance
-    match Rustpotter::new(&rustpotter_config) {
-        Ok(mut rinstance) => {
-            // success
-            // wake word files list
-            // @TODO. Make it configurable via GUI for custom user voice.
-            let rustpotter_dir = crate::config::get_rustpotter_path()?;
-
-            let rustpotter_wake_word_files = [
-                rustpotter_dir.join("jarvis-default.rpw"),
-                rustpotter_dir.join("jarvis-community-1.rpw"),
-                rustpotter_dir.join("jarvis-community-2.rpw"),
-                rustpotter_dir.join("jarvis-community-3.rpw"),
-                rustpotter_dir.join("jarvis-community-4.rpw"),
-            ];
-
-            // load wake word files
-            for rpw_path in rustpotter_wake_word_files {
-                rinstance.add_wakeword_from_file(&rpw_path).unwrap();
-            }
+use crate::config::structs::WakeWordEngine;
+use crate::error::{JarvisResult, JarvisError, ListenerError};
+use log::{debug, warn, info, error};
 
-            // load wake word files
-            for rpw in rustpotter_wake_word_files {
-                rinstance.add_wakeword_from_file(rpw).unwrap();
-            }
+// configured wake word files which were not found on disk
+static MISSING_WAKE_WORD_FILES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+pub struct RustpotterDetector {
+    rustpotter: Rustpotter,
+    // names of loaded wake words, position in the list is the keyword index
+    keyword_names: Vec<String>,
+    // detection thresholds: default one + per wake word overrides
+    min_score: f32,
+    keyword_thresholds: HashMap<String, f32>,
+}
 
-            // store
-            RUSTPOTTER.set(Mutex::new(rinstance));
-        },
-        Err(msg) => {
+impl RustpotterDetector {
+    pub fn new() -> JarvisResult<Self> {
+        let settings = db::get_current_settings()
+            .map(|settings| settings.wake_word.rustpotter)
+            .unwrap_or_default();
+        let rustpotter_config = config::build_rustpotter_config(&settings);
+
+        // create rustpotter instance
+        let mut rinstance = Rustpotter::new(&rustpotter_config).map_err(|msg| {
             error!("Rustpotter failed to initialize.\nError details: {}", msg);
+            JarvisError::ListenerError(ListenerError::EngineInitializationFailed(
+                format!("Rustpotter: {}", msg)
+            ))
+        })?;
 
-            return Err(());
+        // wake word files from settings (user directory first, then bundled ones)
+        let resolution = config::resolve_rustpotter_files(&settings.wake_word_files)?;
+        for missing in &resolution.missing {
+            warn!("Rustpotter wake-word file not found: {}", missing);
         }
+
+        // load wake word files
+        let mut keyword_names = Vec::new();
+        let mut keyword_thresholds = HashMap::new();
+        let mut failed_files = resolution.missing.clone();
+
+        for wake_word_file in resolution.found {
+            match rinstance.add_wakeword_from_file(&wake_word_file.key, &wake_word_file.path.to_string_lossy()) {
+                Ok(_) => {
+                    info!("Loaded wake-word file: {}", wake_word_file.path.display());
+
+                    if let Some(threshold) = wake_word_file.threshold {
+                        keyword_thresholds.insert(wake_word_file.key.clone(), threshold);
+                    }
+                    keyword_names.push(wake_word_file.key);
+                },
+                Err(msg) => {
+                    warn!("Failed to load wake-word file {}: {}", wake_word_file.path.display(), msg);
+                    failed_files.push(wake_word_file.path.to_string_lossy().to_string());
+                }
+            }
+        }
+
+        *MISSING_WAKE_WORD_FILES.lock().unwrap() = failed_files;
+
+        if keyword_names.is_empty() {
+            return Err(JarvisError::ListenerError(ListenerError::ModelLoadingFailed(
+                "No Rustpotter wake-word files could be loaded".to_string()
+            )));
+        }
+
+        info!("Loaded {} wake-word files for Rustpotter", keyword_names.len());
+
+        Ok(Self {
+            rustpotter: rinstance,
+            keyword_names,
+            min_score: settings.min_score,
+            keyword_thresholds,
+        })
     }
 
-    Ok(())
+    // threshold for the given wake word (per-file override or default min score)
+    fn detection_threshold(&self, name: &str) -> f32 {
+        self.keyword_thresholds.get(name).copied().unwrap_or(self.min_score)
+    }
+
+    // map wake word name to a stable keyword index
+    fn keyword_index(&mut self, name: &str) -> i32 {
+        match self.keyword_names.iter().position(|n| n == name) {
+            Some(idx) => idx as i32,
+            None => {
+                self.keyword_names.push(name.to_string());
+                (self.keyword_names.len() - 1) as i32
+            }
+        }
+    }
 }
 
-pub fn data_callback(frame_buffer: &[i16]) -> Option<i32> {
-    let mut lock = RUSTPOTTER.get().unwrap().lock();
-    let rustpotter = lock.as_mut().unwrap();
-    let detection = rustpotter.process_i16(&frame_buffer);
+impl WakeWordDetector for RustpotterDetector {
+    fn engine(&self) -> WakeWordEngine {
+        WakeWordEngine::Rustpotter
+    }
+
+    fn process(&mut self, frame_buffer: &[i16]) -> Option<i32> {
+        let detection = self.rustpotter.process_i16(&frame_buffer);
+
+        if let Some(detection) = detection {
+            let activated = detection.score > self.detection_threshold(&detection.name);
 
-    if let Some(detection) = detection {
-        if detection.score > config::RUSPOTTER_MIN_SCORE {
-            info!("Rustpotter detection info:\n{:?}", detection);
+            // every raw score is collected while calibrating
+            calibration::record_score(&detection.name, detection.score, activated);
 
-            return Some(0)
-        } else {
-            info!("Rustpotter detection info:\n{:?}", detection)
+            if activated {
+                info!("Rustpotter detection: '{}' score={:.3}", detection.name, detection.score);
+                debug!("Rustpotter detection info:\n{:?}", detection);
+
+                return Some(self.keyword_index(&detection.name))
+            } else {
+                debug!("Rustpotter detection below threshold:\n{:?}", detection)
+            }
         }
+
+        None
     }
 
-    None
-}
\ No newline at end of file
+    fn keyword_name(&self, keyword_index: i32) -> Option<String> {
+        self.keyword_names.get(keyword_index as usize).cloned()
+    }
+}
+
+/// Wake-word файлы из настроек, которые не удалось найти или загрузить
+pub fn missing_wake_word_files() -> Vec<String> {
+    MISSING_WAKE_WORD_FILES.lock().unwrap().clone()
+}
@@ -0,0 +1,333 @@
+// Обучение собственного wake-word на записанных образцах (experimental-voice-training)
+
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use rustpotter::{Rustpotter, WakewordRef, WakewordRefBuildFromBuffers, WakewordSave};
+use serde::Serialize;
+use log::{info, warn};
+
+use crate::audio::recorder;
+use crate::listener;
+use crate::db::{self, structs::RustpotterWakeWordFile};
+use crate::config;
+use crate::error::{JarvisResult, JarvisError, ListenerError, RecorderError};
+
+// Текущая сессия записи образцов
+static TRAINING_SESSION: Mutex<Option<TrainingSession>> = Mutex::new(None);
+
+struct TrainingSession {
+    name: String,
+    samples: Vec<Vec<i16>>,
+}
+
+/// Результат проверки обученного wake-word
+#[derive(Debug, Clone, Serialize)]
+pub struct TrainingTestReport {
+    pub name: String,
+    pub detections: usize,
+    pub max_score: f32,
+    pub avg_score: f32,
+    pub scores: Vec<f32>,
+}
+
+/// Начало новой сессии обучения
+pub fn start_session(name: &str) -> JarvisResult<()> {
+    ensure_training_enabled()?;
+
+    let name = validate_name(name)?;
+
+    *TRAINING_SESSION.lock().unwrap() = Some(TrainingSession {
+        name: name.to_string(),
+        samples: Vec::new(),
+    });
+
+    info!("Voice training session started for wake-word '{}'", name);
+    Ok(())
+}
+
+/// Запись одного образца произношения wake-word через активный рекордер
+/// Возвращает количество записанных образцов в сессии
+pub fn record_sample() -> JarvisResult<usize> {
+    ensure_training_enabled()?;
+
+    if TRAINING_SESSION.lock().unwrap().is_none() {
+        return Err(no_session_error());
+    }
+
+    let samples = record_audio(config::TRAINING_SAMPLE_DURATION)?;
+    let samples = trim_silence(&samples, config::TRAINING_SILENCE_LEVEL);
+
+    if samples.len() < (config::TRAINING_SAMPLE_RATE as usize / 4) {
+        return Err(JarvisError::ListenerError(ListenerError::ProcessingError(
+            "Recorded sample is too short or silent, please repeat".to_string()
+        )));
+    }
+
+    let mut session = TRAINING_SESSION.lock().unwrap();
+    let session = session.as_mut().ok_or_else(no_session_error)?;
+    session.samples.push(samples);
+
+    info!("Recorded training sample {} for '{}'", session.samples.len(), session.name);
+    Ok(session.samples.len())
+}
+
+/// Построение .rpw файла из записанных образцов и сохранение в пользовательскую директорию
+pub fn build_wakeword() -> JarvisResult<PathBuf> {
+    ensure_training_enabled()?;
+
+    let session = TRAINING_SESSION.lock().unwrap();
+    let session = session.as_ref().ok_or_else(no_session_error)?;
+
+    if session.samples.len() < config::TRAINING_MIN_SAMPLES {
+        return Err(JarvisError::ListenerError(ListenerError::ProcessingError(
+            format!("At least {} samples are required, got {}",
+                    config::TRAINING_MIN_SAMPLES, session.samples.len())
+        )));
+    }
+
+    let mut sample_buffers = HashMap::new();
+    for (idx, samples) in session.samples.iter().enumerate() {
+        sample_buffers.insert(format!("{}-{}.wav", session.name, idx + 1), encode_wav(samples)?);
+    }
+
+    let wakeword = WakewordRef::new_from_sample_buffers(
+        session.name.clone(),
+        None,
+        None,
+        sample_buffers,
+        config::TRAINING_MFCC_SIZE,
+    ).map_err(|e| JarvisError::ListenerError(ListenerError::ModelLoadingFailed(
+        format!("Failed to build wake-word: {}", e)
+    )))?;
+
+    let wakeword_path = wakeword_file_path(&session.name)?;
+    wakeword.save_to_file(&wakeword_path.to_string_lossy())
+        .map_err(|e| JarvisError::ListenerError(ListenerError::ModelLoadingFailed(
+            format!("Failed to save wake-word file: {}", e)
+        )))?;
+
+    info!("Wake-word '{}' saved to {}", session.name, wakeword_path.display());
+    Ok(wakeword_path)
+}
+
+/// Проверка обученного wake-word: слушаем микрофон и собираем оценки срабатываний
+pub fn test_wakeword(name: &str, duration: std::time::Duration) -> JarvisResult<TrainingTestReport> {
+    ensure_training_enabled()?;
+
+    let name = validate_name(name)?;
+    let wakeword_path = wakeword_file_path(name)?;
+    if !wakeword_path.exists() {
+        return Err(JarvisError::ListenerError(ListenerError::ModelLoadingFailed(
+            format!("Wake-word file not found: {}", wakeword_path.display())
+        )));
+    }
+
+    let settings = db::get_current_settings()
+        .map(|settings| settings.wake_word.rustpotter)
+        .unwrap_or_default();
+
+    let mut rustpotter = Rustpotter::new(&config::build_rustpotter_config(&settings))
+        .map_err(|e| JarvisError::ListenerError(ListenerError::EngineInitializationFailed(e)))?;
+    rustpotter.add_wakeword_from_file(name, &wakeword_path.to_string_lossy())
+        .map_err(|e| JarvisError::ListenerError(ListenerError::ModelLoadingFailed(e)))?;
+
+    let audio = record_audio(duration)?;
+    let frame_length = recorder::get_frame_length()? as usize;
+
+    let scores = audio.chunks_exact(frame_length)
+        .filter_map(|frame| rustpotter.process_i16(frame))
+        .map(|detection| detection.score)
+        .collect::<Vec<_>>();
+
+    let report = TrainingTestReport {
+        name: name.to_string(),
+        detections: scores.len(),
+        max_score: scores.iter().cloned().fold(0.0, f32::max),
+        avg_score: if scores.is_empty() { 0.0 } else { scores.iter().sum::<f32>() / scores.len() as f32 },
+        scores,
+    };
+
+    info!("Wake-word '{}' test: {} detections, max score {:.3}",
+          name, report.detections, report.max_score);
+    Ok(report)
+}
+
+/// Включение обученного wake-word в набор загружаемых файлов
+pub fn enable_wakeword(name: &str, threshold: Option<f32>) -> JarvisResult<()> {
+    ensure_training_enabled()?;
+
+    let name = validate_name(name)?;
+    let file_name = format!("{}.{}", name, config::RUSTPOTTER_WAKE_WORD_EXTENSION);
+    if !wakeword_file_path(name)?.exists() {
+        return Err(JarvisError::ListenerError(ListenerError::ModelLoadingFailed(
+            format!("Wake-word file not found: {}", file_name)
+        )));
+    }
+
+    db::update_settings(|settings| {
+        let files = &mut settings.wake_word.rustpotter.wake_word_files;
+
+        match files.iter_mut().find(|f| f.path == file_name) {
+            Some(file) => {
+                file.enabled = true;
+                file.threshold = threshold;
+            }
+            None => files.push(RustpotterWakeWordFile {
+                path: file_name.clone(),
+                threshold,
+                enabled: true,
+            }),
+        }
+
+        settings.touch();
+    })?;
+
+    info!("Trained wake-word '{}' enabled", name);
+    Ok(())
+}
+
+// Обучение доступно только при включенной экспериментальной функции
+fn ensure_training_enabled() -> JarvisResult<()> {
+    let enabled = db::get_current_settings()
+        .map(|settings| settings.advanced_settings.experimental_features.enable_voice_training)
+        .unwrap_or(false);
+
+    if enabled {
+        Ok(())
+    } else {
+        Err(JarvisError::ListenerError(ListenerError::ProcessingError(
+            "Voice training is disabled in experimental settings".to_string()
+        )))
+    }
+}
+
+fn no_session_error() -> JarvisError {
+    JarvisError::ListenerError(ListenerError::ProcessingError(
+        "Voice training session is not started".to_string()
+    ))
+}
+
+// Имя wake-word становится именем файла, поэтому допускаются только буквы, цифры, '-' и '_'
+fn validate_name(name: &str) -> JarvisResult<&str> {
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        return Err(JarvisError::ListenerError(ListenerError::ProcessingError(
+            format!("Invalid wake-word name: '{}'", name)
+        )));
+    }
+
+    Ok(name)
+}
+
+fn wakeword_file_path(name: &str) -> JarvisResult<PathBuf> {
+    Ok(config::get_user_rustpotter_path()?
+        .join(format!("{}.{}", name, config::RUSTPOTTER_WAKE_WORD_EXTENSION)))
+}
+
+// Запись звука заданной длительности через активный рекордер
+fn record_audio(duration: std::time::Duration) -> JarvisResult<Vec<i16>> {
+    let frame_length = recorder::get_frame_length()? as usize;
+    let frames_count = (duration.as_secs_f32() * config::TRAINING_SAMPLE_RATE as f32) as usize / frame_length;
+
+    // Главный цикл не должен забирать кадры с микрофона во время записи
+    let _pause = listener::pause();
+
+    // Если запись не шла, запускаем ее только на время образца
+    let started_here = !recorder::is_recording();
+    if started_here {
+        recorder::start_recording()?;
+    }
+
+    let mut frame_buffer = vec![0i16; frame_length];
+    let mut audio = Vec::with_capacity(frames_count * frame_length);
+    let mut result = Ok(());
+
+    for _ in 0..frames_count {
+        if let Err(e) = recorder::read_microphone(&mut frame_buffer) {
+            result = Err(e);
+            break;
+        }
+        audio.extend_from_slice(&frame_buffer);
+    }
+
+    if started_here {
+        if let Err(e) = recorder::stop_recording() {
+            warn!("Failed to stop recording after training sample: {}", e);
+        }
+    }
+
+    result.map(|_| audio)
+}
+
+/// Обрезка тишины в начале и конце образца
+fn trim_silence(samples: &[i16], level: i16) -> Vec<i16> {
+    let start = samples.iter().position(|s| s.unsigned_abs() > level.unsigned_abs());
+    let end = samples.iter().rposition(|s| s.unsigned_abs() > level.unsigned_abs());
+
+    match (start, end) {
+        (Some(start), Some(end)) => samples[start..=end].to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+/// Кодирование образца в WAV (16 кГц, моно, 16 бит)
+fn encode_wav(samples: &[i16]) -> JarvisResult<Vec<u8>> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: config::TRAINING_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut buffer = Vec::new();
+    {
+        let mut writer = hound::WavWriter::new(Cursor::new(&mut buffer), spec)
+            .map_err(|e| JarvisError::RecorderError(RecorderError::RecordingFailed(e.to_string())))?;
+
+        for sample in samples {
+            writer.write_sample(*sample)
+                .map_err(|e| JarvisError::RecorderError(RecorderError::RecordingFailed(e.to_string())))?;
+        }
+
+        writer.finalize()
+            .map_err(|e| JarvisError::RecorderError(RecorderError::RecordingFailed(e.to_string())))?;
+    }
+
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trim_silence() {
+        let samples = vec![0, 10, -20, 3000, -4000, 2500, 15, 0];
+        assert_eq!(trim_silence(&samples, 500), vec![3000, -4000, 2500]);
+        assert!(trim_silence(&[0, 10, -10], 500).is_empty());
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert_eq!(validate_name("  hey_friday-2 ").unwrap(), "hey_friday-2");
+        assert!(validate_name("../x").is_err());
+        assert!(validate_name("a/b").is_err());
+        assert!(validate_name("   ").is_err());
+    }
+
+    #[test]
+    fn test_encode_wav_roundtrip() {
+        let samples = vec![0i16, 1200, -1200, i16::MAX, i16::MIN];
+        let wav = encode_wav(&samples).unwrap();
+
+        let mut reader = hound::WavReader::new(Cursor::new(wav)).unwrap();
+        assert_eq!(reader.spec().sample_rate, config::TRAINING_SAMPLE_RATE);
+        assert_eq!(reader.spec().channels, 1);
+
+        let decoded = reader.samples::<i16>().map(|s| s.unwrap()).collect::<Vec<_>>();
+        assert_eq!(decoded, samples);
+    }
+}
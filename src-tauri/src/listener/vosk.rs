@@ -1,33 +1,91 @@
-use crate::{config, stt};
+use super::{guard, WakeWordDetector};
+use crate::{config, db, stt};
+use crate::config::structs::WakeWordEngine;
+use crate::error::{JarvisResult, JarvisError, ListenerError};
 use log::info;
-pub fn init() -> Result<(), ()> {
-    Ok(()) // nothing to init for Vosk
+
+pub struct VoskDetector {
+    // wake phrases: default one + every bound keyword, position in the list is the keyword index
+    wake_phrases: Vec<String>,
 }
 
-// @TODO. Make it better somehow (more accurate or with higher sensitivity).
-pub fn data_callback(frame_buffer: &[i16]) -> Option<i32> {
-    // recognize & convert to sequence
-    let recognized_phrase = stt::recognize(&frame_buffer, true).unwrap_or("".into());
+impl VoskDetector {
+    pub fn new() -> JarvisResult<Self> {
+        // recognizer itself is shared with STT, so it has to be available
+        if let Some(reason) = stt::unavailable_reason() {
+            return Err(JarvisError::ListenerError(ListenerError::EngineInitializationFailed(
+                format!("Vosk wake-word requires STT: {}", reason)
+            )));
+        }
 
-    if !recognized_phrase.trim().is_empty() {
-        info!("Vosk wake-word debug info:");
-        info!("rec: {}", recognized_phrase);
-        let recognized_phrases = recognized_phrase.split_whitespace();
-        for phrase in recognized_phrases {
-            let recognized_phrase_chars = phrase.trim().to_lowercase().chars().collect::<Vec<_>>();
-
-            // compare
-            let compare_ratio = seqdiff::ratio(&config::VOSK_FETCH_PHRASE.chars().collect::<Vec<_>>(), &recognized_phrase_chars);
-            info!("og phrase: {:?}", &config::VOSK_FETCH_PHRASE);
-            info!("recognized phrase: {:?}", &recognized_phrase_chars);
-            info!("compare ratio: {}", compare_ratio);
-
-            if compare_ratio >= config::VOSK_MIN_RATIO {
-                info!("Phrase activated.");
-                return Some(0)
+        let mut wake_phrases = vec![config::VOSK_FETCH_PHRASE.to_string()];
+
+        if let Some(settings) = db::get_current_settings() {
+            for binding in &settings.wake_word.bindings {
+                let phrase = binding.keyword.trim().to_lowercase();
+                if !phrase.is_empty() && !wake_phrases.contains(&phrase) {
+                    wake_phrases.push(phrase);
+                }
             }
         }
+
+        Ok(Self { wake_phrases })
+    }
+}
+
+impl WakeWordDetector for VoskDetector {
+    fn engine(&self) -> WakeWordEngine {
+        WakeWordEngine::Vosk
     }
 
-    None
-}
\ No newline at end of file
+    // @TODO. Make it better somehow (more accurate or with higher sensitivity).
+    fn process(&mut self, frame_buffer: &[i16]) -> Option<i32> {
+        // recognize & convert to sequence
+        let recognized_phrase = stt::recognize(&frame_buffer, true).ok().flatten().unwrap_or_default();
+
+        if recognized_phrase.trim().is_empty() {
+            return None;
+        }
+
+        info!("Vosk wake-word debug info:");
+        info!("rec: {}", recognized_phrase);
+
+        let keyword_index = matching_phrase(&recognized_phrase, &self.wake_phrases)?;
+        info!("Phrase activated: {:?}", self.wake_phrases[keyword_index]);
+
+        Some(keyword_index as i32)
+    }
+
+    fn keyword_name(&self, keyword_index: i32) -> Option<String> {
+        self.wake_phrases.get(keyword_index as usize).cloned()
+    }
+}
+
+// index of the wake phrase heard in the transcript
+// every word of the phrase has to be recognized, the longest matching phrase wins ("джарвис тихо" over "джарвис")
+fn matching_phrase(transcript: &str, wake_phrases: &[String]) -> Option<usize> {
+    wake_phrases.iter()
+        .enumerate()
+        .filter(|(_, wake_phrase)| guard::transcript_matches(transcript, wake_phrase, config::VOSK_MIN_RATIO))
+        .max_by_key(|(keyword_index, wake_phrase)| (wake_phrase.split_whitespace().count(), std::cmp::Reverse(*keyword_index)))
+        .map(|(keyword_index, _)| keyword_index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matching_multi_word_phrase() {
+        let wake_phrases = vec![
+            config::VOSK_FETCH_PHRASE.to_string(),
+            "эй пятница".to_string(),
+            "джарвис тихо".to_string(),
+        ];
+
+        assert_eq!(matching_phrase("эй пятница включи свет", &wake_phrases), Some(1));
+        assert_eq!(matching_phrase("джарвис тихо", &wake_phrases), Some(2));
+        assert_eq!(matching_phrase("джарвис", &wake_phrases), Some(0));
+        assert_eq!(matching_phrase("пятница", &wake_phrases), None);
+    }
+}
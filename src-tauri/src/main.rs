@@ -119,12 +119,13 @@ fn initialize_audio_system() -> JarvisResult<()> {
         e
     })?;
 
-    // Инициализируем STT
+    // Инициализируем STT (без него приложение работает в режиме "без распознавания речи")
     info!("Starting Speech-to-Text...");
-    stt::init().map_err(|e| {
-        error!("❌ Critical: STT initialization failed");
-        e
-    })?;
+    if let Err(e) = stt::init() {
+        error!("❌ STT initialization failed: {}", e);
+        warn!("Starting in degraded mode: wake-word detection works, voice commands are disabled");
+        warn!("Check that a speech recognition model is installed and restart the application");
+    }
 
     // Инициализируем wake-word engine
     info!("Starting wake-word detection...");
@@ -141,12 +142,15 @@ fn initialize_audio_system() -> JarvisResult<()> {
 fn initialize_commands_and_documents() -> JarvisResult<()> {
     info!("Initializing command system...");
 
-    let _commands = commands::parse_commands().map_err(|e| {
+    let commands_list = commands::parse_commands().map_err(|e| {
         JarvisError::CommandError(error::CommandError::ParseError(
             format!("Failed to parse commands: {}", e)
         ))
     })?;
 
+    // Грамматика распознавания для режима команд
+    stt::apply_command_grammar(&commands_list);
+
     // Инициализируем поиск документов (если включен)
     #[cfg(feature = "document-search")]
     {
@@ -1,18 +1,244 @@
 // STT module exports
 pub mod vosk;
 
-// Re-export main functions
-pub use vosk::*;
+#[cfg(feature = "experimental-whisper-stt")]
+pub mod whisper;
 
-use crate::error::JarvisResult;
+use std::sync::Mutex;
 
+use crate::config;
+use crate::config::structs::SpeechToTextEngine;
+use crate::db;
+use crate::error::{JarvisResult, JarvisError, SttError};
+use log::{info, warn};
+
+use self::vosk::VoskRecognizer;
+
+/// Вариант распознавания с уверенностью распознавателя
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecognitionAlternative {
+    pub text: String,
+    pub confidence: f32,
+}
+
+/// Режим распознавания речи
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecognitionMode {
+    Command,    // Короткие команды (может быть ограничено грамматикой)
+    Dictation,  // Свободная речь (разговор с AI)
+}
+
+/// Общий интерфейс движков распознавания речи
+pub trait SpeechRecognizer: Send {
+    /// Тип движка
+    fn engine(&self) -> SpeechToTextEngine;
+
+    /// Текущий язык распознавания
+    fn language(&self) -> String;
+
+    /// Все загруженные языки
+    fn languages(&self) -> Vec<String>;
+
+    /// Потоковое распознавание кадра, возвращает текст завершенной (или частичной) фразы
+    fn recognize(&mut self, data: &[i16], include_partial: bool) -> JarvisResult<Option<String>>;
+
+    /// Потоковое распознавание с N лучшими вариантами завершенной фразы
+    fn recognize_alternatives(&mut self, data: &[i16]) -> JarvisResult<Option<Vec<RecognitionAlternative>>>;
+
+    /// Распознавание готового фрагмента аудио целиком (не затрагивает потоковое состояние)
+    fn transcribe(&mut self, data: &[i16]) -> JarvisResult<Option<String>>;
+
+    /// Переключение режима распознавания
+    fn set_mode(&mut self, mode: RecognitionMode);
+
+    /// Грамматика режима команд для языка (пустая - без ограничений)
+    fn set_command_grammar(&mut self, language: &str, grammar: &[String]);
+}
+
+// Активный движок распознавания
+static RECOGNIZER: Mutex<Option<Box<dyn SpeechRecognizer>>> = Mutex::new(None);
+
+// Причина, по которой STT недоступен (режим без распознавания речи)
+static UNAVAILABLE_REASON: Mutex<Option<String>> = Mutex::new(None);
+
+// Настройки, с которыми загружен активный движок
+static LOADED_WITH: Mutex<Option<EngineSettings>> = Mutex::new(None);
+
+/// Настройки, от которых зависят загруженные модели
+/// (грамматика команд применяется отдельно и перезагрузки не требует)
+#[derive(Clone, Debug, PartialEq)]
+struct EngineSettings {
+    engine: SpeechToTextEngine,
+    language: String,
+    multi_language: bool,
+    auto_language: bool,
+}
+
+impl EngineSettings {
+    fn current() -> Self {
+        match db::get_current_settings() {
+            Some(settings) => Self {
+                engine: settings.speech_to_text_engine,
+                language: settings.stt.language,
+                multi_language: settings.advanced_settings.experimental_features.enable_multi_language,
+                auto_language: settings.stt.auto_language,
+            },
+            None => Self {
+                engine: config::DEFAULT_SPEECH_TO_TEXT_ENGINE,
+                language: config::DEFAULT_STT_LANGUAGE.to_string(),
+                multi_language: false,
+                auto_language: false,
+            },
+        }
+    }
+}
+
+/// Инициализация движка, выбранного в настройках
+/// При ошибке работающий движок сохраняется; если движка нет, причина сохраняется для диагностики
 pub fn init() -> JarvisResult<()> {
-    vosk::init_vosk();
-    log::info!("STT system initialized");
+    let engine_settings = EngineSettings::current();
+
+    let recognizer = match create_recognizer(engine_settings.engine) {
+        Ok(recognizer) => recognizer,
+        Err(e) => {
+            if lock_recognizer()?.is_some() {
+                warn!("Keeping the current STT engine, new one failed to load: {}", e);
+            } else {
+                *UNAVAILABLE_REASON.lock().unwrap() = Some(e.to_string());
+            }
+            return Err(e);
+        }
+    };
+    info!("STT engine initialized: {:?}", recognizer.engine());
+
+    *lock_recognizer()? = Some(recognizer);
+    *UNAVAILABLE_REASON.lock().unwrap() = None;
+    *LOADED_WITH.lock().unwrap() = Some(engine_settings);
+    info!("STT system initialized");
     Ok(())
 }
 
+/// Применение настроек: модели загружаются заново, только если изменились движок или языки
+pub fn reload() -> JarvisResult<()> {
+    let unchanged = LOADED_WITH.lock().unwrap().as_ref() == Some(&EngineSettings::current());
+
+    if unchanged && is_available() {
+        info!("STT settings unchanged, keeping loaded models");
+        return Ok(());
+    }
+
+    init()
+}
+
+/// Доступно ли распознавание речи
+pub fn is_available() -> bool {
+    lock_recognizer().map(|recognizer| recognizer.is_some()).unwrap_or(false)
+}
+
+/// Причина недоступности STT (None, если движок работает)
+pub fn unavailable_reason() -> Option<String> {
+    if is_available() {
+        return None;
+    }
+
+    Some(unavailable_reason_locked())
+}
+
+/// Создание движка; без поддержки Whisper в сборке используется Vosk
+fn create_recognizer(engine: SpeechToTextEngine) -> JarvisResult<Box<dyn SpeechRecognizer>> {
+    match engine {
+        SpeechToTextEngine::Vosk => Ok(Box::new(VoskRecognizer::new()?)),
+
+        #[cfg(feature = "experimental-whisper-stt")]
+        SpeechToTextEngine::Whisper => Ok(Box::new(whisper::WhisperRecognizer::new()?)),
+
+        #[cfg(not(feature = "experimental-whisper-stt"))]
+        SpeechToTextEngine::Whisper => {
+            warn!("Whisper STT is not included in this build, falling back to Vosk");
+            Ok(Box::new(VoskRecognizer::new()?))
+        }
+    }
+}
+
+fn lock_recognizer() -> JarvisResult<std::sync::MutexGuard<'static, Option<Box<dyn SpeechRecognizer>>>> {
+    RECOGNIZER.lock().map_err(|e| JarvisError::SttError(SttError::RecognitionFailed(
+        format!("Failed to lock STT engine: {}", e)
+    )))
+}
+
+// выполнение действия над активным движком
+fn with_recognizer<T>(f: impl FnOnce(&mut dyn SpeechRecognizer) -> T) -> Option<T> {
+    let mut recognizer = lock_recognizer().ok()?;
+    Some(f(recognizer.as_mut()?.as_mut()))
+}
+
+// то же, но отсутствие движка - ошибка
+fn require_recognizer<T>(f: impl FnOnce(&mut dyn SpeechRecognizer) -> JarvisResult<T>) -> JarvisResult<T> {
+    let mut recognizer = lock_recognizer()?;
+    let recognizer = recognizer.as_mut().ok_or_else(|| JarvisError::SttError(SttError::InitializationFailed(
+        unavailable_reason_locked()
+    )))?;
+
+    f(recognizer.as_mut())
+}
+
+// причина недоступности без повторной блокировки движка
+fn unavailable_reason_locked() -> String {
+    UNAVAILABLE_REASON.lock().ok()
+        .and_then(|reason| reason.clone())
+        .unwrap_or_else(|| "STT engine is not initialized".to_string())
+}
+
+pub fn recognize(data: &[i16], include_partial: bool) -> JarvisResult<Option<String>> {
+    require_recognizer(|recognizer| recognizer.recognize(data, include_partial))
+}
+
+pub fn recognize_alternatives(data: &[i16]) -> JarvisResult<Option<Vec<RecognitionAlternative>>> {
+    require_recognizer(|recognizer| recognizer.recognize_alternatives(data))
+}
+
+pub fn transcribe(data: &[i16]) -> JarvisResult<Option<String>> {
+    require_recognizer(|recognizer| recognizer.transcribe(data))
+}
+
+pub fn set_mode(mode: RecognitionMode) {
+    with_recognizer(|recognizer| recognizer.set_mode(mode));
+}
+
+/// Тип активного движка
+pub fn current_engine() -> Option<SpeechToTextEngine> {
+    with_recognizer(|recognizer| recognizer.engine())
+}
+
+/// Текущий язык распознавания
+pub fn current_language() -> String {
+    with_recognizer(|recognizer| recognizer.language())
+        .unwrap_or_else(|| config::DEFAULT_STT_LANGUAGE.to_string())
+}
+
+/// Применение грамматики команд согласно настройкам (для каждого загруженного языка)
+pub fn apply_command_grammar(commands: &[crate::commands::AssistantCommand]) {
+    let use_grammar = db::get_current_settings()
+        .map(|settings| settings.stt.use_command_grammar)
+        .unwrap_or(false);
+    let wake_phrases = crate::listener::binding_phrases();
+
+    with_recognizer(|recognizer| {
+        for language in recognizer.languages() {
+            let grammar = if use_grammar {
+                crate::commands::grammar_phrases(commands, &language, &wake_phrases)
+            } else {
+                Vec::new()
+            };
+
+            recognizer.set_command_grammar(&language, &grammar);
+        }
+    });
+}
+
 pub fn shutdown() -> JarvisResult<()> {
-    log::info!("STT system shutdown completed");
+    *lock_recognizer()? = None;
+    *LOADED_WITH.lock().unwrap() = None;
+    info!("STT system shutdown completed");
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,53 +1,273 @@
-use once_cell::sync::OnceCell;
-use vosk::{DecodingState, Model, Recognizer};
+use vosk::{CompleteResult, DecodingState, Model, Recognizer};
 
-use std::sync::Mutex;
+use log::{info, warn};
 
-static MODEL: OnceCell<Model> = OnceCell::new();
-static RECOGNIZER: OnceCell<Mutex<Recognizer>> = OnceCell::new();
+use super::{RecognitionAlternative, RecognitionMode, SpeechRecognizer};
+use crate::config;
+use crate::config::structs::SpeechToTextEngine;
+use crate::db;
+use crate::error::{JarvisResult, JarvisError, SttError};
 
-pub fn init_vosk() {
-    if !RECOGNIZER.get().is_none() {return;} // already initialized
+const SAMPLE_RATE: f32 = 16000.0;
 
-    let model_path = crate::config::get_vosk_model_path()
-        .map_err(|_| "Failed to get Vosk model path")?;
-    let model = Model::new(model_path.to_str().unwrap()).unwrap();
-    let mut recognizer = Recognizer::new(&model, 16000.0).unwrap();
+// loaded model of a single language
+struct LanguageModel {
+    language: String,
+    model: Model,
+    // open vocabulary recognizer (dictation and command mode without grammar)
+    recognizer: Recognizer,
+    // recognizer limited by the command grammar (if enabled in settings)
+    grammar_recognizer: Option<Recognizer>,
+}
+
+impl LanguageModel {
+    fn load(model_info: &config::VoskModelInfo) -> JarvisResult<Self> {
+        let model = Model::new(model_info.path.to_string_lossy()).ok_or_else(|| {
+            JarvisError::SttError(SttError::ModelNotFound(model_info.path.display().to_string()))
+        })?;
+
+        let mut recognizer = Recognizer::new(&model, SAMPLE_RATE).ok_or_else(|| {
+            JarvisError::SttError(SttError::InitializationFailed(
+                format!("Failed to create Vosk recognizer for '{}'", model_info.language)
+            ))
+        })?;
+        configure_recognizer(&mut recognizer);
+
+        info!("Vosk model loaded: {} ({})", model_info.path.display(), model_info.language);
+
+        Ok(Self {
+            language: model_info.language.clone(),
+            model,
+            recognizer,
+            grammar_recognizer: None,
+        })
+    }
+
+    // recognizer for the given mode
+    fn recognizer(&mut self, mode: RecognitionMode) -> &mut Recognizer {
+        match (mode, self.grammar_recognizer.as_mut()) {
+            (RecognitionMode::Command, Some(grammar_recognizer)) => grammar_recognizer,
+            _ => &mut self.recognizer,
+        }
+    }
+}
+
+pub struct VoskRecognizer {
+    models: Vec<LanguageModel>,
+    // index of the model in use
+    active: usize,
+    // run every model and pick the most confident one
+    auto_language: bool,
+    mode: RecognitionMode,
+}
+
+impl VoskRecognizer {
+    pub fn new() -> JarvisResult<Self> {
+        let settings = db::get_current_settings().unwrap_or_default();
+        let multi_language = settings.advanced_settings.experimental_features.enable_multi_language;
+
+        let available = config::discover_vosk_models(&config::get_vosk_models_dir()?);
+        if available.is_empty() {
+            return Err(JarvisError::SttError(SttError::ModelNotFound(
+                config::get_vosk_models_dir()?.display().to_string()
+            )));
+        }
+
+        // requested language (or the first available one if there is no such model)
+        let selected = available.iter()
+            .position(|m| m.language == settings.stt.language)
+            .unwrap_or_else(|| {
+                warn!("Vosk model for '{}' not found, using '{}'", settings.stt.language, available[0].language);
+                0
+            });
+
+        // without multi-language support only the selected model is loaded
+        let to_load = if multi_language { available.clone() } else { vec![available[selected].clone()] };
+
+        let mut models = Vec::new();
+        for model_info in &to_load {
+            match LanguageModel::load(model_info) {
+                Ok(model) => models.push(model),
+                Err(e) => warn!("Failed to load Vosk model for '{}': {}", model_info.language, e),
+            }
+        }
+
+        if models.is_empty() {
+            return Err(JarvisError::SttError(SttError::InitializationFailed(
+                "No Vosk model could be loaded".to_string()
+            )));
+        }
+
+        let active = models.iter()
+            .position(|m| m.language == available[selected].language)
+            .unwrap_or(0);
+        let auto_language = multi_language && settings.stt.auto_language && models.len() > 1;
+
+        if auto_language {
+            info!("Automatic STT language selection enabled for {:?}", models.iter().map(|m| &m.language).collect::<Vec<_>>());
+        }
+
+        Ok(Self {
+            models,
+            active,
+            auto_language,
+            mode: RecognitionMode::Command,
+        })
+    }
+
+    // feed the frame to the active model (or to every model in auto mode),
+    // returns N-best alternatives when the phrase is finalized
+    fn accept(&mut self, data: &[i16]) -> JarvisResult<Option<Vec<RecognitionAlternative>>> {
+        let mode = self.mode;
+
+        if !self.auto_language {
+            let recognizer = self.models[self.active].recognizer(mode);
+
+            return match recognizer.accept_waveform(data) {
+                DecodingState::Running => Ok(None),
+                DecodingState::Finalized => Ok(Some(alternatives(recognizer.result()))),
+                DecodingState::Failed => Err(decoding_failed()),
+            };
+        }
+
+        let states = self.models.iter_mut()
+            .map(|model| model.recognizer(mode).accept_waveform(data))
+            .collect::<Vec<_>>();
+
+        if states.iter().all(|state| matches!(state, DecodingState::Failed)) {
+            return Err(decoding_failed());
+        }
+
+        if !states.iter().any(|state| matches!(state, DecodingState::Finalized)) {
+            return Ok(None);
+        }
+
+        // phrase is over for one model - finalize the others as well and compare confidences
+        let results = self.models.iter_mut()
+            .zip(states)
+            .map(|(model, state)| {
+                let recognizer = model.recognizer(mode);
+                match state {
+                    DecodingState::Finalized => alternatives(recognizer.result()),
+                    _ => alternatives(recognizer.final_result()),
+                }
+            })
+            .collect::<Vec<_>>();
 
+        let best = select_language(&results).unwrap_or(self.active);
+        if best != self.active {
+            info!("STT language switched to '{}'", self.models[best].language);
+            self.active = best;
+        }
+
+        Ok(results.into_iter().nth(best))
+    }
+}
+
+fn decoding_failed() -> JarvisError {
+    JarvisError::SttError(SttError::RecognitionFailed("Vosk failed to decode audio".to_string()))
+}
+
+fn configure_recognizer(recognizer: &mut Recognizer) {
     recognizer.set_max_alternatives(10);
     recognizer.set_words(true);
     recognizer.set_partial_words(true);
+}
 
-    MODEL.set(model);
-    RECOGNIZER.set(Mutex::new(recognizer));
+// Result will always be multiple because we called set_max_alternatives
+fn alternatives(result: CompleteResult) -> Vec<RecognitionAlternative> {
+    result.multiple()
+        .map(|multiple| multiple.alternatives.iter()
+            .map(|alternative| RecognitionAlternative {
+                text: alternative.text.to_string(),
+                confidence: alternative.confidence,
+            })
+            .collect())
+        .unwrap_or_default()
 }
 
-pub fn recognize(data: &[i16], include_partial: bool) -> Option<String> {
-    let state = RECOGNIZER.get().unwrap().lock().unwrap().accept_waveform(data);
+/// Индекс языка с наиболее уверенным непустым результатом
+pub fn select_language(results: &[Vec<RecognitionAlternative>]) -> Option<usize> {
+    results.iter()
+        .enumerate()
+        .filter_map(|(idx, alternatives)| {
+            let top = alternatives.first().filter(|a| !a.text.trim().is_empty())?;
+            Some((idx, top.confidence))
+        })
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(idx, _)| idx)
+}
 
-    match state {
-        DecodingState::Running => {
-            if include_partial {
-                Some(RECOGNIZER.get().unwrap().lock().unwrap().partial_result().partial.into())
-            } else {
-                None
+impl SpeechRecognizer for VoskRecognizer {
+    fn engine(&self) -> SpeechToTextEngine {
+        SpeechToTextEngine::Vosk
+    }
+
+    fn language(&self) -> String {
+        self.models[self.active].language.clone()
+    }
+
+    fn languages(&self) -> Vec<String> {
+        self.models.iter().map(|model| model.language.clone()).collect()
+    }
+
+    fn recognize(&mut self, data: &[i16], include_partial: bool) -> JarvisResult<Option<String>> {
+        match self.accept(data)? {
+            Some(alternatives) => Ok(alternatives.into_iter().next().map(|a| a.text)),
+            None if include_partial => {
+                let mode = self.mode;
+                Ok(Some(self.models[self.active].recognizer(mode).partial_result().partial.into()))
             }
+            None => Ok(None),
         }
-        DecodingState::Finalized => {
-            // Result will always be multiple because we called set_max_alternatives
-            Some(
-                RECOGNIZER.get().unwrap().lock().unwrap()
-                    .result()
-                    .multiple()
-                    .unwrap()
-                    .alternatives
-                    .first()
-                    .unwrap()
-                    .text
-                    .into(),
-            )
+    }
+
+    fn recognize_alternatives(&mut self, data: &[i16]) -> JarvisResult<Option<Vec<RecognitionAlternative>>> {
+        self.accept(data)
+    }
+
+    fn transcribe(&mut self, data: &[i16]) -> JarvisResult<Option<String>> {
+        // separate recognizer, so the streaming state is not affected
+        let mut recognizer = Recognizer::new(&self.models[self.active].model, SAMPLE_RATE).ok_or_else(|| {
+            JarvisError::SttError(SttError::InitializationFailed(
+                "Failed to create Vosk recognizer for transcription".to_string()
+            ))
+        })?;
+
+        if let DecodingState::Failed = recognizer.accept_waveform(data) {
+            return Err(decoding_failed());
+        }
+
+        Ok(recognizer.final_result().single().map(|result| result.text.to_string()))
+    }
+
+    fn set_mode(&mut self, mode: RecognitionMode) {
+        if self.mode != mode {
+            info!("STT recognition mode: {:?}", mode);
+            self.mode = mode;
+        }
+    }
+
+    fn set_command_grammar(&mut self, language: &str, grammar: &[String]) {
+        let model = match self.models.iter_mut().find(|model| model.language == language) {
+            Some(model) => model,
+            None => return,
+        };
+
+        model.grammar_recognizer = if grammar.is_empty() {
+            None
+        } else {
+            Recognizer::new_with_grammar(&model.model, SAMPLE_RATE, grammar).map(|mut recognizer| {
+                configure_recognizer(&mut recognizer);
+                recognizer
+            })
+        };
+
+        match &model.grammar_recognizer {
+            Some(_) => info!("Vosk command grammar applied for '{}' ({} entries)", language, grammar.len()),
+            None if !grammar.is_empty() => warn!("Failed to build Vosk command grammar for '{}', using open vocabulary", language),
+            None => info!("Vosk command grammar disabled for '{}', using open vocabulary", language),
         }
-        DecodingState::Failed => None,
     }
 }
 
@@ -61,3 +281,24 @@ pub fn recognize(data: &[i16], include_partial: bool) -> Option<String> {
 
 //     result
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(text: &str, confidence: f32) -> Vec<RecognitionAlternative> {
+        vec![RecognitionAlternative { text: text.to_string(), confidence }]
+    }
+
+    #[test]
+    fn test_select_language() {
+        let results = vec![result("включи музыку", 180.0), result("play music", 240.0)];
+        assert_eq!(select_language(&results), Some(1));
+
+        // пустые результаты не участвуют в выборе
+        let results = vec![result("включи музыку", 180.0), result("", 400.0), vec![]];
+        assert_eq!(select_language(&results), Some(0));
+
+        assert_eq!(select_language(&[vec![], result(" ", 10.0)]), None);
+    }
+}
@@ -0,0 +1,248 @@
+// Локальное распознавание через whisper.cpp (CPU) на буферизованных фразах
+
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+use log::{info, warn};
+
+use super::{RecognitionAlternative, RecognitionMode, SpeechRecognizer};
+use crate::audio::samples_for_ms;
+use crate::config;
+use crate::config::structs::SpeechToTextEngine;
+use crate::db;
+use crate::error::{JarvisResult, JarvisError, SttError};
+
+pub struct WhisperRecognizer {
+    context: WhisperContext,
+    // recognition language from settings (whisper language code: "ru", "en", ...)
+    language: String,
+    utterance: UtteranceBuffer,
+    mode: RecognitionMode,
+    // command phrases are passed as initial prompt to bias recognition
+    command_prompt: Option<String>,
+}
+
+impl WhisperRecognizer {
+    pub fn new() -> JarvisResult<Self> {
+        let model_path = config::get_whisper_model_path()?;
+
+        if !model_path.exists() {
+            return Err(JarvisError::SttError(SttError::ModelNotFound(model_path.display().to_string())));
+        }
+
+        let context = WhisperContext::new_with_params(
+            &model_path.to_string_lossy(),
+            WhisperContextParameters::default(),
+        ).map_err(|e| JarvisError::SttError(SttError::InitializationFailed(
+            format!("Whisper: {}", e)
+        )))?;
+
+        let language = db::get_current_settings()
+            .map(|settings| settings.stt.language.trim().to_lowercase())
+            .filter(|language| !language.is_empty())
+            .unwrap_or_else(|| config::DEFAULT_STT_LANGUAGE.to_string());
+
+        info!("Whisper model loaded: {} ({})", model_path.display(), language);
+
+        Ok(Self {
+            context,
+            language,
+            utterance: UtteranceBuffer::new(),
+            mode: RecognitionMode::Command,
+            command_prompt: None,
+        })
+    }
+
+    fn run(&self, samples: &[i16]) -> Result<String, String> {
+        let mut audio = vec![0.0f32; samples.len()];
+        whisper_rs::convert_integer_to_float_audio(samples, &mut audio).map_err(|e| e.to_string())?;
+
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        params.set_language(Some(&self.language));
+        params.set_n_threads(config::WHISPER_THREADS);
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+        params.set_single_segment(self.mode == RecognitionMode::Command);
+
+        if self.mode == RecognitionMode::Command {
+            if let Some(prompt) = &self.command_prompt {
+                params.set_initial_prompt(prompt);
+            }
+        }
+
+        let mut state = self.context.create_state().map_err(|e| e.to_string())?;
+        state.full(params, &audio).map_err(|e| e.to_string())?;
+
+        let segments = state.full_n_segments().map_err(|e| e.to_string())?;
+        let mut text = String::new();
+        for segment in 0..segments {
+            text.push_str(&state.full_get_segment_text(segment).map_err(|e| e.to_string())?);
+        }
+
+        Ok(text.trim().to_lowercase())
+    }
+
+    fn transcribe_utterance(&mut self, data: &[i16]) -> JarvisResult<Option<String>> {
+        match self.utterance.push(data) {
+            Some(utterance) => self.run(&utterance).map(Some).map_err(recognition_failed),
+            None => Ok(None),
+        }
+    }
+}
+
+impl SpeechRecognizer for WhisperRecognizer {
+    fn engine(&self) -> SpeechToTextEngine {
+        SpeechToTextEngine::Whisper
+    }
+
+    fn language(&self) -> String {
+        self.language.clone()
+    }
+
+    fn languages(&self) -> Vec<String> {
+        vec![self.language.clone()]
+    }
+
+    // whisper has no streaming mode, partial results are not available
+    fn recognize(&mut self, data: &[i16], _include_partial: bool) -> JarvisResult<Option<String>> {
+        self.transcribe_utterance(data)
+    }
+
+    fn recognize_alternatives(&mut self, data: &[i16]) -> JarvisResult<Option<Vec<RecognitionAlternative>>> {
+        Ok(self.transcribe_utterance(data)?.map(|text| vec![RecognitionAlternative {
+            text,
+            confidence: 1.0,
+        }]))
+    }
+
+    fn transcribe(&mut self, data: &[i16]) -> JarvisResult<Option<String>> {
+        self.run(data).map(Some).map_err(recognition_failed)
+    }
+
+    fn set_mode(&mut self, mode: RecognitionMode) {
+        if self.mode != mode {
+            info!("STT recognition mode: {:?}", mode);
+            self.mode = mode;
+            self.utterance.reset();
+        }
+    }
+
+    fn set_command_grammar(&mut self, language: &str, grammar: &[String]) {
+        if language != self.language {
+            return;
+        }
+
+        let phrases = grammar.iter()
+            .filter(|phrase| !phrase.starts_with('['))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        self.command_prompt = if phrases.is_empty() { None } else { Some(phrases.join(", ")) };
+    }
+}
+
+/// Накопление фразы по энергии сигнала: фраза завершается паузой или максимальной длиной
+pub struct UtteranceBuffer {
+    samples: Vec<i16>,
+    speech_samples: usize,
+    silence_samples: usize,
+}
+
+impl UtteranceBuffer {
+    pub fn new() -> Self {
+        Self {
+            samples: Vec::new(),
+            speech_samples: 0,
+            silence_samples: 0,
+        }
+    }
+
+    /// Добавление кадра, возвращает завершенную фразу
+    pub fn push(&mut self, frame: &[i16]) -> Option<Vec<i16>> {
+        let is_speech = frame_rms(frame) >= config::WHISPER_SPEECH_RMS;
+
+        if self.samples.is_empty() && !is_speech {
+            return None;
+        }
+
+        self.samples.extend_from_slice(frame);
+        if is_speech {
+            self.speech_samples += frame.len();
+            self.silence_samples = 0;
+        } else {
+            self.silence_samples += frame.len();
+        }
+
+        let pause_reached = self.silence_samples >= samples_for_ms(config::WHISPER_END_SILENCE_MS);
+        let limit_reached = self.samples.len() >= samples_for_ms(config::WHISPER_MAX_UTTERANCE_MS);
+
+        if !(pause_reached || limit_reached) {
+            return None;
+        }
+
+        let enough_speech = self.speech_samples >= samples_for_ms(config::WHISPER_MIN_SPEECH_MS);
+        let utterance = std::mem::take(&mut self.samples);
+        self.reset();
+
+        if enough_speech { Some(utterance) } else { None }
+    }
+
+    pub fn reset(&mut self) {
+        self.samples.clear();
+        self.speech_samples = 0;
+        self.silence_samples = 0;
+    }
+}
+
+fn recognition_failed(msg: String) -> JarvisError {
+    warn!("Whisper recognition failed: {}", msg);
+    JarvisError::SttError(SttError::RecognitionFailed(format!("Whisper: {}", msg)))
+}
+
+fn frame_rms(frame: &[i16]) -> f32 {
+    if frame.is_empty() {
+        return 0.0;
+    }
+
+    let sum = frame.iter().map(|&s| (s as f64) * (s as f64)).sum::<f64>();
+    (sum / frame.len() as f64).sqrt() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(value: i16, ms: usize) -> Vec<Vec<i16>> {
+        vec![vec![value; 512]; samples_for_ms(ms) / 512]
+    }
+
+    #[test]
+    fn test_utterance_ends_after_pause() {
+        let mut buffer = UtteranceBuffer::new();
+
+        // тишина до начала речи игнорируется
+        for frame in frames(0, 500) {
+            assert!(buffer.push(&frame).is_none());
+        }
+
+        for frame in frames(3000, 640) {
+            assert!(buffer.push(&frame).is_none());
+        }
+
+        let utterance = frames(0, config::WHISPER_END_SILENCE_MS + 64).iter()
+            .find_map(|frame| buffer.push(frame))
+            .expect("utterance should be finalized by a pause");
+
+        assert!(utterance.len() >= samples_for_ms(640));
+    }
+
+    #[test]
+    fn test_short_noise_is_dropped() {
+        let mut buffer = UtteranceBuffer::new();
+
+        assert!(buffer.push(&vec![3000; 512]).is_none());
+        for frame in frames(0, config::WHISPER_END_SILENCE_MS + 64) {
+            assert!(buffer.push(&frame).is_none());
+        }
+    }
+}
@@ -97,6 +97,7 @@ pub fn start_listening(app_handle: tauri::AppHandle) -> Result<bool, String> {
 
     // Сохраняем app handle
     if TAURI_APP_HANDLE.get().is_none() {
+        events::set_app_handle(app_handle.clone());
         TAURI_APP_HANDLE.set(app_handle).map_err(|_| {
             "Failed to set app handle".to_string()
         })?;
@@ -105,22 +105,27 @@ pub async fn get_available_voices() -> Result<Vec<String>, String> {
 
 #[tauri::command]
 pub async fn test_tts(text: String, voice: Option<String>, speed: Option<f32>, volume: Option<f32>) -> Result<bool, String> {
-    // Обновляем настройки TTS если переданы
-    if let (Some(v), Some(s), Some(vol)) = (voice, speed, volume) {
-        let settings = tts::VoiceSettings {
-            voice_id: v,
-            speed: s,
-            volume: vol,
-            ..Default::default()
-        };
-
-        tts::update_settings(settings)
-            .map_err(|e| format!("Failed to update TTS settings: {}", e))?;
-    }
+    // Переданные параметры применяются только к тестовой фразе и не сохраняются,
+    // остальные берутся из текущих настроек
+    let result = if voice.is_some() || speed.is_some() || volume.is_some() {
+        let mut settings = tts::current_settings();
 
-    // Воспроизводим тестовую фразу
-    tts::speak(&text)
-        .map_err(|e| format!("TTS test failed: {}", e))?;
+        if let Some(v) = voice {
+            settings.voice_id = v;
+        }
+        if let Some(s) = speed {
+            settings.speed = s;
+        }
+        if let Some(vol) = volume {
+            settings.volume = vol;
+        }
+
+        tts::speak_with_settings(&text, settings)
+    } else {
+        tts::speak(&text)
+    };
+
+    result.map_err(|e| format!("TTS test failed: {}", e))?;
 
     Ok(true)
 }
@@ -144,6 +149,42 @@ pub async fn is_speaking() -> Result<bool, String> {
     Ok(tts::is_speaking())
 }
 
+#[tauri::command]
+pub async fn enqueue_speech(text: String, priority: Option<tts::SpeechPriority>) -> Result<u64, String> {
+    if !tts::is_initialized() {
+        return Err("TTS not initialized".to_string());
+    }
+
+    Ok(tts::enqueue(&text, priority.unwrap_or(tts::SpeechPriority::Normal)).id())
+}
+
+#[tauri::command]
+pub async fn cancel_speech(id: u64) -> Result<bool, String> {
+    Ok(tts::cancel(id))
+}
+
+#[tauri::command]
+pub async fn get_speech_queue() -> Result<Value, String> {
+    serde_json::to_value(tts::queue_status())
+        .map_err(|e| format!("Serialization error: {}", e))
+}
+
+#[tauri::command]
+pub async fn prewarm_tts_cache(phrases: Vec<String>) -> Result<bool, String> {
+    if !tts::is_initialized() {
+        return Err("TTS not initialized".to_string());
+    }
+
+    tts::prewarm(phrases);
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn clear_tts_cache() -> Result<usize, String> {
+    tts::clear_cache()
+        .map_err(|e| format!("Failed to clear TTS cache: {}", e))
+}
+
 // AI команды
 #[tauri::command]
 pub async fn test_ai_connection(
@@ -152,7 +193,7 @@ pub async fn test_ai_connection(
     model: Option<String>
 ) -> Result<String, String> {
     // Временно обновляем конфигурацию для теста
-    let mut config = ai_integration::AiConfig::default();
+    let mut config = ai_integration::AiConfig::load().unwrap_or_default();
 
     if let Some(key) = openai_key {
         config.openai_api_key = key;
@@ -165,12 +206,9 @@ pub async fn test_ai_connection(
     }
 
     // Создаем временный AI менеджер для теста
-    let mut manager = ai_integration::AiManager::new()
+    let mut manager = ai_integration::AiManager::with_config(config)
         .map_err(|e| format!("Failed to create AI manager: {}", e))?;
 
-    manager.update_config(config)
-        .map_err(|e| format!("Failed to update AI config: {}", e))?;
-
     // Тестовый запрос
     let test_request = ai_integration::AiRequestType::Question(
         "Привет! Это тест подключения. Ответь кратко.".to_string()
@@ -236,6 +274,28 @@ pub async fn apply_settings() -> Result<bool, String> {
     // Останавливаем прослушивание
     let _ = crate::listener::stop_listening_enhanced().await;
 
+    // Перезагружаем STT, если изменились движок или язык
+    if let Err(e) = crate::stt::reload() {
+        warn!("Failed to reinitialize STT: {}", e);
+    }
+
+    // Переключаем wake-word движок согласно настройкам (Vosk зависит от STT, поэтому после него)
+    if let Err(e) = crate::listener::reload() {
+        warn!("Failed to reload wake-word engine: {}", e);
+    }
+
+    // Перестраиваем грамматику команд
+    match crate::commands::parse_commands() {
+        Ok(commands_list) => crate::stt::apply_command_grammar(&commands_list),
+        Err(e) => warn!("Failed to rebuild command grammar: {}", e),
+    }
+
+    // Обновляем длину pre-roll буфера и предобработку аудио
+    if let Some(settings) = crate::db::get_current_settings() {
+        crate::audio::preroll::configure(settings.advanced_settings.voice_session.preroll_ms);
+        crate::audio::preprocess::configure(&settings.advanced_settings.audio_preprocessing);
+    }
+
     // Перезагружаем TTS настройки
     if let Err(e) = tts::init() {
         warn!("Failed to reinitialize TTS: {}", e);
@@ -261,6 +321,8 @@ pub async fn get_system_info() -> Result<Value, String> {
         "architecture": std::env::consts::ARCH,
         "tts_initialized": tts::is_initialized(),
         "ai_initialized": ai_integration::is_initialized(),
+        "stt_available": crate::stt::is_available(),
+        "stt_error": crate::stt::unavailable_reason(),
         "audio_monitor_running": audio_monitor::is_running(),
         "device_count": audio_monitor::get_all_devices().unwrap_or_default().len()
     });
@@ -268,6 +330,85 @@ pub async fn get_system_info() -> Result<Value, String> {
     Ok(info)
 }
 
+// Команды калибровки порога wake-word
+#[tauri::command]
+pub async fn wake_word_calibration_start() -> Result<bool, String> {
+    crate::listener::calibration::start();
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn wake_word_calibration_status() -> Result<Value, String> {
+    serde_json::to_value(crate::listener::calibration::status())
+        .map_err(|e| format!("Serialization error: {}", e))
+}
+
+#[tauri::command]
+pub async fn wake_word_calibration_confirm(is_true_activation: bool) -> Result<Value, String> {
+    let sample = crate::listener::calibration::label_last(is_true_activation)
+        .map_err(|e| format!("Failed to confirm detection: {}", e))?;
+
+    serde_json::to_value(sample).map_err(|e| format!("Serialization error: {}", e))
+}
+
+#[tauri::command]
+pub async fn wake_word_calibration_apply() -> Result<Value, String> {
+    let thresholds = crate::listener::calibration::apply_suggested_thresholds()
+        .map_err(|e| format!("Failed to apply threshold: {}", e))?;
+
+    serde_json::to_value(thresholds).map_err(|e| format!("Serialization error: {}", e))
+}
+
+#[tauri::command]
+pub async fn wake_word_calibration_stop() -> Result<Value, String> {
+    let report = crate::listener::calibration::stop()
+        .map_err(|e| format!("Failed to stop calibration: {}", e))?;
+
+    serde_json::to_value(report).map_err(|e| format!("Serialization error: {}", e))
+}
+
+// Команды обучения wake-word
+#[cfg(feature = "experimental-voice-training")]
+#[tauri::command(async)]
+pub fn voice_training_start(name: String) -> Result<bool, String> {
+    crate::listener::training::start_session(&name)
+        .map_err(|e| format!("Failed to start voice training: {}", e))?;
+    Ok(true)
+}
+
+#[cfg(feature = "experimental-voice-training")]
+#[tauri::command(async)]
+pub fn voice_training_record_sample() -> Result<usize, String> {
+    crate::listener::training::record_sample()
+        .map_err(|e| format!("Failed to record sample: {}", e))
+}
+
+#[cfg(feature = "experimental-voice-training")]
+#[tauri::command(async)]
+pub fn voice_training_build() -> Result<String, String> {
+    let path = crate::listener::training::build_wakeword()
+        .map_err(|e| format!("Failed to build wake-word: {}", e))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[cfg(feature = "experimental-voice-training")]
+#[tauri::command(async)]
+pub fn voice_training_test(name: String, seconds: Option<u64>) -> Result<Value, String> {
+    let duration = std::time::Duration::from_secs(seconds.unwrap_or(10));
+    let report = crate::listener::training::test_wakeword(&name, duration)
+        .map_err(|e| format!("Wake-word test failed: {}", e))?;
+
+    serde_json::to_value(report).map_err(|e| format!("Serialization error: {}", e))
+}
+
+#[cfg(feature = "experimental-voice-training")]
+#[tauri::command(async)]
+pub fn voice_training_enable(name: String, threshold: Option<f32>) -> Result<bool, String> {
+    crate::listener::training::enable_wakeword(&name, threshold)
+        .map_err(|e| format!("Failed to enable wake-word: {}", e))?;
+    Ok(true)
+}
+
 // Команды управления файлами
 #[tauri::command]
 pub async fn search_documents(query: String) -> Result<Vec<String>, String> {
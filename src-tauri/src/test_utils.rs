@@ -0,0 +1,55 @@
+// src-tauri/src/test_utils.rs - Общие заглушки для тестов
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread::JoinHandle;
+
+/// Локальный HTTP сервер: принимает один запрос, отвечает заданным статусом, типом и телом,
+/// возвращает адрес сервера и handle со строкой запроса, заголовками (в нижнем регистре) и телом запроса
+pub fn mock_server(
+    status: &'static str,
+    content_type: &'static str,
+    body: Vec<u8>,
+) -> (String, JoinHandle<(String, Vec<String>, String)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+
+        let mut headers = Vec::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line.trim().is_empty() {
+                break;
+            }
+            headers.push(line.trim().to_lowercase());
+        }
+
+        let content_length = headers.iter()
+            .find_map(|h| h.strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+            .unwrap_or(0);
+        let mut request_body = vec![0u8; content_length];
+        reader.read_exact(&mut request_body).unwrap();
+
+        let mut stream = stream;
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            status,
+            content_type,
+            body.len()
+        ).unwrap();
+        stream.write_all(&body).unwrap();
+        stream.flush().unwrap();
+
+        (request_line.trim().to_string(), headers, String::from_utf8(request_body).unwrap())
+    });
+
+    (base_url, handle)
+}
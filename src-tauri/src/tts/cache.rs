@@ -0,0 +1,261 @@
+// app/src/tts/cache.rs - Дисковый кэш синтезированной речи (LRU по времени последнего доступа)
+
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use log::{debug, info, warn};
+
+use super::{AudioBuffer, TtsConfig, TtsEngine};
+
+const CACHE_EXTENSION: &str = "audio";
+
+#[derive(Clone)]
+pub struct TtsCache {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl TtsCache {
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        Self { dir, max_bytes }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", key, CACHE_EXTENSION))
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.path(key).exists()
+    }
+
+    /// Аудио из кэша; время доступа обновляется для LRU
+    pub fn get(&self, key: &str) -> Option<AudioBuffer> {
+        let path = self.path(key);
+        let data = fs::read(&path).ok()?;
+
+        if let Err(e) = touch(&path) {
+            debug!("Failed to update TTS cache entry time {}: {}", path.display(), e);
+        }
+
+        Some(AudioBuffer::Encoded(data))
+    }
+
+    /// Сохранение аудио в кэш с вытеснением давно не использованных записей
+    pub fn put(&self, key: &str, buffer: &AudioBuffer) {
+        let data = match buffer {
+            AudioBuffer::Encoded(data) => data.clone(),
+            // поток кэшируется после получения (см. TtsManager::synthesize_cached)
+            AudioBuffer::Stream(_) => return,
+            AudioBuffer::Pcm { samples, sample_rate, channels } => {
+                match encode_wav(samples, *sample_rate, *channels) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        warn!("Failed to encode speech for TTS cache: {}", e);
+                        return;
+                    }
+                }
+            }
+        };
+
+        if data.len() as u64 > self.max_bytes {
+            return;
+        }
+
+        // запись через временный файл, чтобы не оставить поврежденную запись
+        let path = self.path(key);
+        let tmp_path = path.with_extension("tmp");
+        if let Err(e) = fs::write(&tmp_path, &data).and_then(|_| fs::rename(&tmp_path, &path)) {
+            warn!("Failed to write TTS cache entry {}: {}", path.display(), e);
+            let _ = fs::remove_file(&tmp_path);
+            return;
+        }
+
+        self.evict();
+    }
+
+    /// Удаление самых старых записей, пока кэш превышает лимит
+    pub fn evict(&self) -> usize {
+        let mut entries = cache_entries(&self.dir);
+        let mut total = entries.iter().map(|(_, size, _)| size).sum::<u64>();
+
+        if total <= self.max_bytes {
+            return 0;
+        }
+
+        entries.sort_by_key(|(_, _, accessed)| *accessed);
+
+        let mut removed = 0;
+        for (path, size, _) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+
+            if fs::remove_file(&path).is_ok() {
+                total -= size;
+                removed += 1;
+            }
+        }
+
+        debug!("TTS cache evicted {} entries", removed);
+        removed
+    }
+
+    /// Размер кэша в байтах
+    pub fn size(&self) -> u64 {
+        cache_entries(&self.dir).iter().map(|(_, size, _)| size).sum()
+    }
+
+    pub fn clear(&self) -> usize {
+        let entries = cache_entries(&self.dir);
+        let removed = entries.iter().filter(|(path, _, _)| fs::remove_file(path).is_ok()).count();
+        info!("TTS cache cleared ({} entries)", removed);
+        removed
+    }
+}
+
+/// Ключ кэша: FNV-1a от текста, движка и параметров голоса
+/// Громкость не входит в ключ - она применяется при воспроизведении
+pub fn cache_key(text: &str, engine: TtsEngine, settings: &TtsConfig) -> String {
+    let mut source = format!(
+        "{:?}\u{1f}{}\u{1f}{:.2}\u{1f}{:.2}\u{1f}{}",
+        engine, settings.voice_id, settings.speed, settings.pitch, settings.language
+    );
+
+    if engine == TtsEngine::ElevenLabs {
+        let elevenlabs = &settings.elevenlabs;
+        source.push_str(&format!(
+            "\u{1f}{}\u{1f}{}\u{1f}{:.2}\u{1f}{:.2}",
+            elevenlabs.voice_id, elevenlabs.model_id, elevenlabs.stability, elevenlabs.similarity_boost
+        ));
+    }
+
+    source.push('\u{1f}');
+    source.push_str(text.trim());
+
+    format!("{:016x}", fnv1a(source.as_bytes()))
+}
+
+fn fnv1a(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    data.iter().fold(OFFSET_BASIS, |hash, &byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+}
+
+// записи кэша: путь, размер, время последнего доступа
+fn cache_entries(dir: &Path) -> Vec<(PathBuf, u64, SystemTime)> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == CACHE_EXTENSION))
+        .filter_map(|path| {
+            let metadata = fs::metadata(&path).ok()?;
+            let accessed = metadata.modified().ok()?;
+            Some((path, metadata.len(), accessed))
+        })
+        .collect()
+}
+
+// время модификации используется как время доступа (atime часто отключен)
+fn touch(path: &Path) -> std::io::Result<()> {
+    fs::File::options().write(true).open(path)?.set_modified(SystemTime::now())
+}
+
+/// Кодирование PCM в WAV для хранения в кэше
+fn encode_wav(samples: &[i16], sample_rate: u32, channels: u16) -> Result<Vec<u8>, hound::Error> {
+    let spec = hound::WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut buffer = Vec::new();
+    {
+        let mut writer = hound::WavWriter::new(Cursor::new(&mut buffer), spec)?;
+        for sample in samples {
+            writer.write_sample(*sample)?;
+        }
+        writer.finalize()?;
+    }
+
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    fn encoded(size: usize) -> AudioBuffer {
+        AudioBuffer::Encoded(vec![7u8; size])
+    }
+
+    fn set_accessed(cache: &TtsCache, key: &str, secs_ago: u64) {
+        let time = SystemTime::now() - Duration::from_secs(secs_ago);
+        fs::File::options().write(true).open(cache.path(key)).unwrap().set_modified(time).unwrap();
+    }
+
+    #[test]
+    fn test_cache_key_depends_on_voice_settings() {
+        let settings = TtsConfig::default();
+        let key = cache_key("Да, сэр", TtsEngine::System, &settings);
+
+        assert_eq!(key, cache_key(" Да, сэр ", TtsEngine::System, &settings));
+        assert_ne!(key, cache_key("Да, сэр", TtsEngine::OpenAI, &settings));
+        assert_ne!(key, cache_key("Да, сэр", TtsEngine::System, &TtsConfig { speed: 1.2, ..settings.clone() }));
+
+        // громкость не влияет на ключ
+        assert_eq!(key, cache_key("Да, сэр", TtsEngine::System, &TtsConfig { volume: 0.3, ..settings }));
+    }
+
+    #[test]
+    fn test_put_and_get() {
+        let dir = tempdir().unwrap();
+        let cache = TtsCache::new(dir.path().to_path_buf(), 1024);
+
+        assert!(cache.get("missing").is_none());
+
+        cache.put("key", &encoded(100));
+        match cache.get("key") {
+            Some(AudioBuffer::Encoded(data)) => assert_eq!(data.len(), 100),
+            other => panic!("unexpected cache entry: {:?}", other),
+        }
+
+        // PCM сохраняется как WAV
+        cache.put("pcm", &AudioBuffer::Pcm { samples: vec![0; 16], sample_rate: 16000, channels: 1 });
+        match cache.get("pcm") {
+            Some(AudioBuffer::Encoded(data)) => assert!(data.starts_with(b"RIFF")),
+            other => panic!("unexpected cache entry: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let dir = tempdir().unwrap();
+        let cache = TtsCache::new(dir.path().to_path_buf(), 250);
+
+        cache.put("old", &encoded(100));
+        cache.put("recent", &encoded(100));
+        set_accessed(&cache, "old", 60);
+        set_accessed(&cache, "recent", 30);
+
+        // обращение к старой записи делает ее самой свежей
+        assert!(cache.get("old").is_some());
+
+        cache.put("new", &encoded(100));
+
+        assert!(cache.contains("old"));
+        assert!(!cache.contains("recent"));
+        assert!(cache.contains("new"));
+        assert!(cache.size() <= 250);
+    }
+}
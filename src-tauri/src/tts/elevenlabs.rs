@@ -0,0 +1,249 @@
+// app/src/tts/elevenlabs.rs - ElevenLabs (и совместимые) HTTP TTS с потоковым воспроизведением
+
+use std::io::Read;
+use std::sync::mpsc::channel;
+
+use log::{info, warn};
+
+use super::markup::{self, Segment, SsmlSupport};
+use super::stream::{PcmDecoder, PcmStream, StreamChunk};
+use super::{AudioBuffer, TtsBackend, TtsConfig, TtsEngine};
+use crate::config;
+use crate::db;
+use crate::db::structs::ElevenLabsSettings;
+use crate::error::{JarvisResult, JarvisError, AudioError};
+
+// размер порции чтения ответа (~64 мс при 16 кГц)
+const READ_CHUNK_BYTES: usize = 2048;
+
+pub struct ElevenLabsBackend {
+    api_key: String,
+    settings: ElevenLabsSettings,
+}
+
+impl ElevenLabsBackend {
+    pub fn new(api_key: String, settings: ElevenLabsSettings) -> Self {
+        Self { api_key, settings }
+    }
+
+    /// Движок с ключом и параметрами из настроек (None, если ключ не задан)
+    pub fn from_settings() -> Option<Self> {
+        let settings = db::get_current_settings()?;
+
+        if settings.api_keys.elevenlabs.trim().is_empty() {
+            return None;
+        }
+
+        Some(Self::new(settings.api_keys.elevenlabs, settings.tts_config.elevenlabs))
+    }
+
+    fn voice_id<'a>(&'a self, settings: &'a TtsConfig) -> &'a str {
+        if settings.voice_id.is_empty() || settings.voice_id == "default" {
+            &self.settings.voice_id
+        } else {
+            &settings.voice_id
+        }
+    }
+
+    fn stream_url(&self, voice_id: &str) -> String {
+        format!(
+            "{}/v1/text-to-speech/{}/stream?output_format=pcm_{}",
+            self.settings.base_url.trim_end_matches('/'),
+            urlencoding::encode(voice_id),
+            config::ELEVENLABS_SAMPLE_RATE
+        )
+    }
+}
+
+impl TtsBackend for ElevenLabsBackend {
+    fn engine(&self) -> TtsEngine {
+        TtsEngine::ElevenLabs
+    }
+
+    fn is_available(&self) -> bool {
+        !self.api_key.trim().is_empty() && !self.settings.base_url.trim().is_empty()
+    }
+
+    /// Ответ читается в фоне, воспроизведение начинается с первыми данными
+    fn synthesize(&self, text: &str, settings: &TtsConfig) -> JarvisResult<AudioBuffer> {
+        let url = self.stream_url(self.voice_id(settings));
+        let payload = serde_json::json!({
+            "text": text,
+            "model_id": self.settings.model_id,
+            "voice_settings": {
+                "stability": self.settings.stability,
+                "similarity_boost": self.settings.similarity_boost,
+                "speed": settings.speed,
+            }
+        });
+        let api_key = self.api_key.clone();
+
+        // ошибки запроса и статуса возвращаются сразу, до начала воспроизведения
+        let mut response = super::run_blocking(move || {
+            let response = reqwest::blocking::Client::new()
+                .post(&url)
+                .header("xi-api-key", api_key)
+                .json(&payload)
+                .timeout(config::TTS_REQUEST_TIMEOUT)
+                .send()
+                .map_err(request_failed)?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().unwrap_or_default();
+                return Err(JarvisError::AudioError(AudioError::PlaybackFailed(
+                    format!("ElevenLabs TTS error {}: {}", status, body)
+                )));
+            }
+
+            Ok(response)
+        })?;
+
+        let (sender, receiver) = channel();
+
+        std::thread::spawn(move || {
+            let mut decoder = PcmDecoder::default();
+            let mut buffer = [0u8; READ_CHUNK_BYTES];
+
+            loop {
+                let chunk = match response.read(&mut buffer) {
+                    Ok(0) => StreamChunk::End,
+                    Ok(read) => StreamChunk::Samples(decoder.decode(&buffer[..read])),
+                    Err(e) => StreamChunk::Error(format!("ElevenLabs stream failed: {}", e)),
+                };
+                let last = !matches!(chunk, StreamChunk::Samples(_));
+
+                // получатель закрыт - воспроизведение прервано
+                if sender.send(chunk).is_err() {
+                    info!("ElevenLabs stream dropped by player");
+                    return;
+                }
+
+                if last {
+                    return;
+                }
+            }
+        });
+
+        Ok(AudioBuffer::Stream(PcmStream::new(receiver, config::ELEVENLABS_SAMPLE_RATE, 1)))
+    }
+
+    // ElevenLabs понимает только паузы <break>, остальная разметка отбрасывается
+    fn synthesize_segments(&self, segments: &[Segment], settings: &TtsConfig) -> JarvisResult<AudioBuffer> {
+        self.synthesize(&markup::render_ssml(segments, &SsmlSupport::BREAKS_ONLY, &settings.language), settings)
+    }
+
+    fn voices(&self) -> JarvisResult<Vec<String>> {
+        let url = format!("{}/v1/voices", self.settings.base_url.trim_end_matches('/'));
+        let api_key = self.api_key.clone();
+
+        let voices: serde_json::Value = super::run_blocking(move || {
+            reqwest::blocking::Client::new()
+                .get(&url)
+                .header("xi-api-key", api_key)
+                .timeout(config::TTS_REQUEST_TIMEOUT)
+                .send()
+                .and_then(|response| response.error_for_status())
+                .and_then(|response| response.json())
+                .map_err(request_failed)
+        }).unwrap_or_else(|e| {
+            warn!("Failed to load ElevenLabs voices: {}", e);
+            serde_json::Value::Null
+        });
+
+        let mut ids = voices["voices"].as_array()
+            .map(|voices| voices.iter()
+                .filter_map(|voice| voice["voice_id"].as_str().map(|id| id.to_string()))
+                .collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        if ids.is_empty() {
+            ids.push(self.settings.voice_id.clone());
+        }
+
+        Ok(ids)
+    }
+}
+
+fn request_failed(e: reqwest::Error) -> JarvisError {
+    JarvisError::AudioError(AudioError::PlaybackFailed(format!("ElevenLabs TTS request failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_server;
+
+    fn backend(base_url: String) -> ElevenLabsBackend {
+        ElevenLabsBackend::new("test-key".to_string(), ElevenLabsSettings {
+            base_url,
+            voice_id: "voice-1".to_string(),
+            stability: 0.3,
+            ..ElevenLabsSettings::default()
+        })
+    }
+
+    #[test]
+    fn test_streams_pcm_from_server() {
+        let samples: Vec<i16> = vec![0, 1000, -1000, i16::MAX, i16::MIN];
+        let body = samples.iter().flat_map(|s| s.to_le_bytes()).collect::<Vec<_>>();
+        let (base_url, server) = mock_server("200 OK", "application/octet-stream", body);
+
+        let buffer = backend(base_url).synthesize("Привет", &TtsConfig::default()).unwrap();
+        let stream = match buffer {
+            AudioBuffer::Stream(stream) => stream,
+            other => panic!("expected stream, got {:?}", other),
+        };
+
+        assert_eq!(stream.sample_rate, config::ELEVENLABS_SAMPLE_RATE);
+        assert_eq!(stream.into_samples().unwrap(), samples);
+
+        let (request_line, headers, request_body) = server.join().unwrap();
+        assert!(request_line.starts_with("POST /v1/text-to-speech/voice-1/stream?output_format=pcm_"));
+        assert!(headers.contains(&"xi-api-key: test-key".to_string()));
+
+        let payload: serde_json::Value = serde_json::from_str(&request_body).unwrap();
+        assert_eq!(payload["text"], "Привет");
+        assert!((payload["voice_settings"]["stability"].as_f64().unwrap() - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_voice_id_from_voice_settings() {
+        let (base_url, server) = mock_server("200 OK", "application/octet-stream", vec![0, 0]);
+
+        let settings = TtsConfig { voice_id: "custom voice".to_string(), ..Default::default() };
+        let buffer = backend(base_url).synthesize("тест", &settings).unwrap();
+        if let AudioBuffer::Stream(stream) = buffer {
+            stream.into_samples().unwrap();
+        }
+
+        let (request_line, _, _) = server.join().unwrap();
+        assert!(request_line.contains("/v1/text-to-speech/custom%20voice/stream"));
+    }
+
+    #[test]
+    fn test_markup_sent_as_breaks() {
+        let (base_url, server) = mock_server("200 OK", "application/octet-stream", vec![0, 0]);
+
+        let segments = markup::parse("Раз<pause 1s><emph>два</emph>");
+        let buffer = backend(base_url).synthesize_segments(&segments, &TtsConfig::default()).unwrap();
+        if let AudioBuffer::Stream(stream) = buffer {
+            stream.into_samples().unwrap();
+        }
+
+        let (_, _, request_body) = server.join().unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&request_body).unwrap();
+        assert_eq!(payload["text"], "Раз<break time=\"1s\"/>два");
+    }
+
+    #[test]
+    fn test_error_status_is_reported() {
+        let (base_url, server) = mock_server("401 Unauthorized", "application/octet-stream", b"{\"detail\": \"invalid api key\"}".to_vec());
+
+        let result = backend(base_url).synthesize("тест", &TtsConfig::default());
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("401"));
+        server.join().unwrap();
+    }
+}
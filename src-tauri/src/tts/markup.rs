@@ -0,0 +1,506 @@
+// app/src/tts/markup.rs - Упрощенная разметка речи (SSML-lite)
+//
+// Поддерживаемые теги:
+//   <pause>, <pause 300ms>, <pause 1.5s>  - пауза (по умолчанию 500 мс)
+//   <emph>...</emph>                       - выделение
+//   <spell>...</spell>                     - произнести по буквам
+//   <lang en>...</lang>                    - другой язык
+//   <rate 1.5>...</rate>, <rate 80%>       - скорость речи
+// Неизвестные и некорректные теги остаются в тексте как есть.
+
+use std::io::Cursor;
+use std::time::Duration;
+
+use super::AudioBuffer;
+use crate::config;
+use crate::error::{JarvisResult, JarvisError, AudioError};
+
+// частота для фразы из одних пауз
+const FALLBACK_SAMPLE_RATE: u32 = 16000;
+// длинный "тег" - скорее всего обычный текст со знаком <
+const MAX_TAG_LEN: usize = 32;
+
+/// Оформление фрагмента текста
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Style {
+    pub emphasis: bool,
+    pub spell: bool,
+    pub language: Option<String>,
+    pub rate: Option<f32>,
+}
+
+/// Нейтральное представление размеченной фразы
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Text { text: String, style: Style },
+    Pause(Duration),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TagKind {
+    Emphasis,
+    Spell,
+    Language,
+    Rate,
+}
+
+enum Tag {
+    Pause(Duration),
+    Open(TagKind, Style),
+    Close(TagKind),
+}
+
+/// Разбор размеченного текста в сегменты
+/// Незакрытые теги действуют до конца фразы, лишние закрывающие теги отбрасываются
+pub fn parse(input: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut style = Style::default();
+    let mut open: Vec<(TagKind, Style)> = Vec::new();
+    let mut rest = input;
+
+    while let Some(start) = rest.find('<') {
+        push_text(&mut segments, &rest[..start], &style);
+        rest = &rest[start..];
+
+        let tag = rest[1..].find('>')
+            .filter(|end| *end <= MAX_TAG_LEN)
+            .and_then(|end| parse_tag(&rest[1..end + 1], &style).map(|tag| (tag, end + 2)));
+
+        let (tag, consumed) = match tag {
+            Some(tag) => tag,
+            None => {
+                push_text(&mut segments, "<", &style);
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        rest = &rest[consumed..];
+
+        match tag {
+            Tag::Pause(duration) => segments.push(Segment::Pause(duration)),
+            Tag::Open(kind, new_style) => {
+                open.push((kind, std::mem::replace(&mut style, new_style)));
+            }
+            Tag::Close(kind) => {
+                // закрываются и все вложенные теги
+                if let Some(position) = open.iter().rposition(|(open_kind, _)| *open_kind == kind) {
+                    style = open[position].1.clone();
+                    open.truncate(position);
+                }
+            }
+        }
+    }
+
+    push_text(&mut segments, rest, &style);
+    segments
+}
+
+fn push_text(segments: &mut Vec<Segment>, text: &str, style: &Style) {
+    if text.is_empty() {
+        return;
+    }
+
+    if let Some(Segment::Text { text: last, style: last_style }) = segments.last_mut() {
+        if last_style == style {
+            last.push_str(text);
+            return;
+        }
+    }
+
+    segments.push(Segment::Text { text: text.to_string(), style: style.clone() });
+}
+
+fn parse_tag(inner: &str, style: &Style) -> Option<Tag> {
+    let inner = inner.trim().trim_end_matches('/').trim();
+
+    if let Some(name) = inner.strip_prefix('/') {
+        return tag_kind(name.trim()).map(Tag::Close);
+    }
+
+    let mut parts = inner.split_whitespace();
+    let name = parts.next()?.to_lowercase();
+    let argument = parts.next().map(|arg| arg.trim_start_matches("time=").trim_matches('"'));
+    if parts.next().is_some() {
+        return None;
+    }
+
+    if name == "pause" || name == "break" {
+        let duration = match argument {
+            Some(arg) => parse_duration(arg)?,
+            None => config::TTS_DEFAULT_PAUSE,
+        };
+        return Some(Tag::Pause(duration.min(config::TTS_MAX_PAUSE)));
+    }
+
+    let kind = tag_kind(&name)?;
+    let mut style = style.clone();
+
+    match (kind, argument) {
+        (TagKind::Emphasis, None) => style.emphasis = true,
+        (TagKind::Spell, None) => style.spell = true,
+        (TagKind::Language, Some(language)) if is_language_code(language) => {
+            style.language = Some(language.to_string());
+        }
+        (TagKind::Rate, Some(rate)) => style.rate = Some(parse_rate(rate)?),
+        _ => return None,
+    }
+
+    Some(Tag::Open(kind, style))
+}
+
+fn tag_kind(name: &str) -> Option<TagKind> {
+    match name.to_lowercase().as_str() {
+        "emph" | "emphasis" => Some(TagKind::Emphasis),
+        "spell" => Some(TagKind::Spell),
+        "lang" => Some(TagKind::Language),
+        "rate" => Some(TagKind::Rate),
+        _ => None,
+    }
+}
+
+// "300ms", "1.5s", "300" (мс)
+fn parse_duration(value: &str) -> Option<Duration> {
+    let millis = if let Some(ms) = value.strip_suffix("ms") {
+        ms.parse::<f32>().ok()?
+    } else if let Some(s) = value.strip_suffix('s') {
+        s.parse::<f32>().ok()? * 1000.0
+    } else {
+        value.parse::<f32>().ok()?
+    };
+
+    (millis.is_finite() && millis >= 0.0).then(|| Duration::from_millis(millis.round() as u64))
+}
+
+// "1.5", "150%"
+fn parse_rate(value: &str) -> Option<f32> {
+    let rate = match value.strip_suffix('%') {
+        Some(percent) => percent.parse::<f32>().ok()? / 100.0,
+        None => value.parse::<f32>().ok()?,
+    };
+
+    (rate.is_finite() && rate > 0.0).then(|| rate.clamp(0.5, 2.0))
+}
+
+// "en", "en-US", "ru_RU"
+fn is_language_code(value: &str) -> bool {
+    (2..=10).contains(&value.len())
+        && value.chars().all(|c| c.is_ascii_alphabetic() || c == '-' || c == '_')
+}
+
+/// Фраза без разметки (оформление не задано, пауз нет)
+pub fn is_plain(segments: &[Segment]) -> bool {
+    segments.iter().all(|segment| matches!(segment, Segment::Text { style, .. } if *style == Style::default()))
+}
+
+/// Текст фрагмента в том виде, в котором его нужно произнести
+/// (по буквам - буквы через пробел, слова через запятую)
+pub fn spoken_text(text: &str, style: &Style) -> String {
+    if !style.spell {
+        return text.to_string();
+    }
+
+    text.split_whitespace()
+        .map(|word| word.chars().map(String::from).collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Текст без разметки для движков, которые ее не поддерживают
+pub fn plain_text(segments: &[Segment]) -> String {
+    let mut result = String::new();
+
+    for segment in segments {
+        match segment {
+            Segment::Text { text, style } => {
+                // буквы не сливаются с предыдущим словом
+                if style.spell && result.ends_with(|c: char| !c.is_whitespace()) {
+                    result.push(' ');
+                }
+                result.push_str(&spoken_text(text, style));
+            }
+            Segment::Pause(_) => result.push(' '),
+        }
+    }
+
+    result.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Теги SSML, которые понимает движок
+#[derive(Debug, Clone, Copy)]
+pub struct SsmlSupport {
+    // Обертка <speak> с языком фразы
+    pub speak_root: bool,
+    pub breaks: bool,
+    pub emphasis: bool,
+    pub say_as: bool,
+    pub language: bool,
+    pub prosody: bool,
+}
+
+impl SsmlSupport {
+    pub const FULL: Self = Self {
+        speak_root: true,
+        breaks: true,
+        emphasis: true,
+        say_as: true,
+        language: true,
+        prosody: true,
+    };
+
+    // Только паузы внутри обычного текста (ElevenLabs)
+    pub const BREAKS_ONLY: Self = Self {
+        speak_root: false,
+        breaks: true,
+        emphasis: false,
+        say_as: false,
+        language: false,
+        prosody: false,
+    };
+}
+
+/// Преобразование сегментов в SSML; неподдерживаемые теги отбрасываются
+pub fn render_ssml(segments: &[Segment], support: &SsmlSupport, language: &str) -> String {
+    let mut body = String::new();
+
+    for segment in segments {
+        match segment {
+            Segment::Pause(duration) if support.breaks => {
+                body.push_str(&format!("<break time=\"{}s\"/>", duration.as_secs_f32()));
+            }
+            Segment::Pause(_) => body.push(' '),
+            Segment::Text { text, style } => {
+                let (mut open, mut close) = (String::new(), Vec::new());
+
+                if let Some(language) = style.language.as_ref().filter(|_| support.language) {
+                    open.push_str(&format!("<voice xml:lang=\"{}\">", language));
+                    close.push("</voice>");
+                }
+                if let Some(rate) = style.rate.filter(|_| support.prosody) {
+                    open.push_str(&format!("<prosody rate=\"{}%\">", (rate * 100.0).round()));
+                    close.push("</prosody>");
+                }
+                if style.emphasis && support.emphasis {
+                    open.push_str("<emphasis>");
+                    close.push("</emphasis>");
+                }
+
+                let content = if style.spell && support.say_as {
+                    open.push_str("<say-as interpret-as=\"characters\">");
+                    close.push("</say-as>");
+                    text.clone()
+                } else {
+                    spoken_text(text, style)
+                };
+
+                body.push_str(&open);
+                body.push_str(&escape_xml(&content));
+                close.iter().rev().for_each(|tag| body.push_str(tag));
+            }
+        }
+    }
+
+    if !support.speak_root {
+        return body;
+    }
+
+    format!(
+        "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" xml:lang=\"{}\">{}</speak>",
+        escape_xml(language), body
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Синтез по сегментам с тишиной на месте пауз (для движков без поддержки разметки)
+/// `synthesize` получает произносимый текст и оформление, возвращает моно сэмплы и частоту
+pub fn synthesize_concatenated<F>(segments: &[Segment], mut synthesize: F) -> JarvisResult<AudioBuffer>
+where
+    F: FnMut(&str, &Style) -> JarvisResult<(Vec<i16>, u32)>,
+{
+    enum Part {
+        Audio(Vec<i16>, u32),
+        Pause(Duration),
+    }
+
+    let mut parts = Vec::new();
+    let mut sample_rate = None;
+
+    for segment in segments {
+        match segment {
+            Segment::Pause(duration) => parts.push(Part::Pause(*duration)),
+            Segment::Text { text, style } => {
+                let text = spoken_text(text, style);
+                if text.trim().is_empty() {
+                    continue;
+                }
+
+                let (samples, rate) = synthesize(text.trim(), style)?;
+                sample_rate.get_or_insert(rate);
+                parts.push(Part::Audio(samples, rate));
+            }
+        }
+    }
+
+    // частота первого фрагмента, остальные приводятся к ней
+    let sample_rate = sample_rate.unwrap_or(FALLBACK_SAMPLE_RATE);
+    let mut samples = Vec::new();
+
+    for part in parts {
+        match part {
+            Part::Audio(audio, rate) => samples.extend(resample(&audio, rate, sample_rate)),
+            Part::Pause(duration) => {
+                let silence = (sample_rate as f32 * duration.as_secs_f32()) as usize;
+                samples.resize(samples.len() + silence, 0);
+            }
+        }
+    }
+
+    Ok(AudioBuffer::Pcm { samples, sample_rate, channels: 1 })
+}
+
+/// WAV в моно сэмплы и частоту
+/// Длина в заголовке не проверяется: espeak пишет в stdout заголовок с фиктивной длиной
+pub fn decode_wav(data: &[u8]) -> JarvisResult<(Vec<i16>, u32)> {
+    let mut reader = hound::WavReader::new(Cursor::new(data))
+        .map_err(|e| JarvisError::AudioError(AudioError::PlaybackFailed(
+            format!("Invalid WAV from TTS engine: {}", e)
+        )))?;
+
+    let spec = reader.spec();
+    let channels = spec.channels.max(1) as usize;
+    let samples = reader.samples::<i16>().map_while(Result::ok).collect::<Vec<_>>();
+
+    let mono = if channels == 1 {
+        samples
+    } else {
+        samples.chunks(channels)
+            .map(|frame| (frame.iter().map(|&s| s as i32).sum::<i32>() / frame.len() as i32) as i16)
+            .collect()
+    };
+
+    Ok((mono, spec.sample_rate))
+}
+
+// линейная интерполяция, для склейки фрагментов разных голосов
+fn resample(samples: &[i16], from: u32, to: u32) -> Vec<i16> {
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+
+    let length = (samples.len() as u64 * to as u64 / from as u64) as usize;
+    let last = samples.len() - 1;
+
+    (0..length)
+        .map(|i| {
+            let position = i as f64 * from as f64 / to as f64;
+            let index = (position as usize).min(last);
+            let fraction = position - index as f64;
+            let (a, b) = (samples[index] as f64, samples[(index + 1).min(last)] as f64);
+            (a + (b - a) * fraction) as i16
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str, style: Style) -> Segment {
+        Segment::Text { text: text.to_string(), style }
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        let segments = parse("Внимание!<pause 300ms> Код <spell>ABC</spell>, <emph>срочно</emph>");
+
+        assert_eq!(segments, vec![
+            text("Внимание!", Style::default()),
+            Segment::Pause(Duration::from_millis(300)),
+            text(" Код ", Style::default()),
+            text("ABC", Style { spell: true, ..Style::default() }),
+            text(", ", Style::default()),
+            text("срочно", Style { emphasis: true, ..Style::default() }),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_nested_and_unclosed() {
+        let segments = parse("<rate 150%>быстро <lang en>hello</rate> обычно <lang en-US>world");
+
+        assert_eq!(segments, vec![
+            text("быстро ", Style { rate: Some(1.5), ..Style::default() }),
+            text("hello", Style { rate: Some(1.5), language: Some("en".to_string()), ..Style::default() }),
+            text(" обычно ", Style::default()),
+            text("world", Style { language: Some("en-US".to_string()), ..Style::default() }),
+        ]);
+    }
+
+    #[test]
+    fn test_unknown_tags_are_text() {
+        let input = "если a <b и c> d, то <unknown> и </emph> <pause forever>";
+        let segments = parse(input);
+
+        assert!(is_plain(&segments));
+        assert_eq!(plain_text(&segments), "если a <b и c> d, то <unknown> и <pause forever>");
+
+        // паузы ограничены сверху
+        assert_eq!(parse("<pause 60s>"), vec![Segment::Pause(config::TTS_MAX_PAUSE)]);
+        assert_eq!(parse("<break/>"), vec![Segment::Pause(config::TTS_DEFAULT_PAUSE)]);
+    }
+
+    #[test]
+    fn test_plain_text_strips_markup() {
+        let segments = parse("Ваш код<pause><spell>AB 12</spell>. <emph>Готово</emph>");
+        assert_eq!(plain_text(&segments), "Ваш код A B, 1 2. Готово");
+    }
+
+    #[test]
+    fn test_render_ssml() {
+        let segments = parse("Я & <emph>ты</emph><pause 1.5s><lang en><spell>OK</spell></lang>");
+
+        assert_eq!(
+            render_ssml(&segments, &SsmlSupport::FULL, "ru-RU"),
+            "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" xml:lang=\"ru-RU\">\
+             Я &amp; <emphasis>ты</emphasis><break time=\"1.5s\"/>\
+             <voice xml:lang=\"en\"><say-as interpret-as=\"characters\">OK</say-as></voice></speak>"
+        );
+
+        assert_eq!(
+            render_ssml(&segments, &SsmlSupport::BREAKS_ONLY, "ru-RU"),
+            "Я &amp; ты<break time=\"1.5s\"/>O K"
+        );
+    }
+
+    #[test]
+    fn test_synthesize_concatenated() {
+        let segments = parse("раз<pause 500ms><lang en>two</lang>");
+        let mut calls = Vec::new();
+
+        let buffer = synthesize_concatenated(&segments, |text, style| {
+            calls.push((text.to_string(), style.language.clone()));
+            // второй голос с вдвое большей частотой
+            match style.language {
+                None => Ok((vec![100; 10], 1000)),
+                Some(_) => Ok((vec![200; 20], 2000)),
+            }
+        }).unwrap();
+
+        assert_eq!(calls, vec![("раз".to_string(), None), ("two".to_string(), Some("en".to_string()))]);
+
+        match buffer {
+            AudioBuffer::Pcm { samples, sample_rate, channels } => {
+                assert_eq!((sample_rate, channels), (1000, 1));
+                assert_eq!(samples.len(), 10 + 500 + 10);
+                assert!(samples[10..510].iter().all(|&s| s == 0));
+                assert!(samples[510..].iter().all(|&s| s == 200));
+            }
+            other => panic!("expected PCM, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,618 @@
+// app/src/tts/mod.rs - Text-to-Speech: движки синтеза и воспроизведение через общий аудио плеер
+
+pub mod system;
+pub mod openai;
+pub mod process;
+pub mod queue;
+pub mod cache;
+pub mod piper;
+pub mod stream;
+pub mod elevenlabs;
+pub mod markup;
+pub mod normalize;
+
+use std::path::PathBuf;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, Once, OnceLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use log::{debug, info, warn};
+
+use crate::error::{JarvisResult, JarvisError, AudioError};
+use crate::audio;
+use crate::config;
+use crate::events;
+use crate::APP_CONFIG_DIR;
+
+use self::openai::OpenAiBackend;
+use self::piper::PiperBackend;
+use self::elevenlabs::ElevenLabsBackend;
+use self::stream::{PcmStream, StreamSource};
+use self::system::SystemBackend;
+use self::queue::{QueueStatus, SpeechQueue, Utterance};
+use self::cache::TtsCache;
+use self::markup::Segment;
+
+pub use self::queue::{SpeechPriority, UtteranceHandle};
+
+// Настройки голоса и выбор движка хранятся в Settings.tts_config
+pub use crate::db::structs::{TtsConfig, TtsEngine};
+
+/// Результат синтеза речи
+#[derive(Debug)]
+pub enum AudioBuffer {
+    // Сырые 16-битные сэмплы
+    Pcm {
+        samples: Vec<i16>,
+        sample_rate: u32,
+        channels: u16,
+    },
+    // Закодированный файл (WAV, MP3), декодируется плеером
+    Encoded(Vec<u8>),
+    // PCM, поступающий по мере синтеза
+    Stream(PcmStream),
+}
+
+/// Общий интерфейс движков синтеза речи
+/// Движок только синтезирует аудио, воспроизведение выполняет общий плеер
+pub trait TtsBackend: Send + Sync {
+    /// Тип движка
+    fn engine(&self) -> TtsEngine;
+
+    /// Проверка работоспособности движка
+    fn is_available(&self) -> bool;
+
+    /// Синтез фразы в аудио буфер
+    fn synthesize(&self, text: &str, settings: &TtsConfig) -> JarvisResult<AudioBuffer>;
+
+    /// Синтез размеченной фразы (см. markup); по умолчанию разметка отбрасывается
+    fn synthesize_segments(&self, segments: &[Segment], settings: &TtsConfig) -> JarvisResult<AudioBuffer> {
+        self.synthesize(&markup::plain_text(segments), settings)
+    }
+
+    /// Доступные голоса
+    fn voices(&self) -> JarvisResult<Vec<String>>;
+}
+
+// Основная структура TTS (копия используется для заполнения кэша без блокировки)
+#[derive(Clone)]
+pub struct TtsManager {
+    backend: Arc<dyn TtsBackend>,
+    settings: TtsConfig,
+    cache: Option<TtsCache>,
+}
+
+impl TtsManager {
+    fn new(settings: TtsConfig) -> JarvisResult<Self> {
+        let backend = create_backend(&settings)?;
+        info!("TTS engine: {:?} (selected: {:?})", backend.engine(), settings.engine);
+
+        // Кэш синтезированной речи (0 - отключен)
+        let cache = (settings.cache_size_mb > 0)
+            .then(|| TtsCache::new(cache_dir(), settings.cache_size_mb * 1024 * 1024));
+
+        Ok(Self {
+            backend: Arc::from(backend),
+            settings,
+            cache,
+        })
+    }
+
+    /// Синтез фразы с разметкой: движок получает сегменты, только если разметка есть
+    /// Числа, даты и сокращения в русском тексте предварительно раскрываются в слова
+    fn synthesize(&self, text: &str) -> JarvisResult<AudioBuffer> {
+        self.synthesize_with(text, &self.settings)
+    }
+
+    /// Синтез с указанными настройками голоса (движок остается текущим)
+    fn synthesize_with(&self, text: &str, settings: &TtsConfig) -> JarvisResult<AudioBuffer> {
+        let segments = normalize::normalize_segments(markup::parse(text), &settings.language);
+
+        if markup::is_plain(&segments) {
+            self.backend.synthesize(&markup::plain_text(&segments), settings)
+        } else {
+            self.backend.synthesize_segments(&segments, settings)
+        }
+    }
+
+    /// Синтез с использованием дискового кэша; редкие фразы вытесняются по LRU
+    fn synthesize_cached(&self, text: &str) -> JarvisResult<AudioBuffer> {
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => return self.synthesize(text),
+        };
+
+        let key = cache::cache_key(text, self.backend.engine(), &self.settings);
+        if let Some(buffer) = cache.get(&key) {
+            debug!("TTS cache hit: '{}'", text);
+            return Ok(buffer);
+        }
+
+        let buffer = self.synthesize(text)?;
+
+        // поток сохраняется в кэш после полного получения
+        if let AudioBuffer::Stream(stream) = buffer {
+            let cache = cache.clone();
+            let (sample_rate, channels) = (stream.sample_rate, stream.channels);
+
+            return Ok(AudioBuffer::Stream(stream.on_complete(move |samples| {
+                cache.put(&key, &AudioBuffer::Pcm { samples, sample_rate, channels });
+            })));
+        }
+
+        cache.put(&key, &buffer);
+        Ok(buffer)
+    }
+
+    fn is_cached(&self, text: &str) -> bool {
+        self.cache.as_ref().is_some_and(|cache| {
+            cache.contains(&cache::cache_key(text, self.backend.engine(), &self.settings))
+        })
+    }
+
+    // параметры, от которых зависят ключи кэша (None - кэш отключен)
+    fn cache_fingerprint(&self) -> Option<String> {
+        self.cache.as_ref().map(|_| cache::cache_key("", self.backend.engine(), &self.settings))
+    }
+}
+
+// Активный движок и настройки (пересоздается при изменении настроек)
+static TTS_MANAGER: Mutex<Option<TtsManager>> = Mutex::new(None);
+
+// Очередь фраз и сигнал для потока озвучивания
+static QUEUE: Mutex<SpeechQueue> = Mutex::new(SpeechQueue::new(config::TTS_LOW_PRIORITY_TTL));
+static QUEUE_SIGNAL: Condvar = Condvar::new();
+static WORKER: Once = Once::new();
+
+// Поток озвучивания: прерывание завершает только его процессы синтеза, заполнение кэша продолжается
+static WORKER_THREAD: OnceLock<std::thread::ThreadId> = OnceLock::new();
+
+// Фраза, которая сейчас озвучивается
+static CURRENT: Mutex<Option<Utterance>> = Mutex::new(None);
+
+// Счетчик прерываний: синтез, начатый до прерывания, не воспроизводится
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Инициализация (или повторная инициализация) TTS по настройкам из Settings.tts_config
+pub fn init() -> JarvisResult<()> {
+    info!("Initializing Text-to-Speech system...");
+
+    // Создаем cache директорию
+    std::fs::create_dir_all(cache_dir())
+        .map_err(|e| JarvisError::AudioError(AudioError::InitializationFailed(
+            format!("Failed to create TTS cache directory: {}", e)
+        )))?;
+
+    // Речь воспроизводится через общий плеер
+    if audio::rodio::init().is_err() {
+        return Err(JarvisError::AudioError(AudioError::InitializationFailed(
+            "Failed to initialize audio player for TTS".to_string()
+        )));
+    }
+
+    let settings = crate::db::get_current_settings()
+        .map(|settings| settings.tts_config)
+        .unwrap_or_default();
+
+    let manager = TtsManager::new(settings)?;
+    let cache_changed = {
+        let mut current = lock_manager()?;
+        let changed = current.as_ref().and_then(TtsManager::cache_fingerprint) != manager.cache_fingerprint();
+        *current = Some(manager);
+        changed
+    };
+
+    // Частые фразы синтезируются заранее, если их еще нет в кэше с текущим голосом
+    if cache_changed {
+        prewarm(config::TTS_PREWARM_PHRASES.iter().map(|phrase| phrase.to_string()).collect());
+    }
+
+    info!("TTS system initialized successfully");
+    Ok(())
+}
+
+fn cache_dir() -> PathBuf {
+    APP_CONFIG_DIR.get()
+        .cloned()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("tts_cache")
+}
+
+/// Предварительный синтез фраз в кэш (в фоне), чтобы они воспроизводились без задержки
+/// Синтез идет на копии менеджера и не задерживает речь
+pub fn prewarm(phrases: Vec<String>) {
+    std::thread::spawn(move || {
+        let manager = match current_manager() {
+            Ok(manager) if manager.cache.is_some() => manager,
+            _ => return,
+        };
+        let mut synthesized = 0;
+
+        for phrase in phrases {
+            if manager.is_cached(&phrase) {
+                continue;
+            }
+
+            let result = manager.synthesize_cached(&phrase).and_then(|buffer| match buffer {
+                // поток попадает в кэш после полного получения
+                AudioBuffer::Stream(stream) => stream.into_samples().map(|_| ()).map_err(|e| {
+                    JarvisError::AudioError(AudioError::PlaybackFailed(e))
+                }),
+                _ => Ok(()),
+            });
+
+            match result {
+                Ok(_) => synthesized += 1,
+                Err(e) => warn!("Failed to prewarm TTS phrase '{}': {}", phrase, e),
+            }
+        }
+
+        if synthesized > 0 {
+            info!("TTS cache prewarmed with {} phrases", synthesized);
+        }
+    });
+}
+
+/// Очистка кэша синтезированной речи
+pub fn clear_cache() -> JarvisResult<usize> {
+    with_manager(|manager| Ok(manager.cache.as_ref().map(|cache| cache.clear()).unwrap_or(0)))
+}
+
+/// Движок, выбранный в настройках; если он недоступен - системный TTS
+fn create_backend(settings: &TtsConfig) -> JarvisResult<Box<dyn TtsBackend>> {
+    match selected_backend(settings) {
+        Ok(backend) => return Ok(backend),
+        Err(e) if settings.engine != TtsEngine::System => {
+            warn!("TTS engine {:?} unavailable ({}), falling back to system TTS", settings.engine, e);
+        }
+        Err(e) => return Err(e),
+    }
+
+    let backend = SystemBackend;
+    if backend.is_available() {
+        return Ok(Box::new(backend));
+    }
+
+    Err(JarvisError::AudioError(AudioError::InitializationFailed(
+        "No working TTS engine found".to_string()
+    )))
+}
+
+fn selected_backend(settings: &TtsConfig) -> JarvisResult<Box<dyn TtsBackend>> {
+    let backend: Box<dyn TtsBackend> = match settings.engine {
+        TtsEngine::System => Box::new(SystemBackend),
+        TtsEngine::OpenAI => Box::new(OpenAiBackend::from_settings()
+            .ok_or_else(|| engine_unavailable("OpenAI API key not set"))?),
+        TtsEngine::ElevenLabs => Box::new(ElevenLabsBackend::from_settings()
+            .ok_or_else(|| engine_unavailable("ElevenLabs API key not set"))?),
+        TtsEngine::Local => Box::new(PiperBackend::new()?),
+    };
+
+    if !backend.is_available() {
+        return Err(engine_unavailable(&format!("{:?} TTS engine is not available", settings.engine)));
+    }
+
+    Ok(backend)
+}
+
+fn engine_unavailable(reason: &str) -> JarvisError {
+    JarvisError::AudioError(AudioError::InitializationFailed(reason.to_string()))
+}
+
+fn lock_manager() -> JarvisResult<MutexGuard<'static, Option<TtsManager>>> {
+    TTS_MANAGER.lock()
+        .map_err(|e| JarvisError::AudioError(AudioError::PlaybackFailed(
+            format!("Failed to lock TTS manager: {}", e)
+        )))
+}
+
+// копия инициализированного менеджера (движок общий, через Arc)
+fn current_manager() -> JarvisResult<TtsManager> {
+    with_manager(|manager| Ok(manager.clone()))
+}
+
+// выполнение действия над инициализированным менеджером
+fn with_manager<T>(f: impl FnOnce(&mut TtsManager) -> JarvisResult<T>) -> JarvisResult<T> {
+    let mut manager = lock_manager()?;
+    let manager = manager.as_mut().ok_or_else(|| JarvisError::AudioError(AudioError::InitializationFailed(
+        "TTS not initialized".to_string()
+    )))?;
+
+    f(manager)
+}
+
+/// Озвучивание фразы с обычным приоритетом (блокирует до завершения)
+pub fn speak(text: &str) -> JarvisResult<()> {
+    if !is_initialized() {
+        return Err(JarvisError::AudioError(AudioError::InitializationFailed(
+            "TTS not initialized".to_string()
+        )));
+    }
+
+    enqueue(text, SpeechPriority::Normal)
+        .wait()
+        .map_err(|e| JarvisError::AudioError(AudioError::PlaybackFailed(e)))
+}
+
+/// Озвучивание фразы с разовыми настройками голоса (блокирует до завершения)
+/// Настройки не сохраняются и не меняют текущие; движок остается текущим, аудио не кэшируется
+pub fn speak_with_settings(text: &str, settings: TtsConfig) -> JarvisResult<()> {
+    if !is_initialized() {
+        return Err(JarvisError::AudioError(AudioError::InitializationFailed(
+            "TTS not initialized".to_string()
+        )));
+    }
+
+    enqueue_with_settings(text, SpeechPriority::Normal, Some(settings))
+        .wait()
+        .map_err(|e| JarvisError::AudioError(AudioError::PlaybackFailed(e)))
+}
+
+/// Добавление фразы в очередь озвучивания (не блокирует)
+/// Текст может содержать разметку: паузы, выделение, язык и т.д. (см. markup)
+/// Alert прерывает текущую фразу, если она не Alert
+pub fn enqueue(text: &str, priority: SpeechPriority) -> UtteranceHandle {
+    enqueue_with_settings(text, priority, None)
+}
+
+fn enqueue_with_settings(text: &str, priority: SpeechPriority, settings: Option<TtsConfig>) -> UtteranceHandle {
+    WORKER.call_once(|| {
+        std::thread::spawn(run_worker);
+    });
+
+    let handle = QUEUE.lock().unwrap().push_with_settings(text, priority, settings, Instant::now());
+    info!("Utterance #{} queued ({:?}): '{}'", handle.id(), priority, text);
+
+    if priority == SpeechPriority::Alert {
+        let current_is_alert = CURRENT.lock().unwrap().as_ref()
+            .map(|current| current.priority == SpeechPriority::Alert);
+
+        if current_is_alert == Some(false) {
+            interrupt_current();
+        }
+    }
+
+    QUEUE_SIGNAL.notify_one();
+    handle
+}
+
+/// Отмена фразы из очереди или прерывание, если она уже озвучивается
+pub fn cancel(id: u64) -> bool {
+    if QUEUE.lock().unwrap().cancel(id) {
+        info!("Utterance #{} cancelled", id);
+        return true;
+    }
+
+    let is_current = match CURRENT.lock().unwrap().as_ref() {
+        Some(current) if current.handle.id() == id => {
+            current.handle.mark_cancelled();
+            true
+        }
+        _ => false,
+    };
+
+    if is_current {
+        interrupt_current();
+    }
+
+    is_current
+}
+
+/// Текущая и ожидающие фразы
+pub fn queue_status() -> QueueStatus {
+    let now = Instant::now();
+
+    QueueStatus {
+        current: CURRENT.lock().unwrap().as_ref().map(|current| current.info(now)),
+        pending: QUEUE.lock().unwrap().pending(now),
+    }
+}
+
+// Поток озвучивания: фразы синтезируются и воспроизводятся по одной
+fn run_worker() {
+    let _ = WORKER_THREAD.set(std::thread::current().id());
+
+    loop {
+        let utterance = {
+            let mut queue = QUEUE.lock().unwrap();
+            loop {
+                if let Some(utterance) = queue.pop_next(Instant::now()) {
+                    break utterance;
+                }
+                queue = QUEUE_SIGNAL.wait(queue).unwrap();
+            }
+        };
+
+        let text = utterance.text.clone();
+        let handle = utterance.handle.clone();
+        let settings = utterance.settings.clone();
+
+        // поколение фиксируется до публикации фразы, чтобы прерывание не было пропущено
+        let generation = GENERATION.load(Ordering::SeqCst);
+        *CURRENT.lock().unwrap() = Some(utterance);
+
+        let result = if handle.is_cancelled() {
+            Ok(())
+        } else {
+            info!("Speaking: '{}'", text);
+            synthesize_and_play(&text, settings.as_ref(), generation)
+        };
+
+        CURRENT.lock().unwrap().take();
+
+        if let Err(e) = &result {
+            warn!("Failed to speak utterance #{}: {}", handle.id(), e);
+        }
+        handle.finish(result.map_err(|e| e.to_string()));
+    }
+}
+
+/// Прерывание текущей фразы (очередь не затрагивается)
+fn interrupt_current() -> bool {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+
+    let process_killed = WORKER_THREAD.get().is_some_and(|thread| process::kill_spawned_by(*thread));
+    let playback_stopped = audio::rodio::stop_speech();
+
+    let interrupted_text = CURRENT.lock().unwrap().as_ref().map(|current| current.text.clone());
+
+    match interrupted_text {
+        Some(text) => {
+            info!("TTS interrupted (process killed: {}, playback stopped: {})", process_killed, playback_stopped);
+            events::emit(events::EventTypes::SpeechInterrupted, &text);
+            true
+        }
+        None => false,
+    }
+}
+
+fn synthesize_and_play(text: &str, settings: Option<&TtsConfig>, generation: u64) -> JarvisResult<()> {
+    // синтез идет на копии менеджера: блокировка не удерживается на время сетевого запроса или процесса
+    let synthesized = current_manager().and_then(|manager| match settings {
+        // разовые настройки не попадают в кэш
+        Some(settings) => manager.synthesize_with(text, settings)
+            .map(|buffer| (buffer, settings.volume)),
+        None => manager.synthesize_cached(text)
+            .map(|buffer| (buffer, manager.settings.volume)),
+    });
+
+    // синтез мог завершиться ошибкой из-за прерывания
+    if is_interrupted(generation) {
+        info!("Speech interrupted before playback: '{}'", text);
+        return Ok(());
+    }
+
+    let (buffer, volume) = synthesized?;
+    play_buffer(buffer, volume)
+}
+
+fn is_interrupted(generation: u64) -> bool {
+    GENERATION.load(Ordering::SeqCst) != generation
+}
+
+/// Воспроизведение синтезированного аудио через общий плеер
+pub fn play_buffer(buffer: AudioBuffer, volume: f32) -> JarvisResult<()> {
+    let result = match buffer {
+        AudioBuffer::Pcm { samples, sample_rate, channels } => {
+            audio::rodio::play_speech_samples(samples, sample_rate, channels, volume)
+        }
+        AudioBuffer::Encoded(data) => audio::rodio::play_speech_encoded(data, volume),
+        AudioBuffer::Stream(stream) => audio::rodio::play_speech(StreamSource::new(stream), volume),
+    };
+
+    result.map_err(|e| JarvisError::AudioError(AudioError::PlaybackFailed(e)))
+}
+
+/// Выполнение блокирующего HTTP запроса в отдельном потоке
+/// (reqwest::blocking нельзя использовать внутри async runtime, откуда вызывается TTS)
+pub(crate) fn run_blocking<T, F>(f: F) -> JarvisResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> JarvisResult<T> + Send + 'static,
+{
+    std::thread::spawn(f)
+        .join()
+        .map_err(|_| JarvisError::AudioError(AudioError::PlaybackFailed(
+            "TTS request thread panicked".to_string()
+        )))?
+}
+
+/// Остановка речи: очередь очищается, текущая фраза прерывается
+/// Прерывает процесс синтеза, сетевой ответ отбрасывается, sink речи останавливается
+pub fn stop() -> JarvisResult<()> {
+    let dropped = QUEUE.lock().unwrap().clear();
+
+    if !interrupt_current() {
+        info!("TTS stopped");
+    }
+
+    if dropped > 0 {
+        info!("Dropped {} queued utterances", dropped);
+    }
+
+    Ok(())
+}
+
+/// Проверка, говорит ли TTS в данный момент
+pub fn is_speaking() -> bool {
+    CURRENT.lock().map(|current| current.is_some()).unwrap_or(false)
+}
+
+/// Инициализирована ли TTS система
+pub fn is_initialized() -> bool {
+    lock_manager().map(|manager| manager.is_some()).unwrap_or(false)
+}
+
+/// Получение доступных голосов
+pub fn get_available_voices() -> JarvisResult<Vec<String>> {
+    current_manager()?.backend.voices()
+}
+
+/// Текущие настройки TTS
+pub fn current_settings() -> TtsConfig {
+    lock_manager().ok()
+        .and_then(|manager| manager.as_ref().map(|manager| manager.settings.clone()))
+        .or_else(|| crate::db::get_current_settings().map(|settings| settings.tts_config))
+        .unwrap_or_default()
+}
+
+/// Обновление настроек TTS: сохранение в Settings и применение без перезапуска
+/// Движок пересоздается, если изменились параметры, влияющие на его выбор
+pub fn update_settings(new_settings: TtsConfig) -> JarvisResult<()> {
+    let persisted = new_settings.clone();
+    crate::db::update_settings(move |settings| settings.tts_config = persisted)?;
+
+    let mut manager = lock_manager()?;
+    let rebuild = match manager.as_ref() {
+        Some(current) => requires_rebuild(&current.settings, &new_settings),
+        None => true,
+    };
+
+    if rebuild {
+        *manager = Some(TtsManager::new(new_settings)?);
+    } else if let Some(current) = manager.as_mut() {
+        current.settings = new_settings;
+    }
+
+    info!("TTS settings updated");
+    Ok(())
+}
+
+// смена движка, его параметров или размера кэша требует пересоздания менеджера
+fn requires_rebuild(current: &TtsConfig, new: &TtsConfig) -> bool {
+    current.engine != new.engine
+        || current.cache_size_mb != new.cache_size_mb
+        || current.elevenlabs != new.elevenlabs
+}
+
+/// Graceful shutdown TTS
+pub fn shutdown() -> JarvisResult<()> {
+    // Останавливаем текущий синтез и очищаем очередь
+    QUEUE.lock().unwrap().clear();
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    process::kill_all();
+    audio::rodio::stop_speech();
+
+    info!("TTS system shutdown completed");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requires_rebuild() {
+        let current = TtsConfig::default();
+
+        // голос, скорость и громкость применяются без пересоздания движка
+        let voice_only = TtsConfig { voice_id: "nova".to_string(), speed: 1.3, volume: 0.5, ..current.clone() };
+        assert!(!requires_rebuild(&current, &voice_only));
+
+        assert!(requires_rebuild(&current, &TtsConfig { engine: TtsEngine::OpenAI, ..current.clone() }));
+        assert!(requires_rebuild(&current, &TtsConfig { cache_size_mb: 0, ..current.clone() }));
+
+        let mut elevenlabs = current.clone();
+        elevenlabs.elevenlabs.stability = 0.9;
+        assert!(requires_rebuild(&current, &elevenlabs));
+    }
+}
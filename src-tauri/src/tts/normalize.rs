@@ -0,0 +1,1283 @@
+// app/src/tts/normalize.rs - Нормализация русского текста перед синтезом
+// Числа, время, даты, суммы, проценты, единицы измерения и сокращения раскрываются в слова
+// с согласованием рода и падежа: espeak и Piper плохо читают цифры и сокращения
+
+use super::markup::Segment;
+
+/// Род существительного, с которым согласуется число
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gender {
+    Masculine,
+    Feminine,
+    Neuter,
+}
+
+/// Падеж (порядок совпадает с порядком форм в таблицах)
+/// Винительный для неодушевленных совпадает с именительным, кроме "одну", "тысячу"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Case {
+    Nominative,
+    Genitive,
+    Dative,
+    Accusative,
+    Instrumental,
+    Prepositional,
+}
+
+// формы слова по падежам
+type Forms = [&'static str; 6];
+
+// числа длиннее (без разделителей разрядов) читаются по цифрам: телефоны, коды
+const MAX_NUMBER_DIGITS: usize = 9;
+// до триллиона (не включительно)
+const NUMBER_LIMIT: u64 = 1_000_000_000_000;
+
+const SPACES: [char; 3] = [' ', '\u{a0}', '\u{202f}'];
+
+const ZERO: Forms = ["ноль", "нуля", "нулю", "ноль", "нулём", "нуле"];
+const ONE_MASCULINE: Forms = ["один", "одного", "одному", "один", "одним", "одном"];
+const ONE_FEMININE: Forms = ["одна", "одной", "одной", "одну", "одной", "одной"];
+const ONE_NEUTER: Forms = ["одно", "одного", "одному", "одно", "одним", "одном"];
+const TWO: Forms = ["два", "двух", "двум", "два", "двумя", "двух"];
+
+// 3..9
+const UNITS: [Forms; 7] = [
+    ["три", "трёх", "трём", "три", "тремя", "трёх"],
+    ["четыре", "четырёх", "четырём", "четыре", "четырьмя", "четырёх"],
+    ["пять", "пяти", "пяти", "пять", "пятью", "пяти"],
+    ["шесть", "шести", "шести", "шесть", "шестью", "шести"],
+    ["семь", "семи", "семи", "семь", "семью", "семи"],
+    ["восемь", "восьми", "восьми", "восемь", "восемью", "восьми"],
+    ["девять", "девяти", "девяти", "девять", "девятью", "девяти"],
+];
+
+// 10..19
+const TEENS: [Forms; 10] = [
+    ["десять", "десяти", "десяти", "десять", "десятью", "десяти"],
+    ["одиннадцать", "одиннадцати", "одиннадцати", "одиннадцать", "одиннадцатью", "одиннадцати"],
+    ["двенадцать", "двенадцати", "двенадцати", "двенадцать", "двенадцатью", "двенадцати"],
+    ["тринадцать", "тринадцати", "тринадцати", "тринадцать", "тринадцатью", "тринадцати"],
+    ["четырнадцать", "четырнадцати", "четырнадцати", "четырнадцать", "четырнадцатью", "четырнадцати"],
+    ["пятнадцать", "пятнадцати", "пятнадцати", "пятнадцать", "пятнадцатью", "пятнадцати"],
+    ["шестнадцать", "шестнадцати", "шестнадцати", "шестнадцать", "шестнадцатью", "шестнадцати"],
+    ["семнадцать", "семнадцати", "семнадцати", "семнадцать", "семнадцатью", "семнадцати"],
+    ["восемнадцать", "восемнадцати", "восемнадцати", "восемнадцать", "восемнадцатью", "восемнадцати"],
+    ["девятнадцать", "девятнадцати", "девятнадцати", "девятнадцать", "девятнадцатью", "девятнадцати"],
+];
+
+// 20..90
+const TENS: [Forms; 8] = [
+    ["двадцать", "двадцати", "двадцати", "двадцать", "двадцатью", "двадцати"],
+    ["тридцать", "тридцати", "тридцати", "тридцать", "тридцатью", "тридцати"],
+    ["сорок", "сорока", "сорока", "сорок", "сорока", "сорока"],
+    ["пятьдесят", "пятидесяти", "пятидесяти", "пятьдесят", "пятьюдесятью", "пятидесяти"],
+    ["шестьдесят", "шестидесяти", "шестидесяти", "шестьдесят", "шестьюдесятью", "шестидесяти"],
+    ["семьдесят", "семидесяти", "семидесяти", "семьдесят", "семьюдесятью", "семидесяти"],
+    ["восемьдесят", "восьмидесяти", "восьмидесяти", "восемьдесят", "восемьюдесятью", "восьмидесяти"],
+    ["девяносто", "девяноста", "девяноста", "девяносто", "девяноста", "девяноста"],
+];
+
+// 100..900
+const HUNDREDS: [Forms; 9] = [
+    ["сто", "ста", "ста", "сто", "ста", "ста"],
+    ["двести", "двухсот", "двумстам", "двести", "двумястами", "двухстах"],
+    ["триста", "трёхсот", "трёмстам", "триста", "тремястами", "трёхстах"],
+    ["четыреста", "четырёхсот", "четырёмстам", "четыреста", "четырьмястами", "четырёхстах"],
+    ["пятьсот", "пятисот", "пятистам", "пятьсот", "пятьюстами", "пятистах"],
+    ["шестьсот", "шестисот", "шестистам", "шестьсот", "шестьюстами", "шестистах"],
+    ["семьсот", "семисот", "семистам", "семьсот", "семьюстами", "семистах"],
+    ["восемьсот", "восьмисот", "восьмистам", "восемьсот", "восемьюстами", "восьмистах"],
+    ["девятьсот", "девятисот", "девятистам", "девятьсот", "девятьюстами", "девятистах"],
+];
+
+const DIGITS: [&str; 10] = ["ноль", "один", "два", "три", "четыре", "пять", "шесть", "семь", "восемь", "девять"];
+
+const MONTHS: [&str; 12] = [
+    "января", "февраля", "марта", "апреля", "мая", "июня",
+    "июля", "августа", "сентября", "октября", "ноября", "декабря",
+];
+
+/// Существительное: основа и окончания по падежам в единственном и множественном числе
+pub struct Noun {
+    stem: &'static str,
+    gender: Gender,
+    singular: Forms,
+    plural: Forms,
+}
+
+const HARD_SINGULAR: Forms = ["", "а", "у", "", "ом", "е"];
+const HARD_PLURAL: Forms = ["ы", "ов", "ам", "ы", "ами", "ах"];
+const FEMININE_SINGULAR: Forms = ["а", "ы", "е", "у", "ой", "е"];
+const FEMININE_PLURAL: Forms = ["ы", "", "ам", "ы", "ами", "ах"];
+
+impl Noun {
+    // мужской род на твердый согласный: "доллар", "процент"
+    const fn hard(stem: &'static str) -> Self {
+        Self { stem, gender: Gender::Masculine, singular: HARD_SINGULAR, plural: HARD_PLURAL }
+    }
+
+    // женский род на -а: "минута"
+    const fn feminine(stem: &'static str) -> Self {
+        Self { stem, gender: Gender::Feminine, singular: FEMININE_SINGULAR, plural: FEMININE_PLURAL }
+    }
+
+    fn form(&self, case: Case, plural: bool) -> String {
+        let endings = if plural { &self.plural } else { &self.singular };
+        format!("{}{}", self.stem, endings[case as usize])
+    }
+
+    // слово (в нижнем регистре) - одна из форм существительного
+    fn has_form(&self, word: &str) -> bool {
+        word.strip_prefix(self.stem)
+            .is_some_and(|ending| self.singular.contains(&ending) || self.plural.contains(&ending))
+    }
+}
+
+const RUBLE: Noun = Noun {
+    stem: "рубл",
+    gender: Gender::Masculine,
+    singular: ["ь", "я", "ю", "ь", "ём", "е"],
+    plural: ["и", "ей", "ям", "и", "ями", "ях"],
+};
+const KOPECK: Noun = Noun {
+    stem: "копе",
+    gender: Gender::Feminine,
+    singular: ["йка", "йки", "йке", "йку", "йкой", "йке"],
+    plural: ["йки", "ек", "йкам", "йки", "йками", "йках"],
+};
+const EURO: Noun = Noun {
+    stem: "евро",
+    gender: Gender::Masculine,
+    singular: [""; 6],
+    plural: [""; 6],
+};
+const THOUSAND: Noun = Noun {
+    stem: "тысяч",
+    gender: Gender::Feminine,
+    singular: ["а", "и", "е", "у", "ей", "е"],
+    plural: ["и", "", "ам", "и", "ами", "ах"],
+};
+const MILLION: Noun = Noun::hard("миллион");
+const BILLION: Noun = Noun::hard("миллиард");
+
+const DOLLAR: Noun = Noun::hard("доллар");
+const CENT: Noun = Noun::hard("цент");
+const PERCENT: Noun = Noun::hard("процент");
+const HOUR: Noun = Noun::hard("час");
+const MINUTE: Noun = Noun::feminine("минут");
+const SECOND: Noun = Noun::feminine("секунд");
+const DEGREE: Noun = Noun::hard("градус");
+const KILOMETER: Noun = Noun::hard("километр");
+const METER: Noun = Noun::hard("метр");
+const CENTIMETER: Noun = Noun::hard("сантиметр");
+const MILLIMETER: Noun = Noun::hard("миллиметр");
+const KILOGRAM: Noun = Noun::hard("килограмм");
+const GRAM: Noun = Noun::hard("грамм");
+const GIGABYTE: Noun = Noun::hard("гигабайт");
+const MEGABYTE: Noun = Noun::hard("мегабайт");
+const KILOBYTE: Noun = Noun::hard("килобайт");
+
+// существительные, род которых известен: число перед ними согласуется в роде ("две минуты")
+const NOUNS: [&Noun; 22] = [
+    &RUBLE, &KOPECK, &EURO, &THOUSAND, &MILLION, &BILLION, &DOLLAR, &CENT, &PERCENT, &HOUR, &MINUTE,
+    &SECOND, &DEGREE, &KILOMETER, &METER, &CENTIMETER, &MILLIMETER, &KILOGRAM, &GRAM, &GIGABYTE,
+    &MEGABYTE, &KILOBYTE,
+];
+
+const SCALES: [(u64, &Noun); 3] = [(1_000_000_000, &BILLION), (1_000_000, &MILLION), (1_000, &THOUSAND)];
+
+struct Currency {
+    major: &'static Noun,
+    minor: &'static Noun,
+    // сокращение разменной монеты после суммы: "10 руб. 50 коп."
+    minor_name: Option<&'static str>,
+}
+
+const RUBLES: Currency = Currency { major: &RUBLE, minor: &KOPECK, minor_name: Some("коп") };
+const DOLLARS: Currency = Currency { major: &DOLLAR, minor: &CENT, minor_name: None };
+const EUROS: Currency = Currency { major: &EURO, minor: &CENT, minor_name: None };
+
+// обозначения валют после суммы (без учета регистра)
+const CURRENCY_NAMES: [(&str, &Currency); 10] = [
+    ("₽", &RUBLES), ("руб", &RUBLES), ("р", &RUBLES), ("rub", &RUBLES),
+    ("$", &DOLLARS), ("долл", &DOLLARS), ("usd", &DOLLARS),
+    ("€", &EUROS), ("eur", &EUROS), ("евро", &EUROS),
+];
+
+const SCALE_NAMES: [(&str, &Noun); 3] = [("тыс", &THOUSAND), ("млн", &MILLION), ("млрд", &BILLION)];
+
+// единицы измерения: сокращение, существительное, продолжение ("в час")
+const UNIT_NAMES: [(&str, &Noun, &str); 16] = [
+    ("км/ч", &KILOMETER, " в час"),
+    ("км", &KILOMETER, ""),
+    ("м", &METER, ""),
+    ("см", &CENTIMETER, ""),
+    ("мм", &MILLIMETER, ""),
+    ("кг", &KILOGRAM, ""),
+    ("г", &GRAM, ""),
+    ("ч", &HOUR, ""),
+    ("мин", &MINUTE, ""),
+    ("сек", &SECOND, ""),
+    ("°C", &DEGREE, " по Цельсию"),
+    ("°С", &DEGREE, " по Цельсию"),
+    ("°", &DEGREE, ""),
+    ("гб", &GIGABYTE, ""),
+    ("мб", &MEGABYTE, ""),
+    ("кб", &KILOBYTE, ""),
+];
+
+// сокращения в тексте; точка в конце предложения сохраняется
+const ABBREVIATIONS: [(&str, &str); 10] = [
+    ("т.е.", "то есть"),
+    ("т.к.", "так как"),
+    ("т.д.", "так далее"),
+    ("т.п.", "тому подобное"),
+    ("т.н.", "так называемый"),
+    ("напр.", "например"),
+    ("др.", "другие"),
+    ("см.", "смотри"),
+    ("ул.", "улица"),
+    ("№", "номер"),
+];
+
+/// Нормализация текстовых сегментов на русском языке (фрагменты по буквам не меняются)
+pub fn normalize_segments(segments: Vec<Segment>, language: &str) -> Vec<Segment> {
+    segments.into_iter()
+        .map(|segment| match segment {
+            Segment::Text { text, style }
+                if !style.spell && is_russian(style.language.as_deref().unwrap_or(language)) =>
+            {
+                Segment::Text { text: normalize(&text), style }
+            }
+            other => other,
+        })
+        .collect()
+}
+
+fn is_russian(language: &str) -> bool {
+    language.to_lowercase().starts_with("ru")
+}
+
+/// Раскрытие чисел, дат, времени, сумм, процентов, единиц и сокращений в слова
+pub fn normalize(text: &str) -> String {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut output = String::with_capacity(text.len() * 2);
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        let word_start = pos == 0 || !chars[pos - 1].is_alphanumeric();
+
+        if word_start {
+            if let Some((words, end)) = expand(&chars, pos, &output) {
+                fix_preposition(&mut output, &words);
+                output.push_str(&words);
+                // слово не должно слиться со следующим
+                if chars.get(end).is_some_and(|c| c.is_alphanumeric()) {
+                    output.push(' ');
+                }
+                pos = end;
+                continue;
+            }
+        }
+
+        output.push(chars[pos]);
+        pos += 1;
+    }
+
+    output
+}
+
+// "о одном" -> "об одном": перед гласной предлог "о" меняется на "об"
+fn fix_preposition(output: &mut String, words: &str) {
+    let starts_with_vowel = words.chars().next().is_some_and(|c| "аоуэиАОУЭИ".contains(c));
+
+    if starts_with_vowel && preceding_word(output) == "о" {
+        let preposition_end = output.trim_end_matches(SPACES).len();
+        output.insert(preposition_end, 'б');
+    }
+}
+
+fn expand(chars: &[char], pos: usize, output: &str) -> Option<(String, usize)> {
+    let c = chars[pos];
+
+    // знак перед числом: "-5°C", "+3"
+    if matches!(c, '-' | '−' | '+') && chars.get(pos + 1).is_some_and(|c| c.is_ascii_digit())
+        && (pos == 0 || chars[pos - 1].is_whitespace())
+    {
+        let sign = if c == '+' { "плюс" } else { "минус" };
+        return Some((sign.to_string(), pos + 1));
+    }
+
+    // сумма с символом валюты перед числом: "$5", "€ 10,50"
+    if let Some((currency, currency_end)) = currency_at(chars, pos).filter(|_| !c.is_alphabetic()) {
+        let start = skip_spaces(chars, currency_end);
+        let (number, end) = parse_number(chars, start, true)?;
+        return Some(expand_amount(chars, &number, end, Some(currency), preceding_case(output)));
+    }
+
+    if c.is_ascii_digit() {
+        return expand_time(chars, pos, output)
+            .or_else(|| expand_date(chars, pos, output))
+            .or_else(|| expand_sequence(chars, pos))
+            .or_else(|| expand_number(chars, pos, output));
+    }
+
+    ABBREVIATIONS.iter().find_map(|(abbreviation, replacement)| {
+        let end = match_abbreviation(chars, pos, abbreviation)?;
+        let mut words = replacement.to_string();
+        if abbreviation.ends_with('.') && is_sentence_end(chars, end) {
+            words.push('.');
+        }
+        Some((words, end))
+    })
+}
+
+/// Количественное числительное в нужном роде и падеже
+pub fn cardinal(n: u64, gender: Gender, case: Case) -> String {
+    if n == 0 {
+        return ZERO[case as usize].to_string();
+    }
+    if n >= NUMBER_LIMIT {
+        return spell_digits(&n.to_string());
+    }
+
+    let mut words = Vec::new();
+    let mut rest = n;
+
+    for (scale, noun) in SCALES {
+        let count = rest / scale;
+        rest %= scale;
+
+        if count == 0 {
+            continue;
+        }
+
+        // "тысяча", "миллион" без "одна", "один"
+        if count != 1 {
+            push_hundreds(&mut words, count, noun.gender, case);
+        }
+        words.push(agree(noun, count, case));
+    }
+
+    push_hundreds(&mut words, rest, gender, case);
+    words.join(" ")
+}
+
+// число до 999
+fn push_hundreds(words: &mut Vec<String>, n: u64, gender: Gender, case: Case) {
+    let index = case as usize;
+    let (hundreds, tens, units) = ((n / 100) as usize, (n / 10 % 10) as usize, (n % 10) as usize);
+
+    if hundreds > 0 {
+        words.push(HUNDREDS[hundreds - 1][index].to_string());
+    }
+
+    if tens == 1 {
+        words.push(TEENS[units][index].to_string());
+        return;
+    }
+
+    if tens > 1 {
+        words.push(TENS[tens - 2][index].to_string());
+    }
+
+    let unit = match (units, gender) {
+        (0, _) => return,
+        (1, Gender::Masculine) => ONE_MASCULINE[index],
+        (1, Gender::Feminine) => ONE_FEMININE[index],
+        (1, Gender::Neuter) => ONE_NEUTER[index],
+        (2, Gender::Feminine) if matches!(case, Case::Nominative | Case::Accusative) => "две",
+        (2, _) => TWO[index],
+        (unit, _) => UNITS[unit - 3][index],
+    };
+    words.push(unit.to_string());
+}
+
+/// Форма существительного после числа: "1 рубль", "2 рубля", "5 рублей", "до 2 рублей"
+pub fn agree(noun: &Noun, n: u64, case: Case) -> String {
+    let (last, last_two) = (n % 10, n % 100);
+    let nominative = matches!(case, Case::Nominative | Case::Accusative);
+
+    if (11..=14).contains(&last_two) || !(1..=4).contains(&last) {
+        noun.form(if nominative { Case::Genitive } else { case }, true)
+    } else if last == 1 {
+        noun.form(case, false)
+    } else if nominative {
+        noun.form(Case::Genitive, false)
+    } else {
+        noun.form(case, true)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum OrdinalStem {
+    Hard,      // первый
+    Stressed,  // второй
+    Soft,      // третий
+}
+
+// 1..9
+const ORDINAL_UNITS: [(&str, OrdinalStem); 9] = [
+    ("перв", OrdinalStem::Hard),
+    ("втор", OrdinalStem::Stressed),
+    ("трет", OrdinalStem::Soft),
+    ("четвёрт", OrdinalStem::Hard),
+    ("пят", OrdinalStem::Hard),
+    ("шест", OrdinalStem::Stressed),
+    ("седьм", OrdinalStem::Stressed),
+    ("восьм", OrdinalStem::Stressed),
+    ("девят", OrdinalStem::Hard),
+];
+
+// 10..19
+const ORDINAL_TEENS: [&str; 10] = [
+    "десят", "одиннадцат", "двенадцат", "тринадцат", "четырнадцат",
+    "пятнадцат", "шестнадцат", "семнадцат", "восемнадцат", "девятнадцат",
+];
+
+// 20..90
+const ORDINAL_TENS: [(&str, OrdinalStem); 8] = [
+    ("двадцат", OrdinalStem::Hard),
+    ("тридцат", OrdinalStem::Hard),
+    ("сороков", OrdinalStem::Stressed),
+    ("пятидесят", OrdinalStem::Hard),
+    ("шестидесят", OrdinalStem::Hard),
+    ("семидесят", OrdinalStem::Hard),
+    ("восьмидесят", OrdinalStem::Hard),
+    ("девяност", OrdinalStem::Hard),
+];
+
+fn ordinal_ending(stem: OrdinalStem, gender: Gender, case: Case) -> &'static str {
+    const MASCULINE: Forms = ["ый", "ого", "ому", "ый", "ым", "ом"];
+    const NEUTER: Forms = ["ое", "ого", "ому", "ое", "ым", "ом"];
+    const FEMININE: Forms = ["ая", "ой", "ой", "ую", "ой", "ой"];
+    const SOFT_MASCULINE: Forms = ["ий", "ьего", "ьему", "ий", "ьим", "ьем"];
+    const SOFT_NEUTER: Forms = ["ье", "ьего", "ьему", "ье", "ьим", "ьем"];
+    const SOFT_FEMININE: Forms = ["ья", "ьей", "ьей", "ью", "ьей", "ьей"];
+
+    let index = case as usize;
+    match (stem, gender) {
+        (OrdinalStem::Soft, Gender::Masculine) => SOFT_MASCULINE[index],
+        (OrdinalStem::Soft, Gender::Neuter) => SOFT_NEUTER[index],
+        (OrdinalStem::Soft, Gender::Feminine) => SOFT_FEMININE[index],
+        (OrdinalStem::Stressed, Gender::Masculine) if matches!(case, Case::Nominative | Case::Accusative) => "ой",
+        (_, Gender::Masculine) => MASCULINE[index],
+        (_, Gender::Neuter) => NEUTER[index],
+        (_, Gender::Feminine) => FEMININE[index],
+    }
+}
+
+// окончание порядкового числительного во множественном числе: "девяностые", "третьих"
+fn plural_ordinal_ending(stem: OrdinalStem, case: Case) -> &'static str {
+    const PLURAL: Forms = ["ые", "ых", "ым", "ые", "ыми", "ых"];
+    const SOFT_PLURAL: Forms = ["ьи", "ьих", "ьим", "ьи", "ьими", "ьих"];
+
+    match stem {
+        OrdinalStem::Soft => SOFT_PLURAL[case as usize],
+        _ => PLURAL[case as usize],
+    }
+}
+
+/// Порядковое числительное: склоняется только последнее слово ("две тысячи двадцать четвёртого")
+pub fn ordinal(n: u64, gender: Gender, case: Case) -> String {
+    ordinal_with(n, |stem| ordinal_ending(stem, gender, case))
+}
+
+/// Порядковое числительное во множественном числе: "в 1990-х" - "тысяча девятьсот девяностых"
+pub fn ordinal_plural(n: u64, case: Case) -> String {
+    ordinal_with(n, |stem| plural_ordinal_ending(stem, case))
+}
+
+fn ordinal_with(n: u64, ending: impl Fn(OrdinalStem) -> &'static str) -> String {
+    if n == 0 {
+        return format!("нулев{}", ending(OrdinalStem::Stressed));
+    }
+    if n >= NUMBER_LIMIT {
+        return spell_digits(&n.to_string());
+    }
+
+    // круглые тысячи, миллионы: "двухтысячный", "миллионный"
+    for (scale, stem) in [(1_000_000_000, "миллиардн"), (1_000_000, "миллионн"), (1_000, "тысячн")] {
+        if n.is_multiple_of(scale) {
+            let count = n / scale;
+            let prefix = match count {
+                1 => String::new(),
+                count => cardinal(count, Gender::Masculine, Case::Genitive).replace("одного", "одно").replace(' ', ""),
+            };
+            return format!("{}{}{}", prefix, stem, ending(OrdinalStem::Hard));
+        }
+    }
+
+    let last = n % 1000;
+    let tail = if last.is_multiple_of(100) {
+        last
+    } else if last % 100 < 20 || last.is_multiple_of(10) {
+        last % 100
+    } else {
+        last % 10
+    };
+
+    let (stem, kind) = match tail as usize {
+        tail @ 1..=9 => ORDINAL_UNITS[tail - 1],
+        tail @ 10..=19 => (ORDINAL_TEENS[tail - 10], OrdinalStem::Hard),
+        tail @ 20..=99 => ORDINAL_TENS[tail / 10 - 2],
+        100 => ("сот", OrdinalStem::Hard),
+        // "двухсотый" - основа совпадает с родительным падежом
+        tail => (HUNDREDS[tail / 100 - 1][Case::Genitive as usize], OrdinalStem::Hard),
+    };
+    let word = format!("{}{}", stem, ending(kind));
+
+    match n - tail {
+        0 => word,
+        prefix => format!("{} {}", cardinal(prefix, Gender::Masculine, Case::Nominative), word),
+    }
+}
+
+/// Десятичная дробь: "две целых пять десятых"
+pub fn decimal(integer: u64, fraction: &str, case: Case) -> String {
+    let denominator = match fraction.len() {
+        1 => "десят",
+        2 => "сот",
+        3 => "тысячн",
+        _ => return format!("{} запятая {}", cardinal(integer, Gender::Masculine, case), spell_digits(fraction)),
+    };
+    let numerator = fraction.parse::<u64>().unwrap_or(0);
+
+    format!(
+        "{} {} {} {}",
+        cardinal(integer, Gender::Feminine, case),
+        fraction_word("цел", integer, case),
+        cardinal(numerator, Gender::Feminine, case),
+        fraction_word(denominator, numerator, case)
+    )
+}
+
+// "целая"/"целых", "десятая"/"десятых"
+fn fraction_word(stem: &str, n: u64, case: Case) -> String {
+    const SINGULAR: Forms = ["ая", "ой", "ой", "ую", "ой", "ой"];
+    const PLURAL: Forms = ["ых", "ых", "ым", "ых", "ыми", "ых"];
+
+    let singular = n % 10 == 1 && n % 100 != 11;
+    format!("{}{}", stem, if singular { SINGULAR[case as usize] } else { PLURAL[case as usize] })
+}
+
+fn spell_digits(digits: &str) -> String {
+    digits.chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|digit| DIGITS[digit as usize])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Число из текста
+struct Number {
+    digits: String,
+    integer: u64,
+    fraction: Option<String>,
+    // записано с разделителями разрядов ("1 500 000")
+    grouped: bool,
+}
+
+impl Number {
+    fn words(&self, gender: Gender, case: Case) -> String {
+        match &self.fraction {
+            Some(fraction) => decimal(self.integer, fraction, case),
+            None => cardinal(self.integer, gender, case),
+        }
+    }
+
+    /// Число с существительным: "пять километров", "две целых пять десятых километра"
+    fn with_noun(&self, noun: &Noun, case: Case) -> String {
+        match &self.fraction {
+            Some(fraction) => format!("{} {}", decimal(self.integer, fraction, case), noun.form(Case::Genitive, false)),
+            None => format!("{} {}", cardinal(self.integer, noun.gender, case), agree(noun, self.integer, case)),
+        }
+    }
+}
+
+// `currency_prefix` - перед числом стоит символ валюты: "$1,500" записано по-английски, запятая отделяет разряды
+fn parse_number(chars: &[char], pos: usize, currency_prefix: bool) -> Option<(Number, usize)> {
+    let (mut digits, mut end) = read_digits(chars, pos);
+    if digits.is_empty() {
+        return None;
+    }
+
+    // разряды через пробел: "1 500 000"
+    let mut grouped = false;
+    while digits.len() <= 3 || grouped {
+        let is_group = chars.get(end).is_some_and(|c| SPACES.contains(c))
+            && (1..=3).all(|offset| chars.get(end + offset).is_some_and(|c| c.is_ascii_digit()))
+            && !chars.get(end + 4).is_some_and(|c| c.is_ascii_digit());
+
+        if !is_group {
+            break;
+        }
+
+        digits.extend(&chars[end + 1..end + 4]);
+        end += 4;
+        grouped = true;
+    }
+
+    // разряды через запятую: "1,000,000", "1,000.50", "$1,500" (иначе одна группа "1,000" - дробь)
+    if !grouped && digits.len() <= 3 {
+        let mut groups = Vec::new();
+        let mut group_end = end;
+        while chars.get(group_end) == Some(&',')
+            && (1..=3).all(|offset| chars.get(group_end + offset).is_some_and(|c| c.is_ascii_digit()))
+            && !chars.get(group_end + 4).is_some_and(|c| c.is_ascii_digit())
+        {
+            groups.push(group_end + 1);
+            group_end += 4;
+        }
+
+        let fraction_follows = chars.get(group_end) == Some(&'.')
+            && chars.get(group_end + 1).is_some_and(|c| c.is_ascii_digit());
+
+        if groups.len() >= 2 || (groups.len() == 1 && (currency_prefix || fraction_follows)) {
+            for start in groups {
+                digits.extend(&chars[start..start + 3]);
+            }
+            end = group_end;
+            grouped = true;
+        }
+    }
+
+    // дробная часть; "1.2.3" и "1,2,3" - не дробь
+    let mut fraction = None;
+    if chars.get(end).is_some_and(|c| matches!(c, ',' | '.')) {
+        let (fraction_digits, fraction_end) = read_digits(chars, end + 1);
+        let continues = chars.get(fraction_end).is_some_and(|c| matches!(c, ',' | '.'))
+            && chars.get(fraction_end + 1).is_some_and(|c| c.is_ascii_digit());
+
+        if !fraction_digits.is_empty() && !continues {
+            fraction = Some(fraction_digits);
+            end = fraction_end;
+        }
+    }
+
+    let integer = digits.parse::<u64>().unwrap_or(u64::MAX);
+    Some((Number { digits, integer, fraction, grouped }, end))
+}
+
+fn read_digits(chars: &[char], pos: usize) -> (String, usize) {
+    let mut end = pos;
+    while chars.get(end).is_some_and(|c| c.is_ascii_digit()) {
+        end += 1;
+    }
+    (chars[pos..end].iter().collect(), end)
+}
+
+// целое из ровно `len` цифр, за которым нет других цифр
+fn read_exact(chars: &[char], pos: usize, min_len: usize, max_len: usize) -> Option<(u64, usize)> {
+    let (digits, end) = read_digits(chars, pos);
+    if !(min_len..=max_len).contains(&digits.len()) {
+        return None;
+    }
+    Some((digits.parse().ok()?, end))
+}
+
+// "14:30", "12:30:45"
+fn expand_time(chars: &[char], pos: usize, output: &str) -> Option<(String, usize)> {
+    let (hours, colon) = read_exact(chars, pos, 1, 2)?;
+    if chars.get(colon) != Some(&':') {
+        return None;
+    }
+    let (minutes, mut end) = read_exact(chars, colon + 1, 2, 2)?;
+
+    let mut seconds = 0;
+    if chars.get(end) == Some(&':') {
+        (seconds, end) = read_exact(chars, end + 1, 2, 2)?;
+    }
+
+    if hours > 24 || minutes > 59 || seconds > 59 || chars.get(end) == Some(&':') {
+        return None;
+    }
+
+    let case = preceding_case(output);
+    let mut words = format!("{} {}", cardinal(hours, Gender::Masculine, case), agree(&HOUR, hours, case));
+    for (value, noun) in [(minutes, &MINUTE), (seconds, &SECOND)] {
+        if value > 0 {
+            words.push_str(&format!(" {} {}", cardinal(value, noun.gender, case), agree(noun, value, case)));
+        }
+    }
+
+    Some((words, end))
+}
+
+// "1.2.3" (версии, IP-адреса) - "один точка два точка три", "1,2,3" - перечисление
+fn expand_sequence(chars: &[char], pos: usize) -> Option<(String, usize)> {
+    let (first, mut end) = read_digits(chars, pos);
+    let separator = *chars.get(end).filter(|c| matches!(c, '.' | ','))?;
+
+    let mut groups = vec![first];
+    while chars.get(end) == Some(&separator) {
+        let (group, group_end) = read_digits(chars, end + 1);
+        if group.is_empty() {
+            break;
+        }
+        groups.push(group);
+        end = group_end;
+    }
+
+    // "1,000,000" - разряды, а не перечисление
+    let thousands = separator == ',' && groups[0].len() <= 3 && groups[1..].iter().all(|group| group.len() == 3);
+    if groups.len() < 3 || thousands || chars.get(end).is_some_and(|c| c.is_alphabetic()) {
+        return None;
+    }
+
+    let words = groups.iter()
+        .map(|group| match group.parse::<u64>() {
+            Ok(value) if group.len() <= MAX_NUMBER_DIGITS && (group.len() == 1 || !group.starts_with('0')) => {
+                cardinal(value, Gender::Masculine, Case::Nominative)
+            }
+            _ => spell_digits(group),
+        })
+        .collect::<Vec<_>>();
+
+    let joiner = if separator == '.' { " точка " } else { ", " };
+    Some((words.join(joiner), end))
+}
+
+// "05.03.2024"
+fn expand_date(chars: &[char], pos: usize, output: &str) -> Option<(String, usize)> {
+    let (day, dot) = read_exact(chars, pos, 1, 2)?;
+    if chars.get(dot) != Some(&'.') {
+        return None;
+    }
+    let (month, dot) = read_exact(chars, dot + 1, 1, 2)?;
+    if chars.get(dot) != Some(&'.') {
+        return None;
+    }
+    let (year, end) = read_exact(chars, dot + 1, 4, 4)?;
+
+    if !(1..=31).contains(&day) || !(1..=12).contains(&month) {
+        return None;
+    }
+
+    // "года" или "г." после даты уже произнесены
+    let end = match_year_word(chars, skip_spaces(chars, end)).unwrap_or(end);
+    Some((date_words(day, month as usize, Some(year), preceding_case(output)), end))
+}
+
+fn date_words(day: u64, month: usize, year: Option<u64>, case: Case) -> String {
+    let mut words = format!("{} {}", ordinal(day, Gender::Neuter, case), MONTHS[month - 1]);
+    if let Some(year) = year {
+        words.push_str(&format!(" {} года", ordinal(year, Gender::Masculine, Case::Genitive)));
+    }
+    words
+}
+
+// "года", "г." после даты
+fn match_year_word(chars: &[char], pos: usize) -> Option<usize> {
+    if let Some(end) = match_word(chars, pos, "года") {
+        return Some(end);
+    }
+    match_word(chars, pos, "г").map(|end| abbreviation_dot(chars, end))
+}
+
+fn expand_number(chars: &[char], pos: usize, output: &str) -> Option<(String, usize)> {
+    let (number, end) = parse_number(chars, pos, false)?;
+    let case = preceding_case(output);
+
+    // телефоны, коды, номера с ведущим нулем - по цифрам
+    let is_code = number.digits.len() > 1 && number.digits.starts_with('0');
+    if number.fraction.is_none() && (is_code || (!number.grouped && number.digits.len() > MAX_NUMBER_DIGITS)) {
+        return Some((spell_digits(&number.digits), end));
+    }
+    // слишком большие числа - по цифрам целиком, вместе с разрядами
+    if number.integer >= NUMBER_LIMIT {
+        let mut words = spell_digits(&number.digits);
+        if let Some(fraction) = &number.fraction {
+            words.push_str(&format!(" запятая {}", spell_digits(fraction)));
+        }
+
+        return match currency_at(chars, skip_spaces(chars, end)) {
+            Some((currency, currency_end)) => {
+                Some((format!("{} {}", words, currency.major.form(Case::Genitive, true)), currency_end))
+            }
+            None => Some((words, end)),
+        };
+    }
+
+    if number.fraction.is_none() {
+        if let Some(result) = expand_ordinal_suffix(chars, &number, end) {
+            return Some(result);
+        }
+    }
+
+    let next = skip_spaces(chars, end);
+
+    // проценты
+    if chars.get(next) == Some(&'%') {
+        return Some((number.with_noun(&PERCENT, case), next + 1));
+    }
+
+    // суммы и крупные числа: "100 руб.", "3 млн руб."
+    if currency_at(chars, next).is_some() || scale_at(chars, next).is_some() {
+        return Some(expand_amount(chars, &number, end, None, case));
+    }
+
+    if number.fraction.is_none() {
+        // "5 мая 2024 года"
+        if let Some(result) = expand_day_month(chars, &number, next, case) {
+            return Some(result);
+        }
+
+        // "в 2024 году", "2024 г."
+        if let Some(result) = expand_year(chars, &number, end, next, output) {
+            return Some(result);
+        }
+    }
+
+    // единицы измерения
+    if let Some((noun, suffix, unit_end)) = unit_at(chars, next) {
+        return Some((format!("{}{}", number.with_noun(noun, case), suffix), unit_end));
+    }
+
+    // число, слитное с буквами ("5G", "2x") или с неизвестным окончанием ("5-ти"), не трогаем
+    let suffix_start = if chars.get(end) == Some(&'-') { end + 1 } else { end };
+    if chars.get(suffix_start).is_some_and(|c| c.is_alphabetic()) {
+        return None;
+    }
+
+    // род берется из следующего существительного, если оно известно: "две минуты", "двадцать одна секунда"
+    let gender = noun_at(chars, next).map_or(Gender::Masculine, |noun| noun.gender);
+    Some((number.words(gender, case), end))
+}
+
+fn noun_at(chars: &[char], pos: usize) -> Option<&'static Noun> {
+    let word = chars[pos.min(chars.len())..].iter()
+        .take_while(|c| c.is_alphabetic())
+        .collect::<String>()
+        .to_lowercase();
+
+    NOUNS.iter().copied().find(|noun| noun.has_form(&word))
+}
+
+// "5-й", "2-го", "3-я"
+fn expand_ordinal_suffix(chars: &[char], number: &Number, end: usize) -> Option<(String, usize)> {
+    if chars.get(end) != Some(&'-') {
+        return None;
+    }
+
+    let mut suffix_end = end + 1;
+    while chars.get(suffix_end).is_some_and(|c| c.is_alphabetic()) {
+        suffix_end += 1;
+    }
+    let suffix = chars[end + 1..suffix_end].iter().collect::<String>().to_lowercase();
+
+    // множественное число: "1990-х", "90-ые"
+    let plural_case = match suffix.as_str() {
+        "х" | "ых" | "их" => Some(Case::Genitive),
+        "ые" | "ие" => Some(Case::Nominative),
+        "ми" | "ыми" | "ими" => Some(Case::Instrumental),
+        _ => None,
+    };
+    if let Some(case) = plural_case {
+        return Some((ordinal_plural(number.integer, case), suffix_end));
+    }
+
+    let (gender, case) = match suffix.as_str() {
+        "й" | "ый" | "ой" | "ий" => (Gender::Masculine, Case::Nominative),
+        "го" | "ого" | "его" => (Gender::Masculine, Case::Genitive),
+        "му" | "ому" | "ему" => (Gender::Masculine, Case::Dative),
+        "м" | "ом" | "ем" => (Gender::Masculine, Case::Prepositional),
+        "я" | "ая" | "ья" => (Gender::Feminine, Case::Nominative),
+        "ю" | "ую" | "ью" => (Gender::Feminine, Case::Accusative),
+        "е" | "ое" | "ье" => (Gender::Neuter, Case::Nominative),
+        _ => return None,
+    };
+
+    Some((ordinal(number.integer, gender, case), suffix_end))
+}
+
+// сумма: число, необязательный разряд ("млн") и валюта после числа (если не указана перед ним)
+fn expand_amount(chars: &[char], number: &Number, end: usize, currency: Option<&Currency>, case: Case) -> (String, usize) {
+    let mut end = end;
+    let mut scale = None;
+
+    if let Some((noun, scale_end)) = scale_at(chars, skip_spaces(chars, end)) {
+        scale = Some(noun);
+        end = scale_end;
+    }
+
+    let currency = match currency {
+        Some(currency) => Some(currency),
+        None => currency_at(chars, skip_spaces(chars, end)).map(|(currency, currency_end)| {
+            end = currency_end;
+            currency
+        }),
+    };
+
+    let words = match (scale, currency) {
+        // "три миллиона рублей"
+        (Some(scale), Some(currency)) => format!("{} {}", number.with_noun(scale, case), currency.major.form(Case::Genitive, true)),
+        (Some(scale), None) => number.with_noun(scale, case),
+        (None, Some(currency)) => {
+            let mut words = amount_words(number, currency, case);
+
+            // "10 руб. 50 коп."
+            if let Some((minor, minor_end)) = minor_amount_at(chars, skip_spaces(chars, end), currency)
+                .filter(|_| number.fraction.is_none())
+            {
+                let noun = currency.minor;
+                if minor > 0 {
+                    words.push_str(&format!(" {} {}", cardinal(minor, noun.gender, case), agree(noun, minor, case)));
+                }
+                end = minor_end;
+            }
+
+            words
+        }
+        (None, None) => number.words(Gender::Masculine, case),
+    };
+
+    (words, end)
+}
+
+// количество разменной монеты с сокращением: "50 коп."
+fn minor_amount_at(chars: &[char], pos: usize, currency: &Currency) -> Option<(u64, usize)> {
+    let (minor, end) = read_exact(chars, pos, 1, 2)?;
+    let name_end = match_word(chars, skip_spaces(chars, end), currency.minor_name?)?;
+    Some((minor, abbreviation_dot(chars, name_end)))
+}
+
+// "тысяча пятьсот рублей пятьдесят копеек"
+fn amount_words(number: &Number, currency: &Currency, case: Case) -> String {
+    let minor = match number.fraction.as_deref() {
+        None => None,
+        Some(fraction) if fraction.len() <= 2 => fraction.parse::<u64>().ok().map(|minor| {
+            if fraction.len() == 1 { minor * 10 } else { minor }
+        }),
+        // "1,125 $" - обычная дробь
+        Some(_) => return number.with_noun(currency.major, case),
+    };
+
+    let major = currency.major;
+    let mut words = format!("{} {}", cardinal(number.integer, major.gender, case), agree(major, number.integer, case));
+
+    if let Some(minor) = minor.filter(|minor| *minor > 0) {
+        let noun = currency.minor;
+        words.push_str(&format!(" {} {}", cardinal(minor, noun.gender, case), agree(noun, minor, case)));
+    }
+
+    words
+}
+
+// "5 мая", "5 мая 2024", "5 мая 2024 г."
+fn expand_day_month(chars: &[char], number: &Number, pos: usize, case: Case) -> Option<(String, usize)> {
+    if !(1..=31).contains(&number.integer) {
+        return None;
+    }
+
+    let (month, mut end) = MONTHS.iter().enumerate()
+        .find_map(|(index, month)| match_word(chars, pos, month).map(|end| (index + 1, end)))?;
+
+    let mut year = None;
+    if let Some((value, year_end)) = read_exact(chars, skip_spaces(chars, end), 4, 4) {
+        year = Some(value);
+        end = match_year_word(chars, skip_spaces(chars, year_end)).unwrap_or(year_end);
+    }
+
+    Some((date_words(number.integer, month, year, case), end))
+}
+
+// год с существительным: падеж берется из формы слова "год" или из предлога перед "г."
+fn expand_year(chars: &[char], number: &Number, end: usize, next: usize, output: &str) -> Option<(String, usize)> {
+    if !(1000..=2100).contains(&number.integer) {
+        return None;
+    }
+
+    let case = preceding_case(output);
+
+    let noun_case = [("годом", Case::Instrumental), ("году", Case::Prepositional), ("года", Case::Genitive), ("год", Case::Nominative)]
+        .into_iter()
+        .find(|(word, _)| match_word(chars, next, word).is_some())
+        .map(|(_, noun_case)| match noun_case {
+            Case::Prepositional if case == Case::Dative => Case::Dative,
+            noun_case => noun_case,
+        });
+
+    if let Some(noun_case) = noun_case {
+        return Some((ordinal(number.integer, Gender::Masculine, noun_case), end));
+    }
+
+    // "г." заменяется формой слова "год"
+    let abbreviation_end = match_word(chars, next, "г")?;
+    let (case, noun) = match (preceding_word(output).as_str(), case) {
+        ("в" | "во", _) | (_, Case::Prepositional) => (Case::Prepositional, "году"),
+        (_, Case::Genitive) => (Case::Genitive, "года"),
+        (_, Case::Dative) => (Case::Dative, "году"),
+        (_, Case::Instrumental) => (Case::Instrumental, "годом"),
+        _ => (Case::Nominative, "год"),
+    };
+
+    Some((format!("{} {}", ordinal(number.integer, Gender::Masculine, case), noun), abbreviation_dot(chars, abbreviation_end)))
+}
+
+fn currency_at(chars: &[char], pos: usize) -> Option<(&'static Currency, usize)> {
+    CURRENCY_NAMES.iter().find_map(|(name, currency)| {
+        let end = match_word(chars, pos, name)?;
+        Some((*currency, abbreviation_dot(chars, end)))
+    })
+}
+
+fn scale_at(chars: &[char], pos: usize) -> Option<(&'static Noun, usize)> {
+    SCALE_NAMES.iter().find_map(|(name, noun)| {
+        let end = match_word(chars, pos, name)?;
+        Some((*noun, abbreviation_dot(chars, end)))
+    })
+}
+
+fn unit_at(chars: &[char], pos: usize) -> Option<(&'static Noun, &'static str, usize)> {
+    UNIT_NAMES.iter().find_map(|(name, noun, suffix)| {
+        let end = match_word(chars, pos, name)?;
+        Some((*noun, *suffix, abbreviation_dot(chars, end)))
+    })
+}
+
+// совпадение слова без учета регистра; после буквенного слова не должно быть буквы
+fn match_word(chars: &[char], pos: usize, word: &str) -> Option<usize> {
+    let mut end = pos;
+    for expected in word.chars() {
+        let c = chars.get(end)?;
+        if c.to_lowercase().ne(expected.to_lowercase()) {
+            return None;
+        }
+        end += 1;
+    }
+
+    let ends_with_letter = word.chars().last().is_some_and(|c| c.is_alphabetic());
+    if ends_with_letter && chars.get(end).is_some_and(|c| c.is_alphabetic()) {
+        return None;
+    }
+
+    Some(end)
+}
+
+// сокращение с точками; пробел после внутренней точки необязателен ("т. е.")
+fn match_abbreviation(chars: &[char], pos: usize, abbreviation: &str) -> Option<usize> {
+    let pattern = abbreviation.chars().collect::<Vec<_>>();
+    let mut end = pos;
+
+    for (index, expected) in pattern.iter().enumerate() {
+        let c = chars.get(end)?;
+        if c.to_lowercase().ne(expected.to_lowercase()) {
+            return None;
+        }
+        end += 1;
+
+        if *expected == '.' && index + 1 < pattern.len() {
+            end = skip_spaces(chars, end);
+        }
+    }
+
+    let ends_with_letter = pattern.last().is_some_and(|c| c.is_alphabetic());
+    if ends_with_letter && chars.get(end).is_some_and(|c| c.is_alphabetic()) {
+        return None;
+    }
+
+    Some(end)
+}
+
+// точка после сокращения поглощается, если она не завершает предложение
+fn abbreviation_dot(chars: &[char], pos: usize) -> usize {
+    if chars.get(pos) == Some(&'.') && !is_sentence_end(chars, pos + 1) {
+        pos + 1
+    } else {
+        pos
+    }
+}
+
+fn is_sentence_end(chars: &[char], pos: usize) -> bool {
+    let next = skip_spaces(chars, pos);
+    chars.get(next).is_none_or(|c| c.is_uppercase() || *c == '\n')
+}
+
+fn skip_spaces(chars: &[char], pos: usize) -> usize {
+    let mut end = pos;
+    while chars.get(end).is_some_and(|c| SPACES.contains(c)) {
+        end += 1;
+    }
+    end
+}
+
+// последнее слово уже обработанного текста
+fn preceding_word(output: &str) -> String {
+    let word = output.trim_end_matches(SPACES)
+        .chars()
+        .rev()
+        .take_while(|c| c.is_alphabetic())
+        .collect::<Vec<_>>();
+
+    word.into_iter().rev().collect::<String>().to_lowercase()
+}
+
+// падеж числа по предлогу перед ним
+fn preceding_case(output: &str) -> Case {
+    match preceding_word(output).as_str() {
+        "до" | "от" | "из" | "без" | "около" | "после" | "с" | "со" | "для" | "у" | "кроме" | "вместо"
+        | "более" | "менее" | "больше" | "меньше" | "свыше" | "среди" | "против" => Case::Genitive,
+        "к" | "ко" | "согласно" | "благодаря" => Case::Dative,
+        "в" | "во" | "на" | "за" | "через" | "про" => Case::Accusative,
+        "над" | "под" | "перед" | "между" => Case::Instrumental,
+        "о" | "об" | "при" => Case::Prepositional,
+        _ => Case::Nominative,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cardinal_gender_and_case() {
+        assert_eq!(cardinal(0, Gender::Masculine, Case::Nominative), "ноль");
+        assert_eq!(cardinal(21, Gender::Feminine, Case::Nominative), "двадцать одна");
+        assert_eq!(cardinal(2, Gender::Feminine, Case::Nominative), "две");
+        assert_eq!(cardinal(2, Gender::Neuter, Case::Nominative), "два");
+        assert_eq!(cardinal(345, Gender::Masculine, Case::Instrumental), "тремястами сорока пятью");
+        assert_eq!(cardinal(1_234_567, Gender::Masculine, Case::Nominative),
+                   "миллион двести тридцать четыре тысячи пятьсот шестьдесят семь");
+        assert_eq!(cardinal(2_005_000, Gender::Masculine, Case::Genitive), "двух миллионов пяти тысяч");
+        assert_eq!(cardinal(1_000, Gender::Feminine, Case::Accusative), "тысячу");
+    }
+
+    #[test]
+    fn test_numbers_in_text() {
+        assert_eq!(normalize("У меня 5 яблок"), "У меня пять яблок");
+        assert_eq!(normalize("осталось до 3, потом к 2 и о 41"), "осталось до трёх, потом к двум и о сорока одном");
+        assert_eq!(normalize("Население 1 500 000 человек"), "Население миллион пятьсот тысяч человек");
+        assert_eq!(normalize("Число 3.14 и 0,5"), "Число три целых четырнадцать сотых и ноль целых пять десятых");
+        assert_eq!(normalize("Температура -5°C"), "Температура минус пять градусов по Цельсию");
+        assert_eq!(normalize("Звоните 89161234567"), "Звоните восемь девять один шесть один два три четыре пять шесть семь");
+        // числа внутри слов не меняются
+        assert_eq!(normalize("mp3 и 5G"), "mp3 и 5G");
+    }
+
+    #[test]
+    fn test_number_agrees_with_known_noun() {
+        assert_eq!(normalize("2 минуты"), "две минуты");
+        assert_eq!(normalize("21 минута"), "двадцать одна минута");
+        assert_eq!(normalize("2 тысячи"), "две тысячи");
+        assert_eq!(normalize("о 1 рубле"), "об одном рубле");
+        assert_eq!(normalize("О 11 копейках"), "Об одиннадцати копейках");
+    }
+
+    #[test]
+    fn test_number_sequences() {
+        assert_eq!(normalize("версия 1.2.3"), "версия один точка два точка три");
+        assert_eq!(normalize("1,2,3"), "один, два, три");
+        assert_eq!(normalize("адрес 192.168.0.10"), "адрес сто девяносто два точка сто шестьдесят восемь точка ноль точка десять");
+        // дата и разряды - не перечисление
+        assert_eq!(normalize("05.03.2024"), "пятое марта две тысячи двадцать четвёртого года");
+        assert_eq!(normalize("1,000,000"), "миллион");
+    }
+
+    #[test]
+    fn test_large_and_grouped_numbers() {
+        // от триллиона - по цифрам целиком, без разбиения на группы
+        assert_eq!(normalize("1 000 000 000 000 руб."), format!("один {}рублей.", "ноль ".repeat(12)));
+        assert_eq!(normalize("2 500 000 000 000 человек"), format!("два пять {}человек", "ноль ".repeat(11)));
+        assert_eq!(normalize("1,000,000"), "миллион");
+        assert_eq!(normalize("Население 2,500,000 человек"), "Население два миллиона пятьсот тысяч человек");
+        assert_eq!(normalize("1,000,000.5"), "миллион целых пять десятых");
+    }
+
+    #[test]
+    fn test_plural_ordinals() {
+        assert_eq!(normalize("в 1990-х"), "в тысяча девятьсот девяностых");
+        assert_eq!(normalize("в 1990-х годах"), "в тысяча девятьсот девяностых годах");
+        assert_eq!(normalize("90-ые"), "девяностые");
+        assert_eq!(ordinal_plural(3, Case::Genitive), "третьих");
+        assert_eq!(ordinal_plural(2000, Case::Instrumental), "двухтысячными");
+        // неизвестное окончание оставляется как есть
+        assert_eq!(normalize("5-ти"), "5-ти");
+    }
+
+    #[test]
+    fn test_units_agreement() {
+        assert_eq!(normalize("1 км"), "один километр");
+        assert_eq!(normalize("22 кг"), "двадцать два килограмма");
+        assert_eq!(normalize("до 5 км"), "до пяти километров");
+        assert_eq!(normalize("500 г муки"), "пятьсот граммов муки");
+        assert_eq!(normalize("2,5 км"), "две целых пять десятых километра");
+        assert_eq!(normalize("со скоростью 60 км/ч"), "со скоростью шестьдесят километров в час");
+        assert_eq!(normalize("через 1 мин. и 2 сек"), "через одну минуту и две секунды");
+        assert_eq!(normalize("через 21 сек"), "через двадцать одну секунду");
+    }
+
+    #[test]
+    fn test_ordinals_and_dates() {
+        assert_eq!(ordinal(3, Gender::Feminine, Case::Nominative), "третья");
+        assert_eq!(ordinal(40, Gender::Masculine, Case::Nominative), "сороковой");
+        assert_eq!(ordinal(2000, Gender::Masculine, Case::Prepositional), "двухтысячном");
+        assert_eq!(ordinal(2024, Gender::Masculine, Case::Genitive), "две тысячи двадцать четвёртого");
+
+        assert_eq!(normalize("5-й подъезд"), "пятый подъезд");
+        assert_eq!(normalize("Сегодня 05.03.2024, вторник"), "Сегодня пятое марта две тысячи двадцать четвёртого года, вторник");
+        assert_eq!(normalize("до 1 мая"), "до первого мая");
+        assert_eq!(normalize("к 12 июня 2025 г. готово"), "к двенадцатому июня две тысячи двадцать пятого года готово");
+        assert_eq!(normalize("в 2024 году"), "в две тысячи двадцать четвёртом году");
+        assert_eq!(normalize("в 1999 г. было"), "в тысяча девятьсот девяносто девятом году было");
+    }
+
+    #[test]
+    fn test_times() {
+        assert_eq!(normalize("в 14:30"), "в четырнадцать часов тридцать минут");
+        assert_eq!(normalize("до 9:05"), "до девяти часов пяти минут");
+        assert_eq!(normalize("в 21:00"), "в двадцать один час");
+        assert_eq!(normalize("1:01"), "один час одна минута");
+        assert_eq!(normalize("с 9:00 до 18:00"), "с девяти часов до восемнадцати часов");
+        assert_eq!(normalize("12:30:45"), "двенадцать часов тридцать минут сорок пять секунд");
+        assert_eq!(normalize("в 0:00:01"), "в ноль часов одну секунду");
+    }
+
+    #[test]
+    fn test_currency() {
+        assert_eq!(normalize("Цена 100 руб. за штуку"), "Цена сто рублей за штуку");
+        assert_eq!(normalize("Итого 100 руб."), "Итого сто рублей.");
+        assert_eq!(normalize("$5"), "пять долларов");
+        assert_eq!(normalize("1 500,50 ₽"), "тысяча пятьсот рублей пятьдесят копеек");
+        assert_eq!(normalize("€1"), "один евро");
+        assert_eq!(normalize("3 млн руб. на счету"), "три миллиона рублей на счету");
+        assert_eq!(normalize("до 2 $"), "до двух долларов");
+        assert_eq!(normalize("5 тыс."), "пять тысяч.");
+        // запись с разделителем разрядов через запятую
+        assert_eq!(normalize("$1,500"), "тысяча пятьсот долларов");
+        assert_eq!(normalize("$1,000.50"), "тысяча долларов пятьдесят центов");
+        assert_eq!(normalize("10 руб. 50 коп."), "десять рублей пятьдесят копеек.");
+        assert_eq!(normalize("до 1 р. 1 коп. за штуку"), "до одного рубля одной копейки за штуку");
+        assert_eq!(normalize("€2,000"), "две тысячи евро");
+        assert_eq!(normalize("1,000.5 км"), "тысяча целых пять десятых километра");
+        // валюта после числа: "1,125" - дробь
+        assert_eq!(normalize("1,125 $"), "одна целая сто двадцать пять тысячных доллара");
+    }
+
+    #[test]
+    fn test_percents() {
+        assert_eq!(normalize("15%"), "пятнадцать процентов");
+        assert_eq!(normalize("1 %"), "один процент");
+        assert_eq!(normalize("2,5%"), "две целых пять десятых процента");
+        assert_eq!(normalize("более 3%"), "более трёх процентов");
+    }
+
+    #[test]
+    fn test_abbreviations() {
+        assert_eq!(normalize("и т.д."), "и так далее.");
+        assert_eq!(normalize("т. е. завтра"), "то есть завтра");
+        assert_eq!(normalize("дом №5"), "дом номер пять");
+        assert_eq!(normalize("груши и т.п. Потом"), "груши и тому подобное. Потом");
+        assert_eq!(normalize("напр. так"), "например так");
+        // внутри слова сокращение не ищется
+        assert_eq!(normalize("мост.е."), "мост.е.");
+    }
+
+    #[test]
+    fn test_normalize_segments() {
+        let segments = super::super::markup::parse("Код <spell>42</spell> и <lang en>5 apples</lang>, 5 яблок");
+        let normalized = normalize_segments(segments, "ru-RU");
+
+        assert_eq!(super::super::markup::plain_text(&normalized), "Код 4 2 и 5 apples, пять яблок");
+    }
+}
@@ -0,0 +1,85 @@
+// app/src/tts/openai.rs - OpenAI TTS API
+
+use super::{AudioBuffer, TtsBackend, TtsConfig, TtsEngine};
+use crate::config;
+use crate::db;
+use crate::error::{JarvisResult, JarvisError, AudioError};
+
+pub const OPENAI_VOICES: [&str; 6] = ["alloy", "echo", "fable", "onyx", "nova", "shimmer"];
+
+pub struct OpenAiBackend {
+    api_key: String,
+}
+
+impl OpenAiBackend {
+    /// Движок с ключом из настроек (None, если ключ не задан)
+    pub fn from_settings() -> Option<Self> {
+        let api_key = db::get_current_settings()?.api_keys.openai;
+
+        if api_key.trim().is_empty() {
+            return None;
+        }
+
+        Some(Self { api_key })
+    }
+}
+
+// OpenAI TTS не поддерживает SSML: разметка отбрасывается (synthesize_segments по умолчанию)
+impl TtsBackend for OpenAiBackend {
+    fn engine(&self) -> TtsEngine {
+        TtsEngine::OpenAI
+    }
+
+    // Простая проверка ключа, реальный запрос выполняется при первом синтезе
+    fn is_available(&self) -> bool {
+        self.api_key.len() >= 20
+    }
+
+    fn synthesize(&self, text: &str, settings: &TtsConfig) -> JarvisResult<AudioBuffer> {
+        let voice = if OPENAI_VOICES.contains(&settings.voice_id.as_str()) {
+            settings.voice_id.as_str()
+        } else {
+            "nova"
+        };
+
+        // Создаем JSON payload для OpenAI TTS API
+        let payload = serde_json::json!({
+            "model": "tts-1",
+            "input": text,
+            "voice": voice,
+            "speed": settings.speed,
+            "response_format": "mp3"
+        });
+
+        let api_key = self.api_key.clone();
+
+        super::run_blocking(move || {
+            let response = reqwest::blocking::Client::new()
+                .post(config::OPENAI_TTS_URL)
+                .bearer_auth(api_key)
+                .json(&payload)
+                .timeout(config::TTS_REQUEST_TIMEOUT)
+                .send()
+                .map_err(request_failed)?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().unwrap_or_default();
+                return Err(JarvisError::AudioError(AudioError::PlaybackFailed(
+                    format!("OpenAI TTS error {}: {}", status, body)
+                )));
+            }
+
+            let audio = response.bytes().map_err(request_failed)?;
+            Ok(AudioBuffer::Encoded(audio.to_vec()))
+        })
+    }
+
+    fn voices(&self) -> JarvisResult<Vec<String>> {
+        Ok(OPENAI_VOICES.iter().map(|voice| voice.to_string()).collect())
+    }
+}
+
+fn request_failed(e: reqwest::Error) -> JarvisError {
+    JarvisError::AudioError(AudioError::PlaybackFailed(format!("OpenAI TTS request failed: {}", e)))
+}
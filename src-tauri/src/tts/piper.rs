@@ -0,0 +1,230 @@
+// app/src/tts/piper.rs - Локальный нейросетевой TTS (Piper, CPU, без сети)
+// Модели `<voice>.onnx` + `<voice>.onnx.json` лежат в директории данных пользователя
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use log::info;
+
+use super::markup::{self, Segment, Style};
+use super::process::capture_output;
+use super::{AudioBuffer, TtsBackend, TtsConfig, TtsEngine};
+use crate::config;
+use crate::error::{JarvisResult, JarvisError, AudioError};
+
+/// Голосовая модель Piper
+#[derive(Debug, Clone, PartialEq)]
+pub struct PiperModel {
+    pub name: String,
+    pub path: PathBuf,
+    pub language: String,  // "ru", "en", ...
+    pub sample_rate: u32,
+}
+
+pub struct PiperBackend {
+    binary: PathBuf,
+    models: Vec<PiperModel>,
+}
+
+impl PiperBackend {
+    pub fn new() -> JarvisResult<Self> {
+        let models_dir = config::get_piper_models_dir()?;
+
+        let models = discover_piper_models(&models_dir);
+        if models.is_empty() {
+            return Err(JarvisError::AudioError(AudioError::FileNotFound(
+                format!("No Piper voices in {}", models_dir.display())
+            )));
+        }
+
+        // бинарник рядом с моделями или в PATH
+        let bundled = models_dir.join(if cfg!(windows) { "piper.exe" } else { "piper" });
+        let binary = if bundled.exists() { bundled } else { PathBuf::from("piper") };
+
+        info!("Piper voices: {:?}", models.iter().map(|m| &m.name).collect::<Vec<_>>());
+
+        Ok(Self { binary, models })
+    }
+
+    fn model_for(&self, settings: &TtsConfig) -> &PiperModel {
+        select_model(&self.models, settings).unwrap_or(&self.models[0])
+    }
+
+    // смена языка в разметке выбирает модель этого языка, если она есть
+    fn model_for_style(&self, settings: &TtsConfig, style: &Style) -> &PiperModel {
+        style.language.as_ref()
+            .and_then(|language| {
+                let language = short_language(language);
+                self.models.iter().find(|model| model.language == language)
+            })
+            .unwrap_or_else(|| self.model_for(settings))
+    }
+
+    fn run(&self, text: &str, model: &PiperModel, speed: f32) -> JarvisResult<Vec<i16>> {
+        // length_scale > 1 замедляет речь
+        let length_scale = 1.0 / speed.clamp(0.5, 2.0);
+
+        let raw = capture_output(
+            Command::new(&self.binary).args([
+                "--quiet",
+                "--output_raw",
+                "--length_scale", &format!("{:.2}", length_scale),
+                "--model",
+            ]).arg(&model.path),
+            Some(text.as_bytes()),
+        ).map_err(|e| JarvisError::AudioError(AudioError::PlaybackFailed(
+            format!("Piper failed: {}", e)
+        )))?;
+
+        Ok(pcm_from_bytes(&raw))
+    }
+}
+
+impl TtsBackend for PiperBackend {
+    fn engine(&self) -> TtsEngine {
+        TtsEngine::Local
+    }
+
+    fn is_available(&self) -> bool {
+        Command::new(&self.binary)
+            .arg("--help")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+
+    fn synthesize(&self, text: &str, settings: &TtsConfig) -> JarvisResult<AudioBuffer> {
+        let model = self.model_for(settings);
+
+        Ok(AudioBuffer::Pcm {
+            samples: self.run(text, model, settings.speed)?,
+            sample_rate: model.sample_rate,
+            channels: 1,
+        })
+    }
+
+    // фрагменты синтезируются по отдельности и склеиваются, выделение не поддерживается
+    fn synthesize_segments(&self, segments: &[Segment], settings: &TtsConfig) -> JarvisResult<AudioBuffer> {
+        markup::synthesize_concatenated(segments, |text, style| {
+            let model = self.model_for_style(settings, style);
+            let speed = settings.speed * style.rate.unwrap_or(1.0);
+
+            self.run(text, model, speed).map(|samples| (samples, model.sample_rate))
+        })
+    }
+
+    fn voices(&self) -> JarvisResult<Vec<String>> {
+        Ok(self.models.iter().map(|model| model.name.clone()).collect())
+    }
+}
+
+/// Поиск голосов Piper: `.onnx` файл с конфигурацией `.onnx.json` рядом
+pub fn discover_piper_models(dir: &Path) -> Vec<PiperModel> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut models = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "onnx"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().to_string();
+            let config_path = PathBuf::from(format!("{}.json", path.display()));
+            let voice_config: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(config_path).ok()?).ok()?;
+
+            let sample_rate = voice_config["audio"]["sample_rate"].as_u64()
+                .unwrap_or(config::PIPER_DEFAULT_SAMPLE_RATE as u64) as u32;
+
+            // "ru_RU" из конфигурации или из имени файла ("ru_RU-irina-medium")
+            let language_code = voice_config["language"]["code"].as_str()
+                .or_else(|| voice_config["espeak"]["voice"].as_str())
+                .unwrap_or(&name);
+
+            Some(PiperModel {
+                language: short_language(language_code),
+                name,
+                path,
+                sample_rate,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+    models
+}
+
+/// Модель по имени голоса, иначе первая модель языка
+pub fn select_model<'a>(models: &'a [PiperModel], settings: &TtsConfig) -> Option<&'a PiperModel> {
+    let language = short_language(&settings.language);
+
+    models.iter().find(|model| model.name == settings.voice_id)
+        .or_else(|| models.iter().find(|model| model.language == language))
+}
+
+// "ru-RU", "ru_RU-irina" -> "ru"
+fn short_language(code: &str) -> String {
+    code.split(['-', '_']).next().unwrap_or(code).to_lowercase()
+}
+
+/// Сырые 16-битные little-endian сэмплы
+fn pcm_from_bytes(raw: &[u8]) -> Vec<i16> {
+    raw.chunks_exact(2)
+        .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write_voice(dir: &Path, name: &str, voice_config: &str) {
+        std::fs::write(dir.join(format!("{}.onnx", name)), b"model").unwrap();
+        std::fs::write(dir.join(format!("{}.onnx.json", name)), voice_config).unwrap();
+    }
+
+    #[test]
+    fn test_discover_piper_models() {
+        let dir = tempdir().unwrap();
+        write_voice(dir.path(), "ru_RU-irina-medium", r#"{"audio": {"sample_rate": 22050}, "language": {"code": "ru_RU"}}"#);
+        write_voice(dir.path(), "en_US-amy-low", r#"{"audio": {"sample_rate": 16000}}"#);
+        // модель без конфигурации пропускается
+        std::fs::write(dir.path().join("broken.onnx"), b"model").unwrap();
+
+        let models = discover_piper_models(dir.path());
+
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].name, "en_US-amy-low");
+        assert_eq!(models[0].language, "en");
+        assert_eq!(models[0].sample_rate, 16000);
+        assert_eq!(models[1].language, "ru");
+        assert_eq!(models[1].sample_rate, 22050);
+    }
+
+    #[test]
+    fn test_select_model() {
+        let model = |name: &str, language: &str| PiperModel {
+            name: name.to_string(),
+            path: PathBuf::from(format!("{}.onnx", name)),
+            language: language.to_string(),
+            sample_rate: 22050,
+        };
+        let models = vec![model("en_US-amy-low", "en"), model("ru_RU-denis-medium", "ru"), model("ru_RU-irina-medium", "ru")];
+
+        let settings = TtsConfig { voice_id: "ru_RU-irina-medium".to_string(), ..Default::default() };
+        assert_eq!(select_model(&models, &settings).unwrap().name, "ru_RU-irina-medium");
+
+        let settings = TtsConfig { language: "en-US".to_string(), ..Default::default() };
+        assert_eq!(select_model(&models, &settings).unwrap().name, "en_US-amy-low");
+
+        let settings = TtsConfig { language: "de-DE".to_string(), ..Default::default() };
+        assert!(select_model(&models, &settings).is_none());
+    }
+
+    #[test]
+    fn test_pcm_from_bytes() {
+        assert_eq!(pcm_from_bytes(&[0x01, 0x00, 0xff, 0xff, 0x00, 0x80, 0x7f]), vec![1, -1, i16::MIN]);
+    }
+}
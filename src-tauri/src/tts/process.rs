@@ -0,0 +1,215 @@
+// app/src/tts/process.rs - Запуск внешних движков синтеза с возможностью прерывания
+
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread::ThreadId;
+
+// Процессы синтеза, выполняющиеся в данный момент, и запустившие их потоки
+// (речь и заполнение кэша идут параллельно, прерывается только речь)
+static ACTIVE_PROCESSES: Mutex<Vec<ActiveProcess>> = Mutex::new(Vec::new());
+static NEXT_PROCESS_ID: AtomicU64 = AtomicU64::new(0);
+
+struct ActiveProcess {
+    id: u64,
+    thread: ThreadId,
+    child: Child,
+}
+
+/// Запуск процесса с передачей данных в stdin и захватом stdout
+/// Процесс может быть завершен из другого потока через `kill_spawned_by`
+pub fn capture_output(command: &mut Command, input: Option<&[u8]>) -> Result<Vec<u8>, String> {
+    let mut child = command
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    // stdin пишется в отдельном потоке, чтобы не заблокироваться на заполненном stdout
+    let stdin_writer = match (input, child.stdin.take()) {
+        (Some(input), Some(mut stdin)) => {
+            let input = input.to_vec();
+            // stdin закрывается при drop, процесс получает EOF
+            Some(std::thread::spawn(move || stdin.write_all(&input)))
+        }
+        _ => None,
+    };
+
+    let mut stdout = child.stdout.take().ok_or("stdout is not captured")?;
+    let mut stderr = child.stderr.take().ok_or("stderr is not captured")?;
+
+    let process_id = NEXT_PROCESS_ID.fetch_add(1, Ordering::SeqCst);
+    lock_processes().push(ActiveProcess {
+        id: process_id,
+        thread: std::thread::current().id(),
+        child,
+    });
+
+    // stderr читается в отдельном потоке, чтобы процесс не заблокировался на переполненном канале
+    let stderr_reader = std::thread::spawn(move || {
+        let mut error = String::new();
+        let _ = stderr.read_to_string(&mut error);
+        error
+    });
+
+    let mut audio = Vec::new();
+    let read_result = stdout.read_to_end(&mut audio);
+    let error = stderr_reader.join().unwrap_or_default();
+    if let Some(writer) = stdin_writer {
+        let _ = writer.join();
+    }
+
+    // процесс уже забран, если синтез был прерван
+    let mut child = take_process(process_id).ok_or("synthesis interrupted")?;
+    let status = child.wait().map_err(|e| e.to_string())?;
+    read_result.map_err(|e| e.to_string())?;
+
+    if !status.success() {
+        return Err(error.trim().to_string());
+    }
+
+    if audio.is_empty() {
+        return Err("no audio produced".to_string());
+    }
+
+    Ok(audio)
+}
+
+fn take_process(process_id: u64) -> Option<Child> {
+    let mut processes = lock_processes();
+    let position = processes.iter().position(|process| process.id == process_id)?;
+    Some(processes.swap_remove(position).child)
+}
+
+fn lock_processes() -> std::sync::MutexGuard<'static, Vec<ActiveProcess>> {
+    ACTIVE_PROCESSES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Принудительное завершение процессов синтеза, запущенных потоком `thread`
+pub fn kill_spawned_by(thread: ThreadId) -> bool {
+    kill_matching(|process| process.thread == thread)
+}
+
+/// Принудительное завершение всех процессов синтеза (при остановке TTS)
+pub fn kill_all() -> bool {
+    kill_matching(|_| true)
+}
+
+fn kill_matching(matches: impl Fn(&ActiveProcess) -> bool) -> bool {
+    let killed = {
+        let mut processes = lock_processes();
+        let (killed, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut *processes)
+            .into_iter()
+            .partition(|process| matches(process));
+        *processes = kept;
+        killed
+    };
+
+    let any_killed = !killed.is_empty();
+    for mut process in killed {
+        let _ = process.child.kill();
+        let _ = process.child.wait();
+    }
+
+    any_killed
+}
+
+/// Враждебные строки для тестов: метасимволы оболочки, PowerShell, cmd и SSML
+/// Каждая полезная нагрузка при интерпретации оболочкой создала бы файл `canary`
+#[cfg(test)]
+pub(crate) fn hostile_inputs(canary: &std::path::Path) -> Vec<String> {
+    let canary = canary.display();
+
+    let mut inputs = vec![
+        format!("'; touch {canary}; echo '"),
+        format!("\"; touch {canary}; echo \""),
+        format!("$(touch {canary})"),
+        format!("`touch {canary}`"),
+        format!("; touch {canary}"),
+        format!("| touch {canary}"),
+        format!("&& touch {canary}"),
+        format!("|| touch {canary}"),
+        format!("\ntouch {canary}\n"),
+        format!("> {canary}"),
+        format!("--output={canary}"),
+        format!("-w {canary}"),
+        format!("'); New-Item -Path '{canary}'; ('"),
+        format!("$(New-Item -Path '{canary}')"),
+        format!("\" & type nul > {canary} & \""),
+        "%PATH%".to_string(),
+        "${env:PATH}".to_string(),
+        "$HOME ~ * ? [a-z] {a,b} !!".to_string(),
+        "<speak><audio src=\"file:///etc/passwd\"/></speak>".to_string(),
+        "]]></speak><!ENTITY x SYSTEM \"file:///etc/passwd\">".to_string(),
+        "Тест 'кавычек' и \"двойных\" кавычек".to_string(),
+        "\\'\\\"\\`\\$".to_string(),
+        "\t\r\u{7f}\u{1b}[31m".to_string(),
+        "-".to_string(),
+        "--".to_string(),
+    ];
+
+    // детерминированные смеси метасимволов (LCG), чтобы тест был воспроизводимым
+    const ALPHABET: &[char] = &[
+        '\'', '"', '`', '$', '(', ')', ';', '|', '&', '<', '>', '\\', '\n', ' ', '*', '?',
+        '{', '}', '%', '!', '#', '~', '-', '=', 'a', 'я', '1', '.',
+    ];
+    let mut seed: u64 = 0x2545f4914f6cdd1d;
+    for i in 0..200 {
+        let mut input = String::new();
+        for _ in 0..(8 + i % 24) {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            input.push(ALPHABET[(seed >> 33) as usize % ALPHABET.len()]);
+        }
+        input.push_str(&format!(" touch {canary}"));
+        inputs.push(input);
+    }
+
+    inputs
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_output_passes_stdin() {
+        let output = capture_output(&mut Command::new("cat"), Some("привет".as_bytes())).unwrap();
+        assert_eq!(output, "привет".as_bytes());
+    }
+
+    #[test]
+    fn test_kill_spawned_by_interrupts_only_own_process() {
+        // процесс другого потока (заполнение кэша) не должен быть прерван
+        let background = std::thread::spawn(|| capture_output(Command::new("sh").args(["-c", "sleep 0.5; echo done"]), None));
+        let speech = std::thread::spawn(|| capture_output(Command::new("sleep").arg("5"), None));
+        let speech_thread = speech.thread().id();
+
+        let mut killed = false;
+        for _ in 0..100 {
+            if kill_spawned_by(speech_thread) {
+                killed = true;
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+
+        assert!(killed);
+        assert!(speech.join().unwrap().is_err());
+        assert_eq!(background.join().unwrap().unwrap(), b"done\n");
+    }
+
+    #[test]
+    fn test_hostile_input_is_not_interpreted() {
+        let dir = tempfile::tempdir().unwrap();
+        let canary = dir.path().join("canary");
+
+        for input in hostile_inputs(&canary) {
+            // текст доходит до процесса байт в байт
+            let output = capture_output(&mut Command::new("cat"), Some(input.as_bytes())).unwrap();
+            assert_eq!(output, input.as_bytes(), "input altered: {:?}", input);
+            assert!(!canary.exists(), "input executed: {:?}", input);
+        }
+    }
+}
@@ -0,0 +1,304 @@
+// app/src/tts/queue.rs - Очередь фраз для озвучивания с приоритетами
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use super::TtsConfig;
+
+/// Приоритет фразы
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SpeechPriority {
+    Low,     // Может быть отброшена, если долго ждет в очереди
+    Normal,  // Озвучивается по порядку
+    Alert,   // Прерывает текущую фразу и озвучивается первой
+}
+
+// Общее состояние фразы и ее handle
+struct UtteranceState {
+    cancelled: AtomicBool,
+    // результат озвучивания (None - еще в очереди или озвучивается)
+    result: Mutex<Option<Result<(), String>>>,
+    done_signal: Condvar,
+}
+
+/// Handle фразы в очереди: отмена и ожидание завершения
+#[derive(Clone)]
+pub struct UtteranceHandle {
+    id: u64,
+    state: Arc<UtteranceState>,
+}
+
+impl UtteranceHandle {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Отмена фразы (если она уже озвучивается - воспроизведение прерывается)
+    pub fn cancel(&self) {
+        super::cancel(self.id);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Ожидание завершения (озвучена, отменена или отброшена)
+    /// Отмена не считается ошибкой, ошибкой считается только сбой синтеза или воспроизведения
+    pub fn wait(&self) -> Result<(), String> {
+        let mut result = self.state.result.lock().unwrap();
+        loop {
+            if let Some(result) = result.as_ref() {
+                return result.clone();
+            }
+            result = self.state.done_signal.wait(result).unwrap();
+        }
+    }
+
+    pub(super) fn finish(&self, result: Result<(), String>) {
+        *self.state.result.lock().unwrap() = Some(result);
+        self.state.done_signal.notify_all();
+    }
+
+    pub(super) fn mark_cancelled(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Фраза в очереди
+pub struct Utterance {
+    pub text: String,
+    pub priority: SpeechPriority,
+    pub enqueued_at: Instant,
+    pub handle: UtteranceHandle,
+    // разовые настройки голоса (пробное прослушивание), None - текущие
+    pub settings: Option<TtsConfig>,
+}
+
+/// Информация о фразе для API статуса
+#[derive(Serialize, Debug, Clone)]
+pub struct UtteranceInfo {
+    pub id: u64,
+    pub text: String,
+    pub priority: SpeechPriority,
+    pub waiting_ms: u64,
+}
+
+impl Utterance {
+    pub fn info(&self, now: Instant) -> UtteranceInfo {
+        UtteranceInfo {
+            id: self.handle.id,
+            text: self.text.clone(),
+            priority: self.priority,
+            waiting_ms: now.saturating_duration_since(self.enqueued_at).as_millis() as u64,
+        }
+    }
+}
+
+/// Состояние очереди для API статуса
+#[derive(Serialize, Debug, Clone)]
+pub struct QueueStatus {
+    pub current: Option<UtteranceInfo>,
+    pub pending: Vec<UtteranceInfo>,
+}
+
+/// Очередь фраз: Alert впереди остальных, внутри приоритета - по порядку
+pub struct SpeechQueue {
+    items: VecDeque<Utterance>,
+    next_id: u64,
+    // время, после которого Low фраза считается устаревшей
+    low_priority_ttl: Duration,
+}
+
+impl SpeechQueue {
+    pub const fn new(low_priority_ttl: Duration) -> Self {
+        Self {
+            items: VecDeque::new(),
+            next_id: 1,
+            low_priority_ttl,
+        }
+    }
+
+    /// Добавление фразы, возвращает ее handle
+    pub fn push(&mut self, text: &str, priority: SpeechPriority, now: Instant) -> UtteranceHandle {
+        self.push_with_settings(text, priority, None, now)
+    }
+
+    /// Добавление фразы с разовыми настройками голоса
+    pub fn push_with_settings(
+        &mut self,
+        text: &str,
+        priority: SpeechPriority,
+        settings: Option<TtsConfig>,
+        now: Instant,
+    ) -> UtteranceHandle {
+        let handle = UtteranceHandle {
+            id: self.next_id,
+            state: Arc::new(UtteranceState {
+                cancelled: AtomicBool::new(false),
+                result: Mutex::new(None),
+                done_signal: Condvar::new(),
+            }),
+        };
+        self.next_id += 1;
+
+        let utterance = Utterance {
+            text: text.to_string(),
+            priority,
+            enqueued_at: now,
+            handle: handle.clone(),
+            settings,
+        };
+
+        // Alert встает после уже ожидающих Alert, но перед остальными
+        match priority {
+            SpeechPriority::Alert => {
+                let position = self.items.iter()
+                    .position(|item| item.priority != SpeechPriority::Alert)
+                    .unwrap_or(self.items.len());
+                self.items.insert(position, utterance);
+            }
+            _ => self.items.push_back(utterance),
+        }
+
+        handle
+    }
+
+    /// Следующая фраза для озвучивания; отмененные и устаревшие Low фразы отбрасываются
+    pub fn pop_next(&mut self, now: Instant) -> Option<Utterance> {
+        while let Some(item) = self.items.pop_front() {
+            if item.handle.is_cancelled() {
+                item.handle.finish(Ok(()));
+                continue;
+            }
+
+            if item.priority == SpeechPriority::Low
+                && now.saturating_duration_since(item.enqueued_at) > self.low_priority_ttl
+            {
+                debug!("Dropping stale low priority utterance: '{}'", item.text);
+                item.handle.mark_cancelled();
+                item.handle.finish(Ok(()));
+                continue;
+            }
+
+            return Some(item);
+        }
+
+        None
+    }
+
+    /// Отмена ожидающей фразы
+    pub fn cancel(&mut self, id: u64) -> bool {
+        match self.items.iter().position(|item| item.handle.id == id) {
+            Some(position) => {
+                let item = self.items.remove(position).unwrap();
+                item.handle.mark_cancelled();
+                item.handle.finish(Ok(()));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Отмена всех ожидающих фраз
+    pub fn clear(&mut self) -> usize {
+        let count = self.items.len();
+        for item in self.items.drain(..) {
+            item.handle.mark_cancelled();
+            item.handle.finish(Ok(()));
+        }
+        count
+    }
+
+    pub fn pending(&self, now: Instant) -> Vec<UtteranceInfo> {
+        self.items.iter().map(|item| item.info(now)).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(queue: &SpeechQueue, now: Instant) -> Vec<String> {
+        queue.pending(now).into_iter().map(|info| info.text).collect()
+    }
+
+    #[test]
+    fn test_alerts_go_first_in_order() {
+        let now = Instant::now();
+        let mut queue = SpeechQueue::new(Duration::from_secs(10));
+
+        queue.push("a", SpeechPriority::Normal, now);
+        queue.push("b", SpeechPriority::Low, now);
+        queue.push("alert 1", SpeechPriority::Alert, now);
+        queue.push("c", SpeechPriority::Normal, now);
+        queue.push("alert 2", SpeechPriority::Alert, now);
+
+        assert_eq!(texts(&queue, now), vec!["alert 1", "alert 2", "a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_stale_low_priority_is_dropped() {
+        let start = Instant::now();
+        let mut queue = SpeechQueue::new(Duration::from_secs(5));
+
+        let stale = queue.push("stale", SpeechPriority::Low, start);
+        queue.push("normal", SpeechPriority::Normal, start);
+
+        let later = start + Duration::from_secs(6);
+        let next = queue.pop_next(later).unwrap();
+
+        assert_eq!(next.text, "normal");
+        assert!(stale.is_cancelled());
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_cancel_pending_utterance() {
+        let now = Instant::now();
+        let mut queue = SpeechQueue::new(Duration::from_secs(10));
+
+        queue.push("first", SpeechPriority::Normal, now);
+        let second = queue.push("second", SpeechPriority::Normal, now);
+
+        assert!(queue.cancel(second.id()));
+        assert!(!queue.cancel(second.id()));
+        assert!(second.is_cancelled());
+
+        // handle отмененной фразы не блокирует ожидание
+        assert!(second.wait().is_ok());
+
+        assert_eq!(texts(&queue, now), vec!["first"]);
+        assert_eq!(queue.pop_next(now).unwrap().text, "first");
+        assert!(queue.pop_next(now).is_none());
+    }
+
+    #[test]
+    fn test_settings_override_stays_with_utterance() {
+        let now = Instant::now();
+        let mut queue = SpeechQueue::new(Duration::from_secs(10));
+
+        let preview = TtsConfig {
+            voice_id: "preview".to_string(),
+            ..TtsConfig::default()
+        };
+        queue.push_with_settings("preview", SpeechPriority::Normal, Some(preview), now);
+        queue.push("regular", SpeechPriority::Normal, now);
+
+        let first = queue.pop_next(now).unwrap();
+        assert_eq!(first.settings.map(|settings| settings.voice_id), Some("preview".to_string()));
+        assert!(queue.pop_next(now).unwrap().settings.is_none());
+    }
+}
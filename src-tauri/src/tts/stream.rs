@@ -0,0 +1,261 @@
+// app/src/tts/stream.rs - Потоковое PCM аудио: воспроизведение начинается до окончания загрузки
+
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use log::warn;
+
+/// Порция потока
+pub enum StreamChunk {
+    Samples(Vec<i16>),
+    // Поток успешно завершен (обрыв канала без End считается ошибкой)
+    End,
+    Error(String),
+}
+
+type CompleteCallback = Box<dyn FnOnce(Vec<i16>) + Send>;
+type Completion = (CompleteCallback, Vec<i16>);
+
+// Фоновый поток для on_complete воспроизводимых потоков: StreamSource работает
+// в аудио callback rodio, где нельзя кодировать WAV и писать файлы
+static COMPLETION_WORKER: OnceLock<Mutex<Sender<Completion>>> = OnceLock::new();
+
+fn complete_in_background(callback: CompleteCallback, samples: Vec<i16>) {
+    let worker = COMPLETION_WORKER.get_or_init(|| {
+        let (sender, receiver) = channel::<Completion>();
+
+        let spawned = std::thread::Builder::new()
+            .name("tts-stream-complete".to_string())
+            .spawn(move || {
+                for (callback, samples) in receiver {
+                    callback(samples);
+                }
+            });
+        if let Err(e) = spawned {
+            warn!("Failed to start speech stream completion worker: {}", e);
+        }
+
+        Mutex::new(sender)
+    });
+
+    let sent = worker.lock().map(|sender| sender.send((callback, samples)).is_ok());
+    if !matches!(sent, Ok(true)) {
+        warn!("Speech stream completion skipped: worker is not running");
+    }
+}
+
+/// Поток PCM сэмплов от движка синтеза
+pub struct PcmStream {
+    receiver: Receiver<StreamChunk>,
+    pub sample_rate: u32,
+    pub channels: u16,
+    // вызывается с полным аудио после успешного завершения (например, для кэша);
+    // при воспроизведении - в фоновом потоке
+    on_complete: Option<CompleteCallback>,
+}
+
+impl std::fmt::Debug for PcmStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PcmStream({} Hz, {} ch)", self.sample_rate, self.channels)
+    }
+}
+
+impl PcmStream {
+    pub fn new(receiver: Receiver<StreamChunk>, sample_rate: u32, channels: u16) -> Self {
+        Self {
+            receiver,
+            sample_rate,
+            channels,
+            on_complete: None,
+        }
+    }
+
+    pub fn on_complete(mut self, callback: impl FnOnce(Vec<i16>) + Send + 'static) -> Self {
+        self.on_complete = Some(Box::new(callback));
+        self
+    }
+
+    /// Ожидание всего потока (без воспроизведения)
+    pub fn into_samples(mut self) -> Result<Vec<i16>, String> {
+        let mut samples = Vec::new();
+
+        loop {
+            match self.receiver.recv() {
+                Ok(StreamChunk::Samples(chunk)) => samples.extend_from_slice(&chunk),
+                Ok(StreamChunk::End) => break,
+                Ok(StreamChunk::Error(e)) => return Err(e),
+                Err(_) => return Err("audio stream interrupted".to_string()),
+            }
+        }
+
+        if let Some(callback) = self.on_complete.take() {
+            callback(samples.clone());
+        }
+
+        Ok(samples)
+    }
+}
+
+/// Источник для плеера: сэмплы выдаются по мере поступления,
+/// пока данных нет - выдается тишина (аудио поток не блокируется)
+pub struct StreamSource {
+    stream: PcmStream,
+    current: Vec<i16>,
+    position: usize,
+    recorded: Vec<i16>,
+    finished: bool,
+}
+
+impl StreamSource {
+    pub fn new(stream: PcmStream) -> Self {
+        Self {
+            stream,
+            current: Vec::new(),
+            position: 0,
+            recorded: Vec::new(),
+            finished: false,
+        }
+    }
+
+    fn complete(&mut self) {
+        self.finished = true;
+        if let Some(callback) = self.stream.on_complete.take() {
+            complete_in_background(callback, std::mem::take(&mut self.recorded));
+        }
+    }
+}
+
+impl Iterator for StreamSource {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        loop {
+            if let Some(sample) = self.current.get(self.position) {
+                self.position += 1;
+                return Some(*sample);
+            }
+
+            if self.finished {
+                return None;
+            }
+
+            match self.stream.receiver.try_recv() {
+                Ok(StreamChunk::Samples(chunk)) => {
+                    if self.stream.on_complete.is_some() {
+                        self.recorded.extend_from_slice(&chunk);
+                    }
+                    self.current = chunk;
+                    self.position = 0;
+                }
+                Ok(StreamChunk::End) => {
+                    self.complete();
+                    return None;
+                }
+                Ok(StreamChunk::Error(e)) => {
+                    warn!("Speech stream failed: {}", e);
+                    self.finished = true;
+                    return None;
+                }
+                Err(TryRecvError::Empty) => return Some(0),
+                Err(TryRecvError::Disconnected) => {
+                    self.finished = true;
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+impl rodio::Source for StreamSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.stream.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.stream.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Преобразование байтового потока в сэмплы с переносом нечетного байта между порциями
+#[derive(Default)]
+pub struct PcmDecoder {
+    pending: Option<u8>,
+}
+
+impl PcmDecoder {
+    pub fn decode(&mut self, bytes: &[u8]) -> Vec<i16> {
+        let mut data = Vec::with_capacity(bytes.len() + 1);
+        data.extend(self.pending.take());
+        data.extend_from_slice(bytes);
+
+        if !data.len().is_multiple_of(2) {
+            self.pending = data.pop();
+        }
+
+        data.chunks_exact(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_pcm_decoder_keeps_odd_byte() {
+        let mut decoder = PcmDecoder::default();
+
+        assert_eq!(decoder.decode(&[0x01, 0x00, 0xff]), vec![1]);
+        assert_eq!(decoder.decode(&[0xff, 0x02]), vec![-1]);
+        assert_eq!(decoder.decode(&[0x00]), vec![2]);
+    }
+
+    #[test]
+    fn test_stream_source_plays_and_records() {
+        let (sender, receiver) = channel();
+        let (completed_sender, completed) = channel();
+
+        let stream = PcmStream::new(receiver, 16000, 1)
+            .on_complete(move |samples| completed_sender.send(samples).unwrap());
+        let mut source = StreamSource::new(stream);
+
+        // данных еще нет - тишина
+        assert_eq!(source.next(), Some(0));
+
+        sender.send(StreamChunk::Samples(vec![1, 2])).unwrap();
+        sender.send(StreamChunk::Samples(vec![3])).unwrap();
+        sender.send(StreamChunk::End).unwrap();
+
+        assert_eq!(source.by_ref().collect::<Vec<_>>(), vec![1, 2, 3]);
+        // запись в кэш выполняется вне аудио потока
+        assert_eq!(completed.recv_timeout(Duration::from_secs(5)), Ok(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_interrupted_stream_is_not_completed() {
+        let (sender, receiver) = channel();
+        let completed = Arc::new(Mutex::new(false));
+        let completed_clone = completed.clone();
+
+        let stream = PcmStream::new(receiver, 16000, 1)
+            .on_complete(move |_| *completed_clone.lock().unwrap() = true);
+
+        sender.send(StreamChunk::Samples(vec![1, 2])).unwrap();
+        drop(sender);
+
+        assert!(stream.into_samples().is_err());
+        assert!(!*completed.lock().unwrap());
+    }
+}
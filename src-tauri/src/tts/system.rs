@@ -0,0 +1,374 @@
+// app/src/tts/system.rs - Системный TTS (SAPI на Windows, espeak/festival на Linux)
+// Движки пишут WAV в stdout, воспроизведение выполняет общий плеер
+
+use std::ffi::OsStr;
+use std::process::Command;
+
+use log::{info, warn};
+
+use super::markup::{self, Segment, Style};
+use super::process::capture_output;
+use super::{AudioBuffer, TtsBackend, TtsConfig, TtsEngine};
+use crate::error::{JarvisResult, JarvisError, AudioError};
+
+pub struct SystemBackend;
+
+impl TtsBackend for SystemBackend {
+    fn engine(&self) -> TtsEngine {
+        TtsEngine::System
+    }
+
+    fn is_available(&self) -> bool {
+        test_system_tts().is_ok()
+    }
+
+    fn synthesize(&self, text: &str, settings: &TtsConfig) -> JarvisResult<AudioBuffer> {
+        synthesize_system(text, &Style::default(), settings).map(AudioBuffer::Encoded)
+    }
+
+    fn synthesize_segments(&self, segments: &[Segment], settings: &TtsConfig) -> JarvisResult<AudioBuffer> {
+        // SAPI понимает SSML целиком
+        #[cfg(target_os = "windows")]
+        {
+            let ssml = markup::render_ssml(segments, &markup::SsmlSupport::FULL, &settings.language);
+            return synthesize_sapi(&ssml, true, settings).map(AudioBuffer::Encoded);
+        }
+
+        // espeak: каждый фрагмент синтезируется со своими флагами, паузы - тишиной
+        #[allow(unreachable_code)]
+        markup::synthesize_concatenated(segments, |text, style| {
+            markup::decode_wav(&synthesize_system(text, style, settings)?)
+        })
+    }
+
+    fn voices(&self) -> JarvisResult<Vec<String>> {
+        get_system_voices()
+    }
+}
+
+/// Тест системного TTS
+fn test_system_tts() -> JarvisResult<()> {
+    #[cfg(target_os = "windows")]
+    {
+        // Тестируем Windows SAPI
+        let output = Command::new("powershell")
+            .args(["-Command", "Add-Type -AssemblyName System.Speech; $speak = New-Object System.Speech.Synthesis.SpeechSynthesizer; $speak.GetInstalledVoices().Count"])
+            .output()
+            .map_err(|e| JarvisError::AudioError(AudioError::InitializationFailed(
+                format!("Failed to test Windows SAPI: {}", e)
+            )))?;
+
+        if output.status.success() {
+            let voice_count = String::from_utf8_lossy(&output.stdout).trim().parse::<i32>().unwrap_or(0);
+            if voice_count > 0 {
+                info!("Windows SAPI available with {} voices", voice_count);
+                return Ok(());
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // Тестируем espeak
+        if let Ok(output) = Command::new("espeak").arg("--version").output() {
+            if output.status.success() {
+                info!("espeak available: {}", String::from_utf8_lossy(&output.stdout));
+                return Ok(());
+            }
+        }
+
+        // Тестируем festival (синтез в файл выполняет text2wave)
+        if let Ok(output) = Command::new("festival").arg("--version").output() {
+            if output.status.success() {
+                info!("festival available");
+                return Ok(());
+            }
+        }
+    }
+
+    Err(JarvisError::AudioError(AudioError::InitializationFailed(
+        "No system TTS found".to_string()
+    )))
+}
+
+/// Синтез фразы системным TTS в WAV
+/// Оформление (язык, скорость, выделение) передается флагами espeak; SAPI его не получает
+fn synthesize_system(text: &str, style: &Style, settings: &TtsConfig) -> JarvisResult<Vec<u8>> {
+    #[cfg(target_os = "windows")]
+    {
+        let _ = style;
+        return synthesize_sapi(text, false, settings);
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        return synthesize_espeak("espeak", "text2wave", text, style, settings);
+    }
+
+    #[allow(unreachable_code)]
+    {
+        let _ = (text, style, settings);
+        Err(JarvisError::AudioError(AudioError::PlaybackFailed(
+            "System TTS is not supported on this platform".to_string()
+        )))
+    }
+}
+
+/// Синтез через espeak с fallback на festival (имена программ задаются для тестов)
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn synthesize_espeak(
+    espeak: impl AsRef<OsStr>,
+    text2wave: impl AsRef<OsStr>,
+    text: &str,
+    style: &Style,
+    settings: &TtsConfig,
+) -> JarvisResult<Vec<u8>> {
+    // текст передается только через stdin: оболочка не участвует, текст не станет опцией
+    let espeak = capture_output(Command::new(espeak).args(espeak_args(style, settings)), Some(text.as_bytes()));
+
+    let error = match espeak {
+        Ok(wav) => return Ok(wav),
+        Err(e) => e,
+    };
+
+    // Fallback to festival: text2wave читает текст из stdin и пишет WAV в stdout
+    warn!("espeak failed ({}), falling back to festival", error);
+    capture_output(&mut Command::new(text2wave), Some(text.as_bytes()))
+        .map_err(|e| JarvisError::AudioError(AudioError::PlaybackFailed(
+            format!("Both espeak and festival failed: {}", e)
+        )))
+}
+
+/// Аргументы espeak для фрагмента: голос по языку, скорость, высота и громкость для выделения
+/// Текст в аргументы не входит, espeak читает его из stdin
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn espeak_args(style: &Style, settings: &TtsConfig) -> Vec<String> {
+    let language = style.language.as_deref().unwrap_or(&settings.language);
+
+    let speed = (settings.speed * style.rate.unwrap_or(1.0) * 150.0) as i32; // words per minute
+    let pitch_boost = if style.emphasis { 1.2 } else { 1.0 };
+    let pitch = (settings.pitch * pitch_boost * 50.0).clamp(0.0, 99.0) as i32;
+    let amplitude = if style.emphasis { 150 } else { 100 };
+
+    vec![
+        "--stdout".to_string(),
+        "-s".to_string(), speed.to_string(),
+        "-p".to_string(), pitch.to_string(),
+        "-a".to_string(), amplitude.to_string(),
+        "-v".to_string(), espeak_voice(language),
+        "--stdin".to_string(),
+    ]
+}
+
+// "ru-RU" -> "ru"; некорректный код языка не попадает в аргументы
+fn espeak_voice(language: &str) -> String {
+    let code = language.split(['-', '_']).next().unwrap_or_default().to_lowercase();
+
+    if (2..=3).contains(&code.len()) && code.chars().all(|c| c.is_ascii_lowercase()) {
+        code
+    } else {
+        "en".to_string()
+    }
+}
+
+/// Имя голоса SAPI: начинается с буквы или цифры, далее буквы, цифры, пробелы и "-_.()"
+pub fn is_valid_voice_id(voice_id: &str) -> bool {
+    voice_id.chars().next().is_some_and(char::is_alphanumeric)
+        && voice_id.chars().count() <= 64
+        && voice_id.chars().all(|c| c.is_alphanumeric() || " -_.()".contains(c))
+}
+
+// Скрипт SAPI не зависит от текста: текст читается из stdin, параметры - из переменных окружения.
+// Громкость применяется плеером, WAV пишется в stdout
+const SAPI_SCRIPT: &str = "$ErrorActionPreference = 'Stop'; \
+    [Console]::InputEncoding = [System.Text.Encoding]::UTF8; \
+    Add-Type -AssemblyName System.Speech; \
+    $text = [Console]::In.ReadToEnd(); \
+    $speak = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+    if ($env:JARVIS_TTS_VOICE) { $speak.SelectVoice($env:JARVIS_TTS_VOICE) }; \
+    $speak.Rate = [int]$env:JARVIS_TTS_RATE; \
+    $stream = New-Object System.IO.MemoryStream; \
+    $speak.SetOutputToWaveStream($stream); \
+    if ($env:JARVIS_TTS_SSML -eq '1') { $speak.SpeakSsml($text) } else { $speak.Speak($text) }; \
+    $bytes = $stream.ToArray(); \
+    $out = [Console]::OpenStandardOutput(); \
+    $out.Write($bytes, 0, $bytes.Length); \
+    $out.Flush()";
+
+/// Команда PowerShell для синтеза через SAPI (текст передается отдельно, через stdin)
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn sapi_command(ssml: bool, settings: &TtsConfig) -> Command {
+    let rate = (((settings.speed - 1.0) * 10.0) as i32).clamp(-10, 10);
+
+    let mut command = Command::new("powershell");
+    command
+        .args(["-NoProfile", "-NonInteractive", "-Command", SAPI_SCRIPT])
+        .env("JARVIS_TTS_RATE", rate.to_string())
+        .env("JARVIS_TTS_SSML", if ssml { "1" } else { "0" })
+        .env_remove("JARVIS_TTS_VOICE");
+
+    if settings.voice_id != "default" {
+        if is_valid_voice_id(&settings.voice_id) {
+            command.env("JARVIS_TTS_VOICE", &settings.voice_id);
+        } else {
+            warn!("Ignoring invalid SAPI voice name: {:?}", settings.voice_id);
+        }
+    }
+
+    command
+}
+
+/// Синтез через Windows SAPI (текст или SSML), WAV пишется в stdout
+#[cfg(target_os = "windows")]
+fn synthesize_sapi(input: &str, ssml: bool, settings: &TtsConfig) -> JarvisResult<Vec<u8>> {
+    capture_output(&mut sapi_command(ssml, settings), Some(input.as_bytes()))
+        .map_err(|e| JarvisError::AudioError(AudioError::PlaybackFailed(
+            format!("Windows TTS error: {}", e)
+        )))
+}
+
+/// Получение системных голосов
+fn get_system_voices() -> JarvisResult<Vec<String>> {
+    let mut voices = Vec::new();
+
+    #[cfg(target_os = "windows")]
+    {
+        let output = Command::new("powershell")
+            .args(["-Command",
+                "Add-Type -AssemblyName System.Speech; \
+                 $speak = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+                 $speak.GetInstalledVoices() | ForEach-Object { $_.VoiceInfo.Name }"
+            ])
+            .output()
+            .map_err(|e| JarvisError::AudioError(AudioError::InitializationFailed(
+                format!("Failed to get Windows voices: {}", e)
+            )))?;
+
+        if output.status.success() {
+            let voice_list = String::from_utf8_lossy(&output.stdout);
+            voices = voice_list.lines()
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty())
+                .collect();
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // espeak voices
+        if let Ok(output) = Command::new("espeak").arg("--voices").output() {
+            if output.status.success() {
+                let voice_list = String::from_utf8_lossy(&output.stdout);
+                for line in voice_list.lines().skip(1) { // skip header
+                    if let Some(voice_name) = line.split_whitespace().nth(3) {
+                        voices.push(voice_name.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    if voices.is_empty() {
+        voices.push("default".to_string());
+    }
+
+    Ok(voices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::process::hostile_inputs;
+    use std::path::Path;
+
+    #[test]
+    fn test_espeak_args_follow_style() {
+        let settings = TtsConfig::default();
+
+        let plain = espeak_args(&Style::default(), &settings);
+        assert_eq!(plain, ["--stdout", "-s", "150", "-p", "50", "-a", "100", "-v", "ru", "--stdin"]);
+
+        let style = Style { emphasis: true, language: Some("en-US".to_string()), rate: Some(2.0), ..Style::default() };
+        let styled = espeak_args(&style, &settings);
+        assert_eq!(styled, ["--stdout", "-s", "300", "-p", "60", "-a", "150", "-v", "en", "--stdin"]);
+    }
+
+    // Программа-заглушка: сохраняет argv и stdin рядом с собой, пишет "WAV" в stdout
+    #[cfg(unix)]
+    fn fake_program(dir: &Path, name: &str, exit_code: i32) -> std::path::PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join(name);
+        let script = format!(
+            "#!/bin/sh\n: > \"$0.args\"\nfor arg in \"$@\"; do printf '%s\\0' \"$arg\" >> \"$0.args\"; done\n\
+             cat > \"$0.stdin\"\nprintf WAV\nexit {exit_code}\n"
+        );
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    // argv, полученный заглушкой (аргументы разделены нулевым байтом)
+    #[cfg(unix)]
+    fn received_args(program: &Path) -> Vec<String> {
+        let args = std::fs::read_to_string(program.with_extension("args")).unwrap();
+        args.split_terminator('\0').map(str::to_string).collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hostile_text_reaches_only_stdin() {
+        let dir = tempfile::tempdir().unwrap();
+        let canary = dir.path().join("canary");
+        let settings = TtsConfig::default();
+        let expected_args = espeak_args(&Style::default(), &settings);
+
+        let espeak = fake_program(dir.path(), "espeak", 0);
+        let failing_espeak = fake_program(dir.path(), "failing-espeak", 1);
+        let text2wave = fake_program(dir.path(), "text2wave", 0);
+
+        for input in hostile_inputs(&canary) {
+            // espeak: аргументы постоянные, текст байт в байт приходит в stdin
+            let wav = synthesize_espeak(&espeak, &text2wave, &input, &Style::default(), &settings).unwrap();
+            assert_eq!(wav, b"WAV");
+            assert_eq!(received_args(&espeak), expected_args, "espeak args altered: {:?}", input);
+            assert_eq!(std::fs::read(espeak.with_extension("stdin")).unwrap(), input.as_bytes());
+
+            // fallback на festival: text2wave не получает аргументов
+            let wav = synthesize_espeak(&failing_espeak, &text2wave, &input, &Style::default(), &settings).unwrap();
+            assert_eq!(wav, b"WAV");
+            assert!(received_args(&text2wave).is_empty(), "text2wave got args: {:?}", input);
+            assert_eq!(std::fs::read(text2wave.with_extension("stdin")).unwrap(), input.as_bytes());
+
+            assert!(!canary.exists(), "input executed: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_sapi_script_is_constant() {
+        let settings = TtsConfig::default();
+
+        // скрипт SAPI постоянный, текст передается только через stdin
+        let command = sapi_command(true, &settings);
+        let args: Vec<&OsStr> = command.get_args().collect();
+        assert_eq!(args, ["-NoProfile", "-NonInteractive", "-Command", SAPI_SCRIPT]);
+    }
+
+    #[test]
+    fn test_hostile_voice_and_language_are_rejected() {
+        for input in hostile_inputs(Path::new("/tmp/jarvis-canary")) {
+            let settings = TtsConfig { voice_id: input.clone(), language: input.clone(), ..TtsConfig::default() };
+
+            let voice = sapi_command(false, &settings).get_envs()
+                .find(|(key, _)| *key == OsStr::new("JARVIS_TTS_VOICE"))
+                .and_then(|(_, value)| value.map(|value| value.to_os_string()));
+            assert!(voice.is_none(), "voice accepted: {:?}", input);
+
+            let args = espeak_args(&Style::default(), &settings);
+            assert!(["en", "ru"].contains(&args[8].as_str()), "language accepted: {:?}", input);
+        }
+
+        assert!(is_valid_voice_id("Microsoft Irina Desktop"));
+        assert!(is_valid_voice_id("IVONA 2 Maxim (Russian)"));
+    }
+}